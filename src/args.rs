@@ -1,11 +1,19 @@
 //! Command-line argument parsing for fs_cli-rs
 
-use crate::commands::{ColorMode, LogLevel};
-use crate::config::{AppConfig, FsCliConfig, ProfileConfig};
+use crate::audit::AuditLog;
+use crate::commands::{ColorMode, LogLevel, OutputFormat};
+use crate::config::{redact_sensitive_fields, AppConfig, FsCliConfig, LoadedConfig, ProfileConfig};
 use crate::esl_debug::EslDebugLevel;
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
+use regex::Regex;
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// API command timeout set by `--script-mode` when none is configured
+const SCRIPT_MODE_COMMAND_TIMEOUT_MS: u64 = 10000;
 
 /// Interactive FreeSWITCH CLI client
 #[derive(Parser, Debug, Clone)]
@@ -23,6 +31,30 @@ pub struct Args {
     #[arg(short = 'P', long)]
     pub port: Option<u16>,
 
+    /// Connect through a Unix domain socket instead of host/port
+    #[arg(long, value_name = "PATH")]
+    pub unix_socket: Option<PathBuf>,
+
+    /// Connect over TLS
+    #[arg(long)]
+    pub tls: bool,
+
+    /// Connect over TLS without verifying the server certificate
+    #[arg(long)]
+    pub tls_insecure: bool,
+
+    /// PEM CA certificate to trust instead of the system store
+    #[arg(long, value_name = "FILE")]
+    pub tls_ca: Option<String>,
+
+    /// PEM client certificate (requires --tls-key)
+    #[arg(long, value_name = "FILE")]
+    pub tls_cert: Option<String>,
+
+    /// PEM private key for --tls-cert
+    #[arg(long, value_name = "FILE")]
+    pub tls_key: Option<String>,
+
     /// ESL password
     #[arg(short = 'p', long)]
     pub password: Option<String>,
@@ -31,11 +63,19 @@ pub struct Args {
     #[arg(short, long)]
     pub user: Option<String>,
 
-    /// ESL debug level (0-7, higher = more verbose)
-    #[arg(short, long)]
-    pub debug: Option<u8>,
+    /// ESL debug level: 0-10 or none, error, warning, info, debug, debug5-debug10
+    #[arg(short, long, value_parser = parse_debug_level)]
+    pub debug: Option<EslDebugLevel>,
 
-    /// Color mode for output (never, tag, line)
+    /// Print debug messages as JSON lines, for jq or log ingestion
+    #[arg(long)]
+    pub debug_json: bool,
+
+    /// Also write debug messages to FILE
+    #[arg(long, value_name = "FILE")]
+    pub debug_output_file: Option<PathBuf>,
+
+    /// Color mode for output (never, tag, line, bright, auto)
     #[arg(long)]
     pub color: Option<ColorMode>,
 
@@ -43,6 +83,37 @@ pub struct Args {
     #[arg(short = 'x', action = clap::ArgAction::Append)]
     pub execute: Vec<String>,
 
+    /// Append each executed command, with a timestamp, to FILE
+    #[arg(long, value_name = "FILE")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Execute the commands in FILE (one per line, # comments) after any -x
+    #[arg(long, value_name = "FILE")]
+    pub execute_file: Option<PathBuf>,
+
+    /// Stop at the first -x command that fails, exiting with status 1
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// Scripting defaults: no color, quiet, raw output, --fail-fast,
+    /// --no-history and a 10 s command timeout. Explicit --color, --quiet and
+    /// --output-format win.
+    #[arg(long)]
+    pub script_mode: bool,
+
+    /// Stop -x and exit with status 1 when a response matches this regex
+    #[arg(long, value_name = "REGEX", value_parser = parse_regex)]
+    pub exit_on_error_pattern: Option<Regex>,
+
+    /// Run -x API commands concurrently, printing results in order
+    /// (runs sequentially with --fail-fast)
+    #[arg(long)]
+    pub parallel: bool,
+
+    /// Save active channels to a SQLite database and exit (after any -x)
+    #[arg(long, value_name = "FILE")]
+    pub export_db: Option<PathBuf>,
+
     /// History file path
     #[arg(long)]
     pub history_file: Option<PathBuf>,
@@ -59,6 +130,20 @@ pub struct Args {
     #[arg(short = 'R', long, num_args = 0..=1, default_missing_value = "true", action = clap::ArgAction::Set)]
     pub reconnect: Option<bool>,
 
+    /// Give up after this many failed connection attempts with --retry or
+    /// --reconnect
+    #[arg(long, visible_alias = "max-retries", value_name = "N")]
+    pub max_reconnect_attempts: Option<u32>,
+
+    /// Block until FreeSWITCH accepts the connection (for init scripts)
+    #[arg(long)]
+    pub wait_for_connection: bool,
+
+    /// Milliseconds between connection attempts (the first delay with
+    /// reconnect_backoff)
+    #[arg(long, value_name = "MS")]
+    pub retry_delay: Option<u64>,
+
     /// Subscribe to events on startup
     #[arg(long, num_args = 0..=1, default_missing_value = "true", action = clap::ArgAction::Set)]
     pub events: Option<bool>,
@@ -67,10 +152,34 @@ pub struct Args {
     #[arg(short = 'l', long)]
     pub log_level: Option<LogLevel>,
 
+    /// Show at most this many log lines per second, holding back the rest
+    #[arg(long, value_name = "LINES_PER_SEC", value_parser = clap::value_parser!(u32).range(1..))]
+    pub log_rate_limit: Option<u32>,
+
+    /// Prefix log lines with their source, e.g. (sofia.c:sofia_reg_parse_auth)
+    #[arg(long)]
+    pub show_log_source: bool,
+
     /// Disable automatic log subscription on startup
     #[arg(short = 'q', long, num_args = 0..=1, default_missing_value = "true", action = clap::ArgAction::Set)]
     pub quiet: Option<bool>,
 
+    /// Record repeated commands in history
+    #[arg(long)]
+    pub no_history_dedupe: bool,
+
+    /// Don't load or save the history file
+    #[arg(long)]
+    pub no_history: bool,
+
+    /// Offer channel details in UUID completion up to this many channels
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=10_000))]
+    pub max_uuid_complete: Option<u32>,
+
+    /// Prefix of environment variables overriding the profile (e.g. FS_CLI_HOST)
+    #[arg(long, value_name = "PREFIX", default_value = "FS_CLI")]
+    pub env_prefix: String,
+
     /// Configuration file path (if missing, creates from embedded example)
     #[arg(long)]
     pub config: Option<PathBuf>,
@@ -78,6 +187,73 @@ pub struct Args {
     /// List available configuration profiles
     #[arg(long)]
     pub list_profiles: bool,
+
+    /// Print a shell completion script (bash, zsh, fish, elvish) and exit
+    #[arg(long, value_name = "SHELL")]
+    pub generate_completions: Option<Shell>,
+
+    /// Print the effective configuration as YAML (credentials masked) and exit
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// Show FreeSWITCH commands instead of executing them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Never ask for confirmation before destructive commands
+    #[arg(long)]
+    pub no_confirm: bool,
+
+    /// Render API responses as raw, json, table or xml
+    #[arg(long, value_name = "FORMAT")]
+    pub output_format: Option<OutputFormat>,
+
+    /// Width for table output and word wrapping, at least 1 (default:
+    /// detect from terminal)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub output_width: Option<u16>,
+
+    /// Milliseconds to wait between commands run by /source
+    #[arg(long, value_name = "MS")]
+    pub source_delay: Option<u64>,
+
+    /// Allow /source within sourced files (at most 5 levels deep)
+    #[arg(long)]
+    pub allow_recursive_source: bool,
+
+    /// Do not wrap long output lines
+    #[arg(long)]
+    pub no_wrap: bool,
+
+    /// Do not set the terminal title
+    #[arg(long)]
+    pub no_title: bool,
+
+    /// Disconnect after this many milliseconds without input
+    #[arg(long, value_name = "MS")]
+    pub idle_timeout: Option<u64>,
+
+    /// Start an in-process mock ESL server and connect to it (0 = any free port)
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "0")]
+    pub mock: Option<u16>,
+
+    /// YAML file with canned API responses for --mock
+    #[arg(long, value_name = "PATH", requires = "mock")]
+    pub mock_fixtures: Option<PathBuf>,
+}
+
+/// Write the completion script for `shell`
+fn write_completions(shell: Shell, out: &mut dyn std::io::Write) {
+    let mut cmd = Args::command();
+    clap_complete::generate(shell, &mut cmd, "fs_cli", out);
+}
+
+fn parse_regex(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|e| e.to_string())
+}
+
+fn parse_debug_level(s: &str) -> Result<EslDebugLevel, String> {
+    EslDebugLevel::parse_number_or_name(s).map_err(|e| e.to_string())
 }
 
 impl Args {
@@ -85,9 +261,21 @@ impl Args {
     pub fn parse_and_merge() -> Result<AppConfig> {
         let args = Self::parse();
 
-        let config = FsCliConfig::load(
+        if let Some(shell) = args.generate_completions {
+            write_completions(shell, &mut std::io::stdout());
+            std::process::exit(0);
+        }
+
+        let profile_name = args
+            .profile
+            .as_deref()
+            .unwrap_or("default");
+
+        // Listing profiles should work even when the active one is invalid
+        let (config, config_path) = FsCliConfig::load(
             args.config
                 .clone(),
+            (!args.list_profiles).then_some(profile_name),
         )?;
 
         if args.list_profiles {
@@ -100,10 +288,6 @@ impl Args {
             std::process::exit(0);
         }
 
-        let profile_name = args
-            .profile
-            .as_deref()
-            .unwrap_or("default");
         let explicitly_named = args
             .profile
             .is_some();
@@ -122,7 +306,39 @@ impl Args {
             }
         };
 
+        app_config.profile_name = profile_name.to_string();
+        app_config.apply_env_overrides(&args.env_prefix)?;
         args.apply_to(&mut app_config)?;
+        if args.script_mode {
+            app_config
+                .debug
+                .debug_print(EslDebugLevel::Info, || "Script mode active".to_string());
+        }
+
+        if args.print_config {
+            let effective = FsCliConfig::new(
+                [(profile_name.to_string(), app_config.to_profile_config())]
+                    .into_iter()
+                    .collect(),
+            );
+            let yaml = serde_yaml::to_string(&effective)?;
+            print!("{}", redact_sensitive_fields(&yaml));
+            std::process::exit(0);
+        }
+
+        app_config.color = app_config
+            .color
+            .for_output(std::io::stdout().is_terminal());
+        app_config.audit_log = app_config
+            .command_audit_file
+            .as_deref()
+            .map(AuditLog::open)
+            .transpose()?;
+        app_config.loaded_config = config_path.map(|path| LoadedConfig {
+            path,
+            config: Arc::new(Mutex::new(config)),
+        });
+
         Ok(app_config)
     }
 
@@ -136,6 +352,24 @@ impl Args {
         if let Some(port) = self.port {
             config.port = port;
         }
+        if let Some(path) = &self.unix_socket {
+            config.unix_socket = Some(path.clone());
+        }
+        if self.tls || self.tls_insecure {
+            config.tls = true;
+        }
+        if self.tls_insecure {
+            config.tls_insecure = true;
+        }
+        if let Some(path) = &self.tls_ca {
+            config.tls_ca_cert = Some(path.clone());
+        }
+        if let Some(path) = &self.tls_cert {
+            config.tls_client_cert = Some(path.clone());
+        }
+        if let Some(path) = &self.tls_key {
+            config.tls_client_key = Some(path.clone());
+        }
         if let Some(password) = &self.password {
             config.password = password.clone();
         }
@@ -143,7 +377,13 @@ impl Args {
             config.user = Some(user.clone());
         }
         if let Some(debug) = self.debug {
-            config.debug = EslDebugLevel::from_u8(debug)?;
+            config.debug = debug;
+        }
+        if self.debug_json {
+            config.debug_structured = true;
+        }
+        if let Some(path) = &self.debug_output_file {
+            config.debug_output_file = Some(path.clone());
         }
         if let Some(color) = self.color {
             config.color = color;
@@ -151,6 +391,15 @@ impl Args {
         if let Some(history_file) = &self.history_file {
             config.history_file = Some(history_file.clone());
         }
+        if let Some(max) = self.max_uuid_complete {
+            config.max_auto_complete_uuid = max;
+        }
+        if self.no_history_dedupe {
+            config.history_dedupe = false;
+        }
+        if self.no_history {
+            config.no_history = true;
+        }
         if let Some(timeout) = self.timeout {
             config.timeout = timeout;
         }
@@ -160,28 +409,137 @@ impl Args {
         if let Some(reconnect) = self.reconnect {
             config.reconnect = reconnect;
         }
+        if let Some(attempts) = self.max_reconnect_attempts {
+            config.reconnect_max_attempts = Some(attempts);
+        }
+        if self.wait_for_connection {
+            config.wait_for_connection = true;
+        }
+        if let Some(delay) = self.retry_delay {
+            config.reconnect_delay_ms = delay;
+            config.reconnect_base_delay_ms = delay;
+        }
         if let Some(events) = self.events {
             config.events = events;
         }
         if let Some(log_level) = self.log_level {
             config.log_level = log_level;
         }
+        if let Some(limit) = self.log_rate_limit {
+            config.log_rate_limit = Some(limit);
+        }
+        if self.show_log_source {
+            config.show_log_source = true;
+        }
         if let Some(quiet) = self.quiet {
             config.quiet = quiet;
         }
+        if let Some(fmt) = self.output_format {
+            config.output_format = fmt;
+        }
+        if let Some(width) = self.output_width {
+            config.output_width = Some(width);
+        }
+        if let Some(ms) = self.idle_timeout {
+            config.idle_timeout_ms = Some(ms);
+        }
+        if let Some(port) = self.mock {
+            config.mock_port = Some(port);
+        }
+        if let Some(fixtures) = &self.mock_fixtures {
+            config.mock_fixtures = Some(fixtures.clone());
+        }
+        if self.dry_run {
+            config.dry_run = true;
+        }
+        if self.fail_fast {
+            config.execute_fail_fast = true;
+        }
+        if self.parallel {
+            config.execute_parallel = true;
+        }
+        if let Some(pattern) = &self.exit_on_error_pattern {
+            config.exit_on_error_pattern = Some(pattern.clone());
+        }
+        if self.no_confirm {
+            config.confirm_dangerous = false;
+        }
+        if self.no_wrap {
+            config.word_wrap = false;
+        }
+        if self.no_title {
+            config.set_terminal_title = false;
+        }
+        if let Some(delay) = self.source_delay {
+            config.inter_command_delay_ms = Some(delay);
+        }
+        if self.allow_recursive_source {
+            config.allow_recursive_source = true;
+        }
         config.execute = self
             .execute
             .clone();
+        config.check_execute_limit("-x")?;
+        if let Some(path) = &self.audit_log {
+            config.command_audit_file = Some(path.clone());
+        }
+        if let Some(path) = &self.execute_file {
+            config.add_execute_file(path)?;
+        }
+        if let Some(path) = &self.export_db {
+            config
+                .execute
+                .push(format!("/export-db {}", path.display()));
+        }
+        config.json_pretty = config
+            .execute
+            .is_empty();
+        if self.script_mode {
+            self.apply_script_mode(config);
+        }
         Ok(())
     }
+
+    /// `--script-mode` defaults, applied after the individual flags so those
+    /// still take precedence
+    fn apply_script_mode(&self, config: &mut AppConfig) {
+        if self
+            .color
+            .is_none()
+        {
+            config.color = ColorMode::Never;
+        }
+        if self
+            .quiet
+            .is_none()
+        {
+            config.quiet = true;
+        }
+        if self
+            .output_format
+            .is_none()
+        {
+            config.output_format = OutputFormat::Raw;
+        }
+        config.execute_fail_fast = true;
+        config.no_history = true;
+        config.json_pretty = false;
+        if config
+            .command_timeout_ms
+            .is_none()
+        {
+            config.command_timeout_ms = Some(SCRIPT_MODE_COMMAND_TIMEOUT_MS);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Args;
-    use crate::commands::{ColorMode, LogLevel};
+    use crate::commands::{ColorMode, LogLevel, OutputFormat};
     use crate::config::AppConfig;
     use crate::esl_debug::EslDebugLevel;
+    use clap::Parser;
     use std::collections::HashMap;
 
     fn make_args_no_overrides() -> Args {
@@ -189,20 +547,57 @@ mod tests {
             profile: None,
             host: None,
             port: None,
+            unix_socket: None,
+            tls: false,
+            tls_insecure: false,
+            tls_ca: None,
+            tls_cert: None,
+            tls_key: None,
             password: None,
             user: None,
             debug: None,
+            debug_json: false,
+            debug_output_file: None,
             color: None,
             execute: Vec::new(),
+            fail_fast: false,
+            script_mode: false,
+            exit_on_error_pattern: None,
+            parallel: false,
+            export_db: None,
+            execute_file: None,
+            audit_log: None,
             history_file: None,
+            no_history_dedupe: false,
+            no_history: false,
+            max_uuid_complete: None,
             timeout: None,
             retry: None,
             reconnect: None,
+            max_reconnect_attempts: None,
+            wait_for_connection: false,
+            retry_delay: None,
             events: None,
             log_level: None,
+            log_rate_limit: None,
+            show_log_source: false,
             quiet: None,
+            env_prefix: "FS_CLI".to_string(),
             config: None,
             list_profiles: false,
+            generate_completions: None,
+            print_config: false,
+            dry_run: false,
+            no_confirm: false,
+            output_format: None,
+            output_width: None,
+            source_delay: None,
+            allow_recursive_source: false,
+            no_wrap: false,
+            no_title: false,
+            idle_timeout: None,
+            mock: None,
+            mock_fixtures: None,
         }
     }
 
@@ -223,7 +618,63 @@ mod tests {
             quiet: true,
             macros: HashMap::new(),
             execute: Vec::new(),
-            max_auto_complete_uuid: 32,
+            max_auto_complete_uuid: 50,
+            cache_ttl_ms: 3000,
+            json_pretty: true,
+            output_format: OutputFormat::Raw,
+            output_width: None,
+            mock_port: None,
+            mock_fixtures: None,
+            dry_run: false,
+            execute_fail_fast: false,
+            exit_on_error_pattern: None,
+            execute_parallel: false,
+            inter_command_delay_ms: None,
+            allow_recursive_source: false,
+            debug_structured: false,
+            debug_output_file: None,
+            execute_max_concurrent: 10,
+            execute_max_commands: 1000,
+            show_channel_count: false,
+            channel_count_interval_ms: 5000,
+            word_wrap: true,
+            set_terminal_title: true,
+            dangerous_commands: vec!["hupall".to_string()],
+            confirm_dangerous: true,
+            reconnect_base_delay_ms: 1000,
+            reconnect_max_delay_ms: 30000,
+            reconnect_backoff: true,
+            reconnect_delay_ms: 1000,
+            reconnect_max_attempts: None,
+            wait_for_connection: false,
+            command_timeout_ms: None,
+            idle_timeout_ms: None,
+            prompt_template: None,
+            profile_name: "default".to_string(),
+            unix_socket: None,
+            per_profile_history: true,
+            no_history: false,
+            history_dedupe: true,
+            history_dedupe_mode: crate::config::DedupeMode::Adjacent,
+            history_max_size: None,
+            log_timestamps: false,
+            log_timestamp_format: None,
+            log_timezone: None,
+            log_dedupe: false,
+            log_dedupe_window: 10,
+            log_rate_limit: None,
+            log_burst_size: 100,
+            show_log_source: false,
+            log_source_max_len: 30,
+            log_colors: HashMap::new(),
+            tls: false,
+            tls_insecure: false,
+            tls_ca_cert: None,
+            tls_client_cert: None,
+            tls_client_key: None,
+            loaded_config: None,
+            command_audit_file: None,
+            audit_log: None,
         }
     }
 
@@ -274,6 +725,25 @@ mod tests {
         assert_eq!(config.port, 9021);
     }
 
+    #[test]
+    fn test_apply_to_tls_insecure_implies_tls() {
+        let mut config = base_app_config();
+        let mut args = make_args_no_overrides();
+        args.tls_insecure = true;
+        args.tls_ca = Some("/etc/ssl/fs-ca.pem".to_string());
+
+        args.apply_to(&mut config)
+            .unwrap();
+        assert!(config.tls);
+        assert!(config.tls_insecure);
+        assert_eq!(
+            config
+                .tls_ca_cert
+                .as_deref(),
+            Some("/etc/ssl/fs-ca.pem")
+        );
+    }
+
     #[test]
     fn test_apply_to_execute_always_replaced() {
         let mut config = base_app_config();
@@ -285,5 +755,156 @@ mod tests {
         args.apply_to(&mut config)
             .unwrap();
         assert_eq!(config.execute, vec!["status", "version"]);
+        assert!(!config.json_pretty);
+    }
+
+    #[test]
+    fn test_apply_to_retry_delay_sets_both_delays() {
+        let mut config = base_app_config();
+        let mut args = make_args_no_overrides();
+        args.retry_delay = Some(250);
+
+        args.apply_to(&mut config)
+            .unwrap();
+        assert_eq!(config.reconnect_delay_ms, 250);
+        assert_eq!(config.reconnect_base_delay_ms, 250);
+    }
+
+    #[test]
+    fn test_apply_to_script_mode_defaults() {
+        let mut config = base_app_config();
+        config.color = ColorMode::Line;
+        let mut args = make_args_no_overrides();
+        args.script_mode = true;
+        args.execute = vec!["status".to_string()];
+
+        args.apply_to(&mut config)
+            .unwrap();
+        assert_eq!(config.color, ColorMode::Never);
+        assert!(config.quiet);
+        assert!(config.execute_fail_fast);
+        assert!(config.no_history);
+        assert_eq!(config.output_format, OutputFormat::Raw);
+        assert_eq!(config.command_timeout_ms, Some(10000));
+    }
+
+    #[test]
+    fn test_apply_to_script_mode_explicit_flags_win() {
+        let mut config = base_app_config();
+        config.command_timeout_ms = Some(2500);
+        let mut args = make_args_no_overrides();
+        args.script_mode = true;
+        args.color = Some(ColorMode::Tag);
+        args.quiet = Some(false);
+        args.output_format = Some(OutputFormat::Json);
+
+        args.apply_to(&mut config)
+            .unwrap();
+        assert_eq!(config.color, ColorMode::Tag);
+        assert!(!config.quiet);
+        assert_eq!(config.output_format, OutputFormat::Json);
+        assert_eq!(config.command_timeout_ms, Some(2500));
+    }
+
+    #[test]
+    fn test_apply_to_execute_file_after_execute() {
+        let path = std::env::temp_dir().join(format!("fs_cli_execute_{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "# nightly checks\nstatus\n\nversion\n  sofia status  \nshow calls\nshow channels count\n",
+        )
+        .unwrap();
+        let mut config = base_app_config();
+        let mut args = make_args_no_overrides();
+        args.execute = vec!["reloadxml".to_string()];
+        args.execute_file = Some(path.clone());
+
+        let result = args.apply_to(&mut config);
+        std::fs::remove_file(&path).ok();
+        result.unwrap();
+        assert_eq!(
+            config.execute,
+            vec![
+                "reloadxml",
+                "status",
+                "version",
+                "sofia status",
+                "show calls",
+                "show channels count"
+            ]
+        );
+        assert!(!config.json_pretty);
+    }
+
+    #[test]
+    fn test_output_width_must_be_positive() {
+        assert!(Args::try_parse_from(["fs_cli", "--output-width", "0"]).is_err());
+        let args = Args::try_parse_from(["fs_cli", "--output-width", "80"]).unwrap();
+        assert_eq!(args.output_width, Some(80));
+    }
+
+    #[test]
+    fn test_apply_to_execute_file_limit_and_missing_file() {
+        let path =
+            std::env::temp_dir().join(format!("fs_cli_execute_max_{}.txt", std::process::id()));
+        std::fs::write(&path, "status\nversion\nuptime\n").unwrap();
+        let mut config = base_app_config();
+        config.execute_max_commands = 2;
+        let mut args = make_args_no_overrides();
+        args.execute_file = Some(path.clone());
+        let err = args
+            .apply_to(&mut config)
+            .unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(err
+            .to_string()
+            .contains("execute_max_commands (2)"));
+
+        args.execute_file = None;
+        args.execute = vec!["status".to_string(); 3];
+        let err = args
+            .apply_to(&mut config)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "-x brings the command count to 3, over execute_max_commands (2)"
+        );
+
+        args.execute = Vec::new();
+        args.execute_file = Some(std::path::PathBuf::from("/nonexistent/fs_cli_commands.txt"));
+        let err = args
+            .apply_to(&mut base_app_config())
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Cannot read execute file /nonexistent/fs_cli_commands.txt"));
+    }
+
+    #[test]
+    fn test_apply_to_export_db_runs_after_execute() {
+        let mut config = base_app_config();
+        let mut args = make_args_no_overrides();
+        args.execute = vec!["status".to_string()];
+        args.export_db = Some(std::path::PathBuf::from("/tmp/channels.db"));
+
+        args.apply_to(&mut config)
+            .unwrap();
+        assert_eq!(
+            config.execute,
+            vec!["status", "/export-db /tmp/channels.db"]
+        );
+    }
+
+    #[test]
+    fn test_generate_completions_for_each_shell() {
+        use clap_complete::Shell;
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::Elvish] {
+            let mut out = Vec::new();
+            super::write_completions(shell, &mut out);
+            let script = String::from_utf8(out).unwrap();
+            assert!(!script.is_empty(), "{} script is empty", shell);
+            assert!(script.contains("host"), "{} script lacks --host", shell);
+            assert!(script.contains("port"), "{} script lacks --port", shell);
+        }
     }
 }
@@ -0,0 +1,172 @@
+//! Append-only log of executed commands (`--audit-log`)
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, SecondsFormat, TimeZone};
+use std::borrow::Cow;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// Audit file opened once at startup and shared by every command processor
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    file: Arc<Mutex<File>>,
+}
+
+impl AuditLog {
+    /// Open `path` for appending, creating it if needed
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Cannot open audit log {}", path.display()))?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Append `command` with the current time, passwords masked. Failures
+    /// are reported but do not stop the command.
+    pub fn record(&self, command: &str) {
+        let entry = format_entry(&Local::now(), &redact_credentials(command));
+        let result = match self
+            .file
+            .lock()
+        {
+            Ok(mut file) => file.write_all(entry.as_bytes()),
+            Err(_) => return,
+        };
+        if let Err(e) = result {
+            warn!("Could not write to the audit log: {}", e);
+        }
+    }
+}
+
+/// Commands taking a password, with the number of words before it
+const PASSWORD_COMMANDS: &[(&str, usize)] = &[("/create-profile", 4), ("/connect", 3)];
+
+/// `command` with the password argument of `/create-profile` and `/connect`
+/// replaced by `****`
+fn redact_credentials(command: &str) -> Cow<'_, str> {
+    let trimmed = command.trim_start();
+    let Some(&(_, kept)) = PASSWORD_COMMANDS
+        .iter()
+        .find(|(name, _)| {
+            trimmed
+                .split_whitespace()
+                .next()
+                == Some(*name)
+        })
+    else {
+        return Cow::Borrowed(command);
+    };
+    let words: Vec<&str> = trimmed
+        .split_whitespace()
+        .collect();
+    if words.len() <= kept {
+        return Cow::Borrowed(command);
+    }
+    Cow::Owned(format!("{} ****", words[..kept].join(" ")))
+}
+
+/// Whether `command` carries a password, so it must stay out of history
+pub fn has_credentials(command: &str) -> bool {
+    matches!(redact_credentials(command), Cow::Owned(_))
+}
+
+/// `<RFC 3339 time>\t<command>\n`; newlines in the command are escaped so
+/// each entry stays on one line
+fn format_entry<Tz: TimeZone>(time: &DateTime<Tz>, command: &str) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    format!(
+        "{}\t{}\n",
+        time.to_rfc3339_opts(SecondsFormat::Millis, false),
+        command.replace('\n', "\\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Utc};
+
+    #[test]
+    fn format_entry_iso8601_tab_command() {
+        let time = FixedOffset::west_opt(5 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 3, 1, 14, 5, 9)
+            .unwrap();
+        assert_eq!(
+            format_entry(&time, "uuid_kill 0f2a"),
+            "2024-03-01T14:05:09.000-05:00\tuuid_kill 0f2a\n"
+        );
+        let utc = Utc
+            .with_ymd_and_hms(2024, 3, 1, 19, 5, 9)
+            .unwrap();
+        assert_eq!(
+            format_entry(&utc, "a\nb"),
+            "2024-03-01T19:05:09.000+00:00\ta\\nb\n"
+        );
+    }
+
+    #[test]
+    fn redact_credentials_masks_passwords() {
+        assert_eq!(
+            redact_credentials("/create-profile lab 10.0.0.5 8021 Clue Con"),
+            "/create-profile lab 10.0.0.5 8021 ****"
+        );
+        assert_eq!(
+            redact_credentials("/connect b 10.0.0.6:8021 secret"),
+            "/connect b 10.0.0.6:8021 ****"
+        );
+        assert_eq!(
+            redact_credentials("/connect b 10.0.0.6:8021"),
+            "/connect b 10.0.0.6:8021"
+        );
+        assert_eq!(redact_credentials("/connections"), "/connections");
+        assert_eq!(redact_credentials("show calls"), "show calls");
+        assert!(has_credentials("/connect b 10.0.0.6:8021 secret"));
+        assert!(!has_credentials("/connect b 10.0.0.6:8021"));
+    }
+
+    #[test]
+    fn record_appends_entries() {
+        let path = std::env::temp_dir().join(format!("fs_cli_audit_{}.log", std::process::id()));
+        std::fs::write(&path, "earlier\n").unwrap();
+        let log = AuditLog::open(&path).unwrap();
+        log.record("status");
+        log.clone()
+            .record("show calls");
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let lines: Vec<&str> = content
+            .lines()
+            .collect();
+        assert_eq!(lines[0], "earlier");
+        assert_eq!(lines.len(), 3);
+        for (line, command) in lines[1..]
+            .iter()
+            .zip(["status", "show calls"])
+        {
+            let (time, logged) = line
+                .split_once('\t')
+                .unwrap();
+            assert_eq!(logged, command);
+            assert!(DateTime::parse_from_rfc3339(time).is_ok(), "{}", time);
+        }
+    }
+
+    #[test]
+    fn open_reports_path() {
+        let err = AuditLog::open(Path::new("/nonexistent/dir/audit.log")).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("/nonexistent/dir/audit.log"));
+    }
+}
@@ -1,9 +1,24 @@
 //! Channel information management for enhanced UUID completion
 
-use crate::console_complete::Completion;
+use crate::console_complete::{parse_console_complete_response, Completion};
 use anyhow::{Context, Result};
 use freeswitch_esl_tokio::EslClient;
 use serde::Deserialize;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long `sofia status` results are reused between Tab presses
+const SOFIA_PROFILE_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// How long module lists are reused between Tab presses (modules rarely change)
+const MODULE_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// `cache_ttl_ms` when not configured
+pub const DEFAULT_CHANNEL_CACHE_TTL_MS: u64 = 3000;
+
+/// A completion list together with the time it was fetched
+type CachedList = Mutex<Option<(Vec<String>, Instant)>>;
 
 /// Channel information from FreeSWITCH JSON output
 #[derive(Debug, Clone, Deserialize)]
@@ -19,33 +34,395 @@ pub struct ChannelInfo {
     pub cid_num: String,
 }
 
+impl ChannelInfo {
+    /// Seconds since the channel was created; 0 if `created_epoch` is
+    /// missing or in the future
+    pub fn age_secs(&self, now: u64) -> u64 {
+        self.created_epoch
+            .parse::<u64>()
+            .map(|created| now.saturating_sub(created))
+            .unwrap_or(0)
+    }
+
+    /// `uuid cid_num <cid_name> [state]`, leaving out the caller ID number
+    /// and name when they are empty
+    pub fn display_name(&self) -> String {
+        let mut display = self
+            .uuid
+            .clone();
+        if !self
+            .cid_num
+            .is_empty()
+        {
+            display.push(' ');
+            display.push_str(&self.cid_num);
+        }
+        if !self
+            .cid_name
+            .is_empty()
+        {
+            display.push_str(&format!(" <{}>", self.cid_name));
+        }
+        display.push_str(&format!(" [{}]", self.state));
+        display
+    }
+
+    /// Text inserted when this channel is picked from a completion list
+    pub fn completion_key(&self) -> String {
+        format!("{} ", self.uuid)
+    }
+}
+
 /// Wrapper for FreeSWITCH JSON response
 #[derive(Debug, Deserialize)]
 pub struct ChannelsResponse {
     pub row_count: u32,
+    /// Absent from FreeSWITCH's reply when there are no channels
+    #[serde(default)]
     pub rows: Vec<ChannelInfo>,
 }
 
+/// SIP registration from `show registrations as json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistrationInfo {
+    #[serde(rename = "reg_user")]
+    pub user: String,
+    #[serde(rename = "realm")]
+    pub host: String,
+    /// Endpoint URL, e.g. `sofia/internal/sip:1000@192.0.2.10:5060`
+    #[serde(rename = "url")]
+    pub contact: String,
+    /// Expiry as a Unix timestamp string
+    pub expires: String,
+    #[serde(default)]
+    pub network_ip: String,
+    #[serde(default)]
+    pub network_port: String,
+}
+
+impl RegistrationInfo {
+    /// Seconds until expiry; negative once expired
+    pub fn expires_in(&self, now: u64) -> i64 {
+        let expires: i64 = self
+            .expires
+            .parse()
+            .unwrap_or(0);
+        expires - now as i64
+    }
+
+    /// Whether the registration came in on SIP profile `profile`
+    pub fn is_on_profile(&self, profile: &str) -> bool {
+        self.contact
+            .strip_prefix("sofia/")
+            .and_then(|rest| rest.split_once('/'))
+            .is_some_and(|(name, _)| name == profile)
+    }
+}
+
+/// Wrapper for `show registrations as json` (`rows` is absent when empty)
+#[derive(Debug, Deserialize)]
+pub struct RegistrationsResponse {
+    #[serde(default)]
+    pub rows: Vec<RegistrationInfo>,
+}
+
+/// Module interface from `show interfaces as json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct InterfaceInfo {
+    /// e.g. `endpoint`, `application`, `api`
+    #[serde(rename = "type")]
+    pub interface_type: String,
+    pub name: String,
+    /// Module registering the interface, e.g. `mod_sofia`
+    #[serde(default)]
+    pub ikey: String,
+}
+
+impl InterfaceInfo {
+    /// Whether `filter` names this interface's type or module (ignoring case)
+    pub fn matches(&self, filter: &str) -> bool {
+        self.interface_type
+            .eq_ignore_ascii_case(filter)
+            || self
+                .ikey
+                .eq_ignore_ascii_case(filter)
+    }
+}
+
+/// Wrapper for `show interfaces as json` (`rows` is absent when empty)
+#[derive(Debug, Deserialize)]
+pub struct InterfacesResponse {
+    #[serde(default)]
+    pub rows: Vec<InterfaceInfo>,
+}
+
+/// Scheduler task from `show tasks as json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaskInfo {
+    pub task_id: String,
+    /// `task_desc` in FreeSWITCH's reply
+    #[serde(alias = "task_desc")]
+    pub task_name: String,
+    #[serde(default)]
+    pub task_group: String,
+    /// Next run as a Unix timestamp string
+    pub task_runtime: String,
+    /// Repeat interval in seconds, when reported
+    #[serde(default)]
+    pub task_interval: String,
+}
+
+impl TaskInfo {
+    /// Seconds until the next run; negative when overdue
+    pub fn next_run_in(&self, now: u64) -> i64 {
+        let runtime: i64 = self
+            .task_runtime
+            .parse()
+            .unwrap_or(0);
+        runtime - now as i64
+    }
+}
+
+/// Wrapper for `show tasks as json` (`rows` is absent when empty)
+#[derive(Debug, Deserialize)]
+pub struct TasksResponse {
+    #[serde(default)]
+    pub rows: Vec<TaskInfo>,
+}
+
+/// Interface row from `show modules as json`; a module appears once per
+/// interface it registers
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModuleInfo {
+    #[serde(rename = "ikey")]
+    pub module_name: String,
+}
+
+/// Wrapper for `show modules as json` (`rows` is absent when empty)
+#[derive(Debug, Deserialize)]
+pub struct ModulesResponse {
+    #[serde(default)]
+    pub rows: Vec<ModuleInfo>,
+}
+
+/// Conference from `conference json_list`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConferenceInfo {
+    #[serde(rename = "conference_name")]
+    pub name: String,
+    #[serde(default)]
+    pub running: bool,
+}
+
+/// Names of running conferences in a `conference json_list` reply
+fn parse_conference_names(body: &str) -> Result<Vec<String>> {
+    let conferences: Vec<ConferenceInfo> = serde_json::from_str(body)
+        .context("Failed to parse JSON response for 'conference json_list'")?;
+    let mut names: Vec<String> = conferences
+        .into_iter()
+        .filter(|conference| conference.running)
+        .map(|conference| conference.name)
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Return the cached list if it is younger than `ttl`
+fn cached_list(cache: &CachedList, ttl: Duration) -> Option<Vec<String>> {
+    let guard = cache
+        .lock()
+        .ok()?;
+    match guard.as_ref() {
+        Some((items, fetched)) if fetched.elapsed() < ttl => Some(items.clone()),
+        _ => None,
+    }
+}
+
+fn store_list(cache: &CachedList, items: &[String]) {
+    if let Ok(mut guard) = cache.lock() {
+        *guard = Some((items.to_vec(), Instant::now()));
+    }
+}
+
+/// Extract profile names from `sofia status` output.
+///
+/// Rows are `<name> <type> <data> <state>`; gateways and aliases are skipped.
+fn parse_sofia_profiles(body: &str) -> Vec<String> {
+    let mut profiles: Vec<String> = body
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?;
+            (fields.next() == Some("profile")).then(|| name.to_string())
+        })
+        .collect();
+    profiles.sort();
+    profiles.dedup();
+    profiles
+}
+
+/// Channels whose UUID starts with `partial`, showing the full channel line,
+/// followed by those whose caller ID number or name starts with it
+/// (case-insensitive), shown as [`ChannelInfo::display_name`].
+fn uuid_completions(channels: Vec<ChannelInfo>, partial: &str) -> Vec<Completion> {
+    let (by_uuid, rest): (Vec<ChannelInfo>, Vec<ChannelInfo>) = channels
+        .into_iter()
+        .partition(|ch| {
+            ch.uuid
+                .starts_with(partial)
+        });
+    let partial_lower = partial.to_lowercase();
+
+    let uuid_matches = by_uuid
+        .into_iter()
+        .map(|ch| {
+            let display = if !ch
+                .cid_num
+                .is_empty()
+                || !ch
+                    .cid_name
+                    .is_empty()
+            {
+                format!(
+                    "{} {} {} ({}) <{}> {}",
+                    ch.uuid, ch.created, ch.name, ch.state, ch.cid_num, ch.cid_name
+                )
+            } else {
+                format!("{} {} {} ({})", ch.uuid, ch.created, ch.name, ch.state)
+            };
+            Completion::Uuid {
+                replacement: ch.completion_key(),
+                display,
+            }
+        });
+
+    let cid_matches = rest
+        .into_iter()
+        .filter(|ch| {
+            !partial.is_empty()
+                && [&ch.cid_num, &ch.cid_name]
+                    .iter()
+                    .any(|cid| {
+                        cid.to_lowercase()
+                            .starts_with(&partial_lower)
+                    })
+        })
+        .map(|ch| Completion::Uuid {
+            replacement: ch.completion_key(),
+            display: ch.display_name(),
+        });
+
+    uuid_matches
+        .chain(cid_matches)
+        .collect()
+}
+
 /// Channel information provider with smart fetching
 pub struct ChannelProvider {
     max_channels: u32,
+    /// Channels from the last UUID completion, reused for `cache_ttl`
+    cache: Mutex<Option<(Vec<ChannelInfo>, Instant)>>,
+    cache_ttl: Duration,
+    sofia_profiles: CachedList,
+    loaded_modules: CachedList,
+    available_modules: CachedList,
 }
 
 impl ChannelProvider {
-    /// Create new channel provider with configurable limit
-    pub fn new(max_channels: u32) -> Self {
-        Self { max_channels }
+    /// Create new channel provider with configurable limit and channel
+    /// cache lifetime
+    pub fn new(max_channels: u32, cache_ttl_ms: u64) -> Self {
+        Self {
+            max_channels,
+            cache: Mutex::new(None),
+            cache_ttl: Duration::from_millis(cache_ttl_ms),
+            sofia_profiles: Mutex::new(None),
+            loaded_modules: Mutex::new(None),
+            available_modules: Mutex::new(None),
+        }
     }
 
-    /// Get enhanced UUID completions with channel info.
+    /// Names of the SIP profiles on the running instance, for
+    /// `sofia profile <Tab>`. Cached briefly so repeated Tab presses don't
+    /// each hit the API.
+    pub async fn get_sofia_profile_completions(&self, client: &EslClient) -> Result<Vec<String>> {
+        if let Some(profiles) = cached_list(&self.sofia_profiles, SOFIA_PROFILE_CACHE_TTL) {
+            return Ok(profiles);
+        }
+        let body = self
+            .api_body(client, "sofia status")
+            .await?;
+        let profiles = parse_sofia_profiles(&body);
+        store_list(&self.sofia_profiles, &profiles);
+        Ok(profiles)
+    }
+
+    /// Get enhanced UUID completions with channel info for the word being
+    /// completed.
     ///
     /// Returns `None` if the channel count exceeds the configured limit (fall back
-    /// to default console_complete). Each `Completion::Uuid` carries the full
-    /// channel line as `display` and the bare UUID as `replacement`.
+    /// to default console_complete). Each `Completion::Uuid` carries the
+    /// [`ChannelInfo::completion_key`] as `replacement`; see [`uuid_completions`] for the matching.
     pub async fn get_uuid_completions(
         &self,
         client: &EslClient,
+        partial: &str,
     ) -> Result<Option<Vec<Completion>>> {
+        let Some(channels) = self
+            .cached_channels(|| self.fetch_completion_channels(client))
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(uuid_completions(channels, partial)))
+    }
+
+    /// Forget cached channels so the next completion fetches them again
+    pub fn invalidate_cache(&self) {
+        if let Ok(mut guard) = self
+            .cache
+            .lock()
+        {
+            *guard = None;
+        }
+    }
+
+    /// Channels from the cache while fresh, otherwise from `fetch`. A `None`
+    /// from `fetch` (too many channels) is not cached.
+    async fn cached_channels<F, Fut>(&self, fetch: F) -> Result<Option<Vec<ChannelInfo>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Option<Vec<ChannelInfo>>>>,
+    {
+        if let Ok(guard) = self
+            .cache
+            .lock()
+        {
+            if let Some((channels, fetched)) = guard.as_ref() {
+                if fetched.elapsed() < self.cache_ttl {
+                    return Ok(Some(channels.clone()));
+                }
+            }
+        }
+
+        let channels = fetch().await?;
+        if let Some(channels) = &channels {
+            if let Ok(mut guard) = self
+                .cache
+                .lock()
+            {
+                *guard = Some((channels.clone(), Instant::now()));
+            }
+        }
+        Ok(channels)
+    }
+
+    /// Channels for UUID completion, or `None` above `max_channels`
+    async fn fetch_completion_channels(
+        &self,
+        client: &EslClient,
+    ) -> Result<Option<Vec<ChannelInfo>>> {
         let count = self
             .get_channel_count(client)
             .await?;
@@ -62,42 +439,67 @@ impl ChannelProvider {
             return Ok(None);
         }
 
-        let channels = self
-            .get_channels(client)
-            .await?;
-
-        let completions = channels
-            .into_iter()
-            .map(|ch| {
-                let display = if !ch
-                    .cid_num
-                    .is_empty()
-                    || !ch
-                        .cid_name
-                        .is_empty()
-                {
-                    format!(
-                        "{} {} {} ({}) <{}> {}",
-                        ch.uuid, ch.created, ch.name, ch.state, ch.cid_num, ch.cid_name
-                    )
-                } else {
-                    format!("{} {} {} ({})", ch.uuid, ch.created, ch.name, ch.state)
-                };
-                Completion::Uuid {
-                    uuid: ch.uuid,
-                    display,
-                }
-            })
-            .collect();
-
-        Ok(Some(completions))
+        self.get_channels(client)
+            .await
+            .map(Some)
     }
 
-    async fn fetch_channels_json(
+    /// Module names for `load`/`unload`/`reload`.
+    ///
+    /// With `loaded_only` the names come from `show modules`; otherwise from
+    /// FreeSWITCH's own list of module files available to `load`.
+    pub async fn get_module_completions(
         &self,
         client: &EslClient,
-        command: &str,
-    ) -> Result<ChannelsResponse> {
+        loaded_only: bool,
+    ) -> Result<Vec<String>> {
+        let cache = if loaded_only {
+            &self.loaded_modules
+        } else {
+            &self.available_modules
+        };
+        if let Some(modules) = cached_list(cache, MODULE_CACHE_TTL) {
+            return Ok(modules);
+        }
+
+        let mut modules: Vec<String> = if loaded_only {
+            let body = self
+                .api_body(client, "show modules as json")
+                .await?;
+            serde_json::from_str::<ModulesResponse>(&body)
+                .context("Failed to parse JSON response for 'show modules as json'")?
+                .rows
+                .into_iter()
+                .map(|module| module.module_name)
+                .collect()
+        } else {
+            let body = self
+                .api_body(client, "console_complete load ")
+                .await?;
+            parse_console_complete_response(&body)
+                .into_iter()
+                .filter_map(|completion| match completion {
+                    Completion::Candidate(name) => Some(name),
+                    _ => None,
+                })
+                .collect()
+        };
+        modules.sort();
+        modules.dedup();
+        store_list(cache, &modules);
+        Ok(modules)
+    }
+
+    /// Names of active conferences, for `conference <Tab>`
+    pub async fn get_conference_completions(&self, client: &EslClient) -> Result<Vec<String>> {
+        let body = self
+            .api_body(client, "conference json_list")
+            .await?;
+        parse_conference_names(&body)
+    }
+
+    /// Run an API command and return its body, failing on `-ERR` replies
+    async fn api_body(&self, client: &EslClient, command: &str) -> Result<String> {
         let response = client
             .api(command)
             .await
@@ -113,10 +515,21 @@ impl ChannelProvider {
             );
         }
 
-        let body = response
+        Ok(response
             .body()
-            .unwrap_or_default();
-        serde_json::from_str::<ChannelsResponse>(body)
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    async fn fetch_channels_json(
+        &self,
+        client: &EslClient,
+        command: &str,
+    ) -> Result<ChannelsResponse> {
+        let body = self
+            .api_body(client, command)
+            .await?;
+        serde_json::from_str::<ChannelsResponse>(&body)
             .with_context(|| format!("Failed to parse JSON response for '{}'", command))
     }
 
@@ -146,3 +559,276 @@ impl ChannelProvider {
         Ok(channels)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_age() {
+        let channel = ChannelInfo {
+            uuid: "0f2a".to_string(),
+            created: String::new(),
+            created_epoch: "1000".to_string(),
+            name: String::new(),
+            state: String::new(),
+            cid_name: String::new(),
+            cid_num: String::new(),
+        };
+        assert_eq!(channel.age_secs(4600), 3600);
+        assert_eq!(channel.age_secs(500), 0);
+        let unknown = ChannelInfo {
+            created_epoch: String::new(),
+            ..channel
+        };
+        assert_eq!(unknown.age_secs(4600), 0);
+    }
+
+    fn registration(contact: &str, expires: &str) -> RegistrationInfo {
+        RegistrationInfo {
+            user: "1000".to_string(),
+            host: "example.com".to_string(),
+            contact: contact.to_string(),
+            expires: expires.to_string(),
+            network_ip: "192.0.2.10".to_string(),
+            network_port: "5060".to_string(),
+        }
+    }
+
+    #[test]
+    fn registration_profile_from_contact() {
+        let reg = registration("sofia/internal/sip:1000@192.0.2.10:5060", "0");
+        assert!(reg.is_on_profile("internal"));
+        assert!(!reg.is_on_profile("external"));
+        assert!(!registration("verto/1000", "0").is_on_profile("internal"));
+    }
+
+    #[test]
+    fn registration_expires_in() {
+        let reg = registration("sofia/internal/sip:1000@x", "1000");
+        assert_eq!(reg.expires_in(900), 100);
+        assert_eq!(reg.expires_in(1100), -100);
+    }
+
+    #[test]
+    fn interfaces_response_filter_by_type_or_module() {
+        let body = r#"{"row_count":3,"rows":[
+            {"type":"endpoint","name":"sofia","ikey":"mod_sofia"},
+            {"type":"api","name":"sofia","ikey":"mod_sofia"},
+            {"type":"endpoint","name":"loopback","ikey":"mod_loopback"}]}"#;
+        let parsed: InterfacesResponse = serde_json::from_str(body).unwrap();
+        let names = |filter: &str| -> Vec<String> {
+            parsed
+                .rows
+                .iter()
+                .filter(|i| i.matches(filter))
+                .map(|i| format!("{} {}", i.interface_type, i.name))
+                .collect()
+        };
+        assert_eq!(names("mod_sofia"), vec!["endpoint sofia", "api sofia"]);
+        assert_eq!(
+            names("ENDPOINT"),
+            vec!["endpoint sofia", "endpoint loopback"]
+        );
+        assert!(names("sofia").is_empty());
+    }
+
+    #[test]
+    fn tasks_response_next_run() {
+        let body = r#"{"row_count":2,"rows":[
+            {"task_id":"1","task_desc":"heartbeat","task_group":"core","task_runtime":"1060","task_sql_manager":"0","hostname":"pbx"},
+            {"task_id":"2","task_name":"limit_hash_cleanup","task_runtime":"900","task_interval":"600"}]}"#;
+        let parsed: TasksResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(parsed.rows[0].task_name, "heartbeat");
+        assert_eq!(parsed.rows[0].next_run_in(1000), 60);
+        assert_eq!(parsed.rows[1].task_interval, "600");
+        assert_eq!(parsed.rows[1].next_run_in(1000), -100);
+        let empty: TasksResponse = serde_json::from_str(r#"{"row_count":0}"#).unwrap();
+        assert!(empty
+            .rows
+            .is_empty());
+    }
+
+    #[test]
+    fn sofia_profiles_from_status() {
+        let body = "\
+                     Name\t   Type\t                                      Data\tState
+=================================================================================================
+            external\tprofile\t          sip:mod_sofia@192.0.2.1:5080\tRUNNING (0)
+external::example.com\tgateway\t              sip:joeuser@example.com\tNOREG
+         192.0.2.1\t  alias\t                                  internal\tALIASED
+            internal\tprofile\t          sip:mod_sofia@192.0.2.1:5060\tRUNNING (0)
+=================================================================================================
+2 profiles 1 alias
+";
+        assert_eq!(parse_sofia_profiles(body), vec!["external", "internal"]);
+    }
+
+    #[test]
+    fn modules_response_names() {
+        let body = r#"{"row_count":3,"rows":[
+            {"type":"api","name":"sofia","ikey":"mod_sofia","filename":"/usr/lib/freeswitch/mod/mod_sofia.so"},
+            {"type":"endpoint","name":"sofia","ikey":"mod_sofia","filename":"/usr/lib/freeswitch/mod/mod_sofia.so"},
+            {"type":"api","name":"conference","ikey":"mod_conference","filename":"/usr/lib/freeswitch/mod/mod_conference.so"}]}"#;
+        let parsed: ModulesResponse = serde_json::from_str(body).unwrap();
+        let names: Vec<_> = parsed
+            .rows
+            .iter()
+            .map(|m| {
+                m.module_name
+                    .as_str()
+            })
+            .collect();
+        assert_eq!(names, vec!["mod_sofia", "mod_sofia", "mod_conference"]);
+    }
+
+    #[test]
+    fn conference_names_running_only() {
+        let body = r#"[
+            {"conference_name":"3001","member_count":2,"running":true,"members":[]},
+            {"conference_name":"3000","member_count":0,"running":false,"members":[]},
+            {"conference_name":"sales","member_count":1,"running":true,"members":[]}]"#;
+        assert_eq!(parse_conference_names(body).unwrap(), vec!["3001", "sales"]);
+        assert!(parse_conference_names("-ERR No active conferences.").is_err());
+    }
+
+    #[test]
+    fn cached_list_expires() {
+        let cache: CachedList = Mutex::new(None);
+        assert!(cached_list(&cache, Duration::from_secs(5)).is_none());
+        store_list(&cache, &["internal".to_string()]);
+        assert_eq!(
+            cached_list(&cache, Duration::from_secs(5)),
+            Some(vec!["internal".to_string()])
+        );
+        assert!(cached_list(&cache, Duration::ZERO).is_none());
+    }
+
+    #[test]
+    fn registrations_response_without_rows() {
+        let parsed: RegistrationsResponse = serde_json::from_str(r#"{"row_count":0}"#).unwrap();
+        assert!(parsed
+            .rows
+            .is_empty());
+    }
+
+    fn channel(uuid: &str) -> ChannelInfo {
+        ChannelInfo {
+            uuid: uuid.to_string(),
+            created: String::new(),
+            created_epoch: "0".to_string(),
+            name: "sofia/internal/1000@example.com".to_string(),
+            state: "CS_EXECUTE".to_string(),
+            cid_name: String::new(),
+            cid_num: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn channel_cache_reused_within_ttl() {
+        let provider = ChannelProvider::new(32, 60_000);
+        let calls = std::cell::Cell::new(0);
+        let counter = &calls;
+        let fetch = move || async move {
+            counter.set(counter.get() + 1);
+            Ok(Some(vec![channel("a1")]))
+        };
+
+        let first = provider
+            .cached_channels(fetch)
+            .await
+            .unwrap()
+            .unwrap();
+        let second = provider
+            .cached_channels(fetch)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(calls.get(), 1);
+        assert_eq!(first[0].uuid, second[0].uuid);
+
+        provider.invalidate_cache();
+        provider
+            .cached_channels(fetch)
+            .await
+            .unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn channel_cache_skips_over_limit_result() {
+        let provider = ChannelProvider::new(32, 60_000);
+        let calls = std::cell::Cell::new(0);
+        let counter = &calls;
+        let fetch = move || async move {
+            counter.set(counter.get() + 1);
+            Ok(None)
+        };
+        assert!(provider
+            .cached_channels(fetch)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(provider
+            .cached_channels(fetch)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn uuid_completions_match_uuid_then_caller_id() {
+        let mut alice = channel("212a");
+        alice.cid_num = "5551000".to_string();
+        alice.cid_name = "Alice".to_string();
+        let mut bob = channel("9f00");
+        bob.cid_num = "2125551234".to_string();
+        bob.cid_name = "Bob".to_string();
+        let mut carol = channel("7e11");
+        carol.cid_num = "1001".to_string();
+        carol.cid_name = "Carol".to_string();
+
+        let uuids = |completions: Vec<Completion>| -> Vec<String> {
+            completions
+                .into_iter()
+                .filter_map(|c| match c {
+                    Completion::Uuid { replacement, .. } => Some(replacement),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let channels = vec![bob.clone(), alice.clone(), carol.clone()];
+        assert_eq!(
+            uuids(uuid_completions(channels.clone(), "212")),
+            vec!["212a ", "9f00 "]
+        );
+        assert_eq!(
+            uuids(uuid_completions(channels.clone(), "car")),
+            vec!["7e11 "]
+        );
+        assert_eq!(uuid_completions(channels.clone(), "").len(), 3);
+
+        match &uuid_completions(vec![bob], "212")[0] {
+            Completion::Uuid { display, .. } => {
+                assert_eq!(display, "9f00 2125551234 <Bob> [CS_EXECUTE]")
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn display_name_skips_empty_caller_id() {
+        let mut ch = channel("0f2a");
+        assert_eq!(ch.display_name(), "0f2a [CS_EXECUTE]");
+        ch.cid_num = "1000".to_string();
+        assert_eq!(ch.display_name(), "0f2a 1000 [CS_EXECUTE]");
+        ch.cid_name = "Alice".to_string();
+        assert_eq!(ch.display_name(), "0f2a 1000 <Alice> [CS_EXECUTE]");
+        ch.cid_num
+            .clear();
+        assert_eq!(ch.display_name(), "0f2a <Alice> [CS_EXECUTE]");
+        assert_eq!(ch.completion_key(), "0f2a ");
+    }
+}
@@ -1,20 +1,51 @@
 //! Command processing and execution for fs_cli-rs
 
+use crate::audit::AuditLog;
+use crate::channel_info::{
+    ChannelsResponse, InterfacesResponse, RegistrationsResponse, TasksResponse,
+};
+use crate::config::{AppConfig, FsCliConfig, LoadedConfig, ProfileConfig};
 use crate::esl_debug::EslDebugLevel;
+use crate::event_manager::{
+    format_job_done, job_uuid_from_reply, parse_event_type, register_job, EventSubscriptionTracker,
+    PendingJobs,
+};
 use crate::printer::Printer;
+use crate::table::{format_duration, format_table, now_epoch, Table};
 use anyhow::{anyhow, Error, Result};
 use colored::*;
-use freeswitch_esl_tokio::{EslClient, EslError};
+use freeswitch_esl_tokio::{EslClient, EslError, EslEventType, EventFormat};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::Path;
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+fn join_event_types(types: &[EslEventType]) -> String {
+    types
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
 /// Color mode for log display
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum ColorMode {
     Never,
     Tag,
     Line,
+    /// `Line` with bright colors, for dark themes that hide the normal ones
+    Bright,
+    /// `Line` on a terminal unless `NO_COLOR` is set, `Never` otherwise
+    #[default]
+    Auto,
 }
 
 impl FromStr for ColorMode {
@@ -28,20 +59,47 @@ impl FromStr for ColorMode {
             "never" => Ok(ColorMode::Never),
             "tag" => Ok(ColorMode::Tag),
             "line" => Ok(ColorMode::Line),
+            "bright" => Ok(ColorMode::Bright),
+            "auto" => Ok(ColorMode::Auto),
             _ => Err(format!(
-                "Invalid color mode: {}. Valid options: never, tag, line",
+                "Invalid color mode: {}. Valid options: never, tag, line, bright, auto",
                 s
             )),
         }
     }
 }
 
+/// Whether the user opted out of color via `NO_COLOR` (https://no-color.org)
+pub fn no_color_requested() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+impl ColorMode {
+    /// Downgrade to `Never` when output is not going to a terminal, so piped
+    /// output (`fs_cli -x ... | grep`) is not polluted with escape codes,
+    /// and resolve `Auto`.
+    pub fn for_output(self, is_terminal: bool) -> Self {
+        self.resolve(is_terminal, no_color_requested())
+    }
+
+    fn resolve(self, is_terminal: bool, no_color: bool) -> Self {
+        match self {
+            _ if !is_terminal => ColorMode::Never,
+            ColorMode::Auto if no_color => ColorMode::Never,
+            ColorMode::Auto => ColorMode::Line,
+            mode => mode,
+        }
+    }
+}
+
 impl std::fmt::Display for ColorMode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ColorMode::Never => write!(f, "never"),
             ColorMode::Tag => write!(f, "tag"),
             ColorMode::Line => write!(f, "line"),
+            ColorMode::Bright => write!(f, "bright"),
+            ColorMode::Auto => write!(f, "auto"),
         }
     }
 }
@@ -60,9 +118,51 @@ impl<'de> Deserialize<'de> for ColorMode {
     }
 }
 
+/// How API responses are rendered (`/output-format`)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    /// As received, re-indenting JSON when `json_pretty` is set
+    #[default]
+    Raw,
+    Json,
+    Table,
+    Xml,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s
+            .to_lowercase()
+            .as_str()
+        {
+            "raw" => Ok(OutputFormat::Raw),
+            "json" => Ok(OutputFormat::Json),
+            "table" => Ok(OutputFormat::Table),
+            "xml" => Ok(OutputFormat::Xml),
+            _ => Err(format!(
+                "Invalid output format: {}. Valid options: raw, json, table, xml",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Raw => write!(f, "raw"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Table => write!(f, "table"),
+            OutputFormat::Xml => write!(f, "xml"),
+        }
+    }
+}
+
 /// FreeSWITCH log levels
 #[derive(
-    Debug, Clone, Copy, PartialEq, strum::EnumString, strum::IntoStaticStr, strum::EnumIter,
+    Debug, Clone, Copy, PartialEq, Default, strum::EnumString, strum::IntoStaticStr, strum::EnumIter,
 )]
 #[strum(serialize_all = "lowercase", ascii_case_insensitive)]
 #[repr(u8)]
@@ -76,6 +176,7 @@ pub enum LogLevel {
     Warning = 4,
     Notice = 5,
     Info = 6,
+    #[default]
     Debug = 7,
     Debug1 = 8,
     Debug2 = 9,
@@ -96,6 +197,35 @@ impl LogLevel {
         self.into()
     }
 
+    /// Map the numeric `Log-Level` header of an ESL log event.
+    ///
+    /// 0-7 are the syslog-style levels and 8-17 follow this enum's
+    /// Debug1-Debug10. FreeSWITCH's own DEBUG1-DEBUG10 values (101-110) are
+    /// accepted too.
+    pub fn from_number(n: u32) -> Option<LogLevel> {
+        use strum::IntoEnumIterator;
+        let n = match n {
+            101..=110 => n - 93,
+            _ => n,
+        };
+        LogLevel::iter().find(|level| *level != LogLevel::NoLog && *level as u32 == n)
+    }
+
+    /// Level tag as FreeSWITCH prints it in log lines, e.g. `WARNING`
+    pub fn tag(&self) -> &'static str {
+        match self {
+            LogLevel::Console => "CONSOLE",
+            LogLevel::Alert => "ALERT",
+            LogLevel::Crit => "CRIT",
+            LogLevel::Err => "ERR",
+            LogLevel::Warning => "WARNING",
+            LogLevel::Notice => "NOTICE",
+            LogLevel::Info => "INFO",
+            LogLevel::NoLog => "NOLOG",
+            _ => "DEBUG",
+        }
+    }
+
     /// Get all available log levels for help text
     pub fn all_variants() -> &'static [LogLevel] {
         use strum::IntoEnumIterator;
@@ -156,26 +286,1004 @@ pub async fn set_log_level(client: &EslClient, level: LogLevel) -> Result<Option
     }
 }
 
+/// Calls and channels older than this many seconds are highlighted in yellow
+const LONG_CALL_SECS: u64 = 3600;
+/// Calls and channels older than this many seconds are highlighted in red
+const VERY_LONG_CALL_SECS: u64 = 4 * 3600;
+
+/// Generic `show <thing> as json` response: rows of string columns.
+///
+/// FreeSWITCH omits `rows` entirely when there are none.
+#[derive(Debug, Deserialize)]
+struct ShowRows {
+    #[serde(default)]
+    rows: Vec<HashMap<String, String>>,
+}
+
+/// DTMF duration used when `/dtmf` is given none, in milliseconds
+const DEFAULT_DTMF_DURATION_MS: u32 = 500;
+
+/// Whether `s` looks like a FreeSWITCH channel UUID (36 chars of lowercase
+/// hex and dashes, as FreeSWITCH prints them)
+pub fn is_uuid(s: &str) -> bool {
+    s.len() == 36
+        && s.chars()
+            .all(|c| matches!(c, '0'..='9' | 'a'..='f' | '-'))
+}
+
+/// Build the `uuid_send_dtmf` command for `/dtmf <uuid> <digits> [duration_ms]`
+fn build_dtmf_command(args: &[&str]) -> Result<String> {
+    let (uuid, digits, duration) = match args {
+        [uuid, digits] => (*uuid, *digits, DEFAULT_DTMF_DURATION_MS),
+        [uuid, digits, duration] => {
+            let duration = duration
+                .parse::<u32>()
+                .ok()
+                .filter(|d| *d > 0)
+                .ok_or_else(|| anyhow!("Invalid DTMF duration '{}' (milliseconds)", duration))?;
+            (*uuid, *digits, duration)
+        }
+        _ => return Err(anyhow!("Usage: /dtmf <uuid> <digits> [duration_ms]")),
+    };
+    if !is_uuid(uuid) {
+        return Err(anyhow!("Invalid channel UUID: {}", uuid));
+    }
+    if !digits
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '*' | '#' | 'A'..='D'))
+    {
+        return Err(anyhow!(
+            "Invalid DTMF digits '{}' (allowed: 0-9 * # A-D)",
+            digits
+        ));
+    }
+    Ok(format!("uuid_send_dtmf {} {}@{}", uuid, digits, duration))
+}
+
+/// One row of `conference <name> list` (fields are `;`-delimited, flags `|`-delimited)
+#[derive(Debug, PartialEq)]
+struct ConferenceMember {
+    id: String,
+    cid_name: String,
+    cid_num: String,
+    flags: Vec<String>,
+    energy: String,
+}
+
+impl ConferenceMember {
+    /// Parse `id;channel;uuid;cid_name;cid_num;flags;vol_in;vol_out;energy`
+    fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line
+            .split(';')
+            .collect();
+        if fields.len() < 9 {
+            return None;
+        }
+        Some(Self {
+            id: fields[0].to_string(),
+            cid_name: fields[3].to_string(),
+            cid_num: fields[4].to_string(),
+            flags: fields[5]
+                .split('|')
+                .filter(|f| !f.is_empty())
+                .map(str::to_string)
+                .collect(),
+            energy: fields[8].to_string(),
+        })
+    }
+
+    fn has_flag(&self, flag: &str) -> bool {
+        self.flags
+            .iter()
+            .any(|f| f == flag)
+    }
+
+    fn has_floor(&self) -> bool {
+        self.has_flag("floor")
+    }
+
+    /// Summarize the audio flags as a single state word
+    fn state(&self) -> &'static str {
+        if self.has_flag("talking") {
+            "talking"
+        } else if !self.has_flag("speak") {
+            "muted"
+        } else if !self.has_flag("hear") {
+            "deaf"
+        } else {
+            "listening"
+        }
+    }
+}
+
+/// Parse the member lines of `conference <name> list`
+fn parse_conference_members(body: &str) -> Vec<ConferenceMember> {
+    body.lines()
+        .filter_map(ConferenceMember::parse)
+        .collect()
+}
+
+/// Parse `uuid_dump` output into (key, value) pairs sorted by group then key.
+///
+/// Lines are `Key: Value` or `key=value`; only the first separator splits, so
+/// values may contain either.
+fn parse_uuid_dump(body: &str) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = body
+        .lines()
+        .filter_map(|line| {
+            let split = line.find([':', '='])?;
+            let key = line[..split].trim();
+            if key.is_empty() {
+                return None;
+            }
+            Some((
+                key.to_string(),
+                line[split + 1..]
+                    .trim()
+                    .to_string(),
+            ))
+        })
+        .collect();
+    vars.sort_by(|(a, _), (b, _)| {
+        channel_var_group(a)
+            .cmp(&channel_var_group(b))
+            .then_with(|| a.cmp(b))
+    });
+    vars
+}
+
+/// Group of a channel variable: its first word, ignoring the `variable_`
+/// prefix, so `variable_sip_from_user` and `Caller-ANI` group as `sip` and
+/// `caller`
+fn channel_var_group(key: &str) -> String {
+    let name = key
+        .strip_prefix("variable_")
+        .unwrap_or(key);
+    match name.split_once(['_', '-']) {
+        Some((group, _)) if !group.is_empty() => group.to_ascii_lowercase(),
+        _ => "other".to_string(),
+    }
+}
+
+/// Color a channel variable value that looks like a UUID, an IP address or a
+/// number (durations, epochs, counters)
+fn highlight_channel_value(value: &str) -> String {
+    static PATTERNS: std::sync::OnceLock<[Regex; 3]> = std::sync::OnceLock::new();
+    let [uuid, ip, number] = PATTERNS.get_or_init(|| {
+        [
+            Regex::new(
+                r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+            )
+            .expect("valid UUID pattern"),
+            Regex::new(r"^(\d{1,3}\.){3}\d{1,3}(:\d+)?$").expect("valid IP pattern"),
+            Regex::new(r"^\d+(\.\d+)?$").expect("valid number pattern"),
+        ]
+    });
+    if uuid.is_match(value) {
+        value
+            .magenta()
+            .to_string()
+    } else if ip.is_match(value) {
+        value
+            .cyan()
+            .to_string()
+    } else if number.is_match(value) {
+        value
+            .yellow()
+            .to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Re-indent an API response body that is a JSON document.
+///
+/// Returns `None` when the body is not JSON so the caller shows it verbatim.
+pub fn pretty_json(body: &str) -> Option<String> {
+    let trimmed = body.trim();
+    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+        return None;
+    }
+    let value = match serde_json::from_str::<serde_json::Value>(trimmed) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!("response looks like JSON but failed to parse: {}", e);
+            return None;
+        }
+    };
+    serde_json::to_string_pretty(&value).ok()
+}
+
+/// Render an API response in `fmt`. Responses that cannot be converted are
+/// returned unchanged.
+pub fn transform_output(
+    body: &str,
+    fmt: OutputFormat,
+    color_mode: ColorMode,
+    max_width: usize,
+) -> String {
+    let transformed = match fmt {
+        OutputFormat::Raw => return body.to_string(),
+        OutputFormat::Json => pretty_json(body).or_else(|| {
+            show_rows_to_json(body).and_then(|rows| serde_json::to_string_pretty(&rows).ok())
+        }),
+        OutputFormat::Table => json_to_table(body)
+            .or_else(|| parse_show_csv(body))
+            .map(|table| table.render(color_mode, Some(max_width))),
+        OutputFormat::Xml => indent_xml(body),
+    };
+    transformed.unwrap_or_else(|| {
+        debug!("cannot render response as {}, showing it raw", fmt);
+        body.to_string()
+    })
+}
+
+/// Split the CSV output of `show <what>` (header line, rows, blank line,
+/// `N total.`) into headers and rows
+fn parse_show_csv(body: &str) -> Option<Table> {
+    let data: String = body
+        .lines()
+        .take_while(|line| {
+            !line
+                .trim()
+                .is_empty()
+        })
+        .map(|line| format!("{}\n", line))
+        .collect();
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(false)
+        .from_reader(data.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .ok()?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    if headers.len() < 2 {
+        return None;
+    }
+    let rows = reader
+        .records()
+        .map(|record| {
+            record.map(|r| {
+                r.iter()
+                    .map(str::to_string)
+                    .collect()
+            })
+        })
+        .collect::<std::result::Result<Vec<Vec<String>>, _>>()
+        .ok()?;
+    Some(Table { headers, rows })
+}
+
+fn show_rows_to_json(body: &str) -> Option<serde_json::Value> {
+    let Table { headers, rows } = parse_show_csv(body)?;
+    Some(serde_json::Value::Array(
+        rows.into_iter()
+            .map(|row| {
+                serde_json::Value::Object(
+                    headers
+                        .iter()
+                        .cloned()
+                        .zip(
+                            row.into_iter()
+                                .map(serde_json::Value::String),
+                        )
+                        .collect(),
+                )
+            })
+            .collect(),
+    ))
+}
+
+/// A JSON array of objects, or an object with a `rows` array (`show ... as
+/// json`), as a table with one column per key
+fn json_to_table(body: &str) -> Option<Table> {
+    let value: serde_json::Value = serde_json::from_str(body.trim()).ok()?;
+    let rows = match &value {
+        serde_json::Value::Array(rows) => rows,
+        serde_json::Value::Object(map) => map
+            .get("rows")?
+            .as_array()?,
+        _ => return None,
+    };
+    let mut headers: Vec<String> = Vec::new();
+    for row in rows {
+        for key in row
+            .as_object()?
+            .keys()
+        {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    let rows = rows
+        .iter()
+        .map(|row| {
+            headers
+                .iter()
+                .map(|h| match row.get(h) {
+                    Some(serde_json::Value::String(s)) => s.clone(),
+                    Some(serde_json::Value::Null) | None => String::new(),
+                    Some(other) => other.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    Some(Table { headers, rows })
+}
+
+/// Re-indent an XML document, one element per line. `None` when the body is
+/// not XML or its tags do not nest.
+fn indent_xml(body: &str) -> Option<String> {
+    let trimmed = body.trim();
+    if !trimmed.starts_with('<') {
+        return None;
+    }
+    let mut out = String::new();
+    let mut open: Vec<String> = Vec::new();
+    let mut rest = trimmed;
+    while !rest.is_empty() {
+        let indent = "  ".repeat(open.len());
+        if let Some(tag_body) = rest.strip_prefix('<') {
+            let end = tag_body.find('>')?;
+            let tag = &tag_body[..end];
+            rest = &tag_body[end + 1..];
+            if let Some(name) = tag.strip_prefix('/') {
+                if open.pop()? != name.trim() {
+                    return None;
+                }
+                out.push_str(&"  ".repeat(open.len()));
+            } else {
+                out.push_str(&indent);
+                let self_closing =
+                    tag.ends_with('/') || tag.starts_with('?') || tag.starts_with('!');
+                if !self_closing {
+                    let name = tag
+                        .split_whitespace()
+                        .next()?;
+                    open.push(name.to_string());
+                    // Keep `<a>text</a>` on one line
+                    if let Some(close) = rest.find('<') {
+                        let text = rest[..close].trim();
+                        let closing = format!("</{}>", name);
+                        if !text.contains('>') && rest[close..].starts_with(&closing) {
+                            out.push_str(&format!("<{}>{}{}\n", tag, text, closing));
+                            open.pop();
+                            rest = &rest[close + closing.len()..];
+                            rest = rest.trim_start();
+                            continue;
+                        }
+                    }
+                }
+            }
+            out.push_str(&format!("<{}>\n", tag));
+        } else {
+            let end = rest
+                .find('<')
+                .unwrap_or(rest.len());
+            let text = rest[..end].trim();
+            if !text.is_empty() {
+                out.push_str(&format!("{}{}\n", indent, text));
+            }
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    if !open.is_empty() {
+        return None;
+    }
+    Some(
+        out.trim_end()
+            .to_string(),
+    )
+}
+
+/// `hupall <CAUSE>`, `NORMAL_CLEARING` unless a cause is given
+pub fn hupall_command(cause: Option<&str>) -> String {
+    format!(
+        "hupall {}",
+        cause
+            .unwrap_or("NORMAL_CLEARING")
+            .to_uppercase()
+    )
+}
+
+/// What `show modules` reports about one module
+#[derive(Debug, Default, PartialEq)]
+struct ModuleSummary {
+    path: Option<String>,
+    apis: Vec<String>,
+    applications: Vec<String>,
+    /// Other interfaces as `type name`, e.g. `endpoint sofia`
+    other: Vec<String>,
+}
+
+/// Collect the interfaces `module` registers from `show modules` rows
+fn summarize_module(interfaces: &[HashMap<String, String>], module: &str) -> ModuleSummary {
+    let mut summary = ModuleSummary::default();
+    let field = |row: &HashMap<String, String>, key: &str| {
+        row.get(key)
+            .cloned()
+            .unwrap_or_default()
+    };
+    for row in interfaces
+        .iter()
+        .filter(|row| {
+            row.get("ikey")
+                .map(String::as_str)
+                == Some(module)
+        })
+    {
+        if summary
+            .path
+            .is_none()
+        {
+            summary.path = row
+                .get("filename")
+                .filter(|f| !f.is_empty())
+                .cloned();
+        }
+        let name = field(row, "name");
+        match field(row, "type").as_str() {
+            "api" => summary
+                .apis
+                .push(name),
+            "application" => summary
+                .applications
+                .push(name),
+            other => summary
+                .other
+                .push(format!("{} {}", other, name)),
+        }
+    }
+    summary
+        .apis
+        .sort();
+    summary
+        .applications
+        .sort();
+    summary
+        .other
+        .sort();
+    summary
+}
+
+/// Lines of `old` missing from `new` (`-`) followed by lines of `new`
+/// missing from `old` (`+`), each in document order. Repeated lines are
+/// counted, not matched by position.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<(char, &'a str)> {
+    fn only_in<'a>(side: &'a str, other: &'a str) -> impl Iterator<Item = &'a str> {
+        let mut remaining: HashMap<&str, usize> = HashMap::new();
+        for line in other.lines() {
+            *remaining
+                .entry(line)
+                .or_default() += 1;
+        }
+        side.lines()
+            .filter(move |line| match remaining.get_mut(line) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            })
+    }
+    only_in(old, new)
+        .map(|line| ('-', line))
+        .chain(only_in(new, old).map(|line| ('+', line)))
+        .collect()
+}
+
+/// Registrations expiring sooner than this are highlighted
+const REGISTRATION_EXPIRING_SECS: i64 = 300;
+
+/// Time until a registration expires as `HH:MM:SS`, or `expired`
+fn format_expires_in(secs: i64) -> String {
+    if secs < 0 {
+        "expired".to_string()
+    } else {
+        format_duration(secs as u64)
+    }
+}
+
+/// Time until a task runs as `+Xm Ys`, or `overdue by Xm Ys`
+fn format_next_run(secs: i64) -> String {
+    let abs = secs.unsigned_abs();
+    if secs < 0 {
+        format!("overdue by {}m {}s", abs / 60, abs % 60)
+    } else {
+        format!("+{}m {}s", abs / 60, abs % 60)
+    }
+}
+
+/// Two-row table of this client's version and the server's `version`
+/// reply, with the component names in bold cyan unless colors are off
+fn format_versions(server: &str, color_mode: ColorMode, max_width: usize) -> String {
+    let rows: Vec<Vec<String>> = [
+        ("fs_cli-rs", env!("CARGO_PKG_VERSION").to_string()),
+        (
+            "FreeSWITCH",
+            server
+                .trim()
+                .to_string(),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, version)| {
+        let name = if color_mode == ColorMode::Never {
+            name.to_string()
+        } else {
+            name.cyan()
+                .bold()
+                .to_string()
+        };
+        vec![name, version]
+    })
+    .collect();
+    format_table(&["Component", "Version"], &rows, color_mode, max_width)
+}
+
+/// `[name, description]` of the `show api` rows whose name contains
+/// `filter` (ignoring case), sorted by name
+fn filter_api_commands(
+    commands: &[HashMap<String, String>],
+    filter: Option<&str>,
+) -> Vec<Vec<String>> {
+    let filter = filter.map(str::to_lowercase);
+    let mut rows: Vec<Vec<String>> = commands
+        .iter()
+        .filter_map(|command| {
+            let name = command.get("name")?;
+            if filter
+                .as_ref()
+                .is_some_and(|f| {
+                    !name
+                        .to_lowercase()
+                        .contains(f.as_str())
+                })
+            {
+                return None;
+            }
+            Some(vec![
+                name.clone(),
+                command
+                    .get("description")
+                    .cloned()
+                    .unwrap_or_default(),
+            ])
+        })
+        .collect();
+    rows.sort();
+    rows.dedup();
+    rows
+}
+
+/// Emphasize every match of `pattern` in `message`, preceded by a match count.
+///
+/// Without color the text is left untouched and only the count is added.
+fn apply_search(message: &str, pattern: &Regex, color: bool) -> String {
+    let count = pattern
+        .find_iter(message)
+        .count();
+    let body = if color {
+        pattern
+            .replace_all(message, |caps: &regex::Captures| {
+                caps[0]
+                    .yellow()
+                    .bold()
+                    .to_string()
+            })
+            .into_owned()
+    } else {
+        message.to_string()
+    };
+    format!("({} matches)\n{}", count, body)
+}
+
+/// Colors accepted by `/highlight`; the first is the default
+const HIGHLIGHT_COLORS: &[&str] = &["cyan", "red", "green", "yellow", "magenta", "blue"];
+
+/// Color every match of the `/highlight` patterns in `message`. Where
+/// matches overlap, the one starting first wins, then the older pattern.
+fn apply_highlights(message: &str, patterns: &[(Regex, &'static str)]) -> String {
+    let mut spans: Vec<(usize, usize, &'static str)> = patterns
+        .iter()
+        .flat_map(|(re, color)| {
+            re.find_iter(message)
+                .filter(|m| !m.is_empty())
+                .map(move |m| (m.start(), m.end(), *color))
+        })
+        .collect();
+    // Stable: equal starts keep pattern order
+    spans.sort_by_key(|(start, _, _)| *start);
+
+    let mut out = String::with_capacity(message.len());
+    let mut pos = 0;
+    for (start, end, color) in spans {
+        if start < pos {
+            continue;
+        }
+        out.push_str(&message[pos..start]);
+        out.push_str(
+            &message[start..end]
+                .color(color)
+                .bold()
+                .to_string(),
+        );
+        pos = end;
+    }
+    out.push_str(&message[pos..]);
+    out
+}
+
+/// `/ping` count when none is given
+const DEFAULT_PING_COUNT: u32 = 5;
+
+/// Per-ping timeout when `command_timeout_ms` is not configured
+const DEFAULT_PING_TIMEOUT_MS: u64 = 5000;
+
+/// `freeswitch@<host:port>`, naming the server `/ping` talks to
+fn ping_target(host: &str, port: u16) -> String {
+    format!("freeswitch@{}", crate::format_host_port(host, port))
+}
+
+/// Summary line for `/ping`, ping(8) style. Packet loss is only shown when
+/// some requests went unanswered.
+fn format_ping_summary(target: &str, sent: u32, rtts: &[Duration]) -> String {
+    let received = rtts.len() as u32;
+    let mut summary = format!(
+        "PING {}: {} packets sent, {} received",
+        target, sent, received
+    );
+    if received < sent {
+        let loss = (sent - received) as f64 * 100.0 / sent as f64;
+        summary.push_str(&format!(", {:.0}% packet loss", loss));
+    }
+    if let (Some(min), Some(max)) = (
+        rtts.iter()
+            .min(),
+        rtts.iter()
+            .max(),
+    ) {
+        let avg = rtts
+            .iter()
+            .sum::<Duration>()
+            / received;
+        summary.push_str(&format!(
+            ", min/avg/max = {:.2}ms/{:.2}ms/{:.2}ms",
+            min.as_secs_f64() * 1000.0,
+            avg.as_secs_f64() * 1000.0,
+            max.as_secs_f64() * 1000.0
+        ));
+    }
+    summary
+}
+
+/// Client-side commands that still run under `--dry-run`: they only change
+/// how this session displays output, not FreeSWITCH state
+const DRY_RUN_PASSTHROUGH: &[&str] = &[
+    "/log",
+    "/search",
+    "/nosearch",
+    "/highlight",
+    "/nohighlight",
+    "/source",
+    "/schedule",
+    "/unschedule-all",
+    "/macros",
+    "/set-macro",
+    "/clear-history",
+    "/history-size",
+    "/output-format",
+];
+
+/// Whether `command` is, or starts with, one of the `dangerous` entries.
+/// Matching ignores case and repeated whitespace. For `/schedule` the
+/// scheduled command is checked, so it is confirmed when it is scheduled.
+pub fn is_dangerous_command(command: &str, dangerous: &[String]) -> bool {
+    let normalized = scheduled_target(command)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    dangerous
+        .iter()
+        .any(|entry| {
+            let entry = entry.to_lowercase();
+            normalized == entry || normalized.starts_with(&format!("{} ", entry))
+        })
+}
+
+/// FreeSWITCH commands `handle_special_command` renders itself
+const LOCALLY_HANDLED_COMMANDS: &[&str] = &["log", "show", "conference", "uptime"];
+
+/// Whether `handle_special_command` may handle `command` instead of sending
+/// it to FreeSWITCH as is: client commands and [`LOCALLY_HANDLED_COMMANDS`]
+pub fn is_special_command(command: &str) -> bool {
+    command
+        .split_whitespace()
+        .next()
+        .is_some_and(|first| {
+            first.starts_with('/')
+                || LOCALLY_HANDLED_COMMANDS.contains(
+                    &first
+                        .to_lowercase()
+                        .as_str(),
+                )
+        })
+}
+
+/// The command a `/schedule <delay_ms> <command>` line will run, or
+/// `line` itself for anything else
+fn scheduled_target(line: &str) -> &str {
+    line.trim_start()
+        .strip_prefix("/schedule")
+        .filter(|rest| rest.starts_with(char::is_whitespace))
+        .and_then(|rest| {
+            rest.trim_start()
+                .split_once(char::is_whitespace)
+        })
+        .map_or(line, |(_, command)| command.trim())
+}
+
+fn is_dry_run_passthrough(command: &str) -> bool {
+    command
+        .split_whitespace()
+        .next()
+        .is_some_and(|first| DRY_RUN_PASSTHROUGH.contains(&first))
+}
+
+/// Run an API command, turning an error reply into `Err`
+pub async fn api_body(client: &EslClient, command: &str) -> Result<String> {
+    let response = client
+        .api(command)
+        .await?;
+    if !response.is_success() {
+        let reply = response
+            .reply_text()
+            .unwrap_or("unknown error");
+        return Err(anyhow!("{}", reply));
+    }
+    Ok(response
+        .body()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Deepest `/source` nesting allowed with `--allow-recursive-source`
+const MAX_SOURCE_DEPTH: usize = 5;
+
+/// Most `/schedule` commands that may be pending at once
+const MAX_SCHEDULED_TASKS: usize = 20;
+
+/// A `/schedule` command waiting for its delay to pass
+struct ScheduledTask {
+    command: String,
+    due: Instant,
+    handle: JoinHandle<()>,
+}
+
+/// Commands in a `/source` file, skipping blank lines and `#` comments.
+///
+/// A nested `/source` is expanded in place when `allow_recursive` (up to
+/// `MAX_SOURCE_DEPTH` files deep) and rejects the whole file otherwise, so
+/// nothing runs from a script that cannot run completely.
+fn read_source_file(path: &Path, allow_recursive: bool, depth: usize) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Cannot read {}: {}", path.display(), e))?;
+    let mut commands = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some(nested) = source_path(line) else {
+            commands.push(line.to_string());
+            continue;
+        };
+        if !allow_recursive {
+            return Err(anyhow!(
+                "{} contains '{}'; nested /source needs --allow-recursive-source",
+                path.display(),
+                line
+            ));
+        }
+        if depth + 1 >= MAX_SOURCE_DEPTH {
+            return Err(anyhow!(
+                "/source nested more than {} levels deep in {}",
+                MAX_SOURCE_DEPTH,
+                path.display()
+            ));
+        }
+        // Relative paths are relative to the file doing the sourcing
+        let nested = path
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(nested);
+        commands.extend(read_source_file(&nested, allow_recursive, depth + 1)?);
+    }
+    Ok(commands)
+}
+
+/// The file argument of a `/source [--yes] <path>` line
+fn source_path(line: &str) -> Option<&str> {
+    source_args(line).map(|(_, path)| path)
+}
+
+/// Whether a `/source` line has `--yes`, and its file argument
+fn source_args(line: &str) -> Option<(bool, &str)> {
+    let rest = line
+        .strip_prefix("/source")
+        .filter(|rest| rest.starts_with(char::is_whitespace))?
+        .trim();
+    match rest
+        .strip_prefix("--yes")
+        .filter(|path| path.is_empty() || path.starts_with(char::is_whitespace))
+    {
+        Some(path) => Some((true, path.trim())),
+        None => Some((false, rest)),
+    }
+}
+
+/// Result of one executed command
+#[derive(Debug, Default, Clone)]
+pub struct CommandOutput {
+    /// API error reply or `-ERR` response body
+    pub failed: bool,
+    /// Response body (the error message when the API call failed)
+    pub body: String,
+}
+
 /// Command processor for FreeSWITCH CLI commands
 pub struct CommandProcessor {
     color_mode: ColorMode,
     debug_level: EslDebugLevel,
+    json_pretty: bool,
+    /// Set by `--output-format` and `/output-format`
+    output_format: Mutex<OutputFormat>,
+    output_width: Option<u16>,
+    /// Wrap long lines when printing to a terminal
+    word_wrap: bool,
+    dry_run: bool,
+    /// `freeswitch@<host>` label for `/ping`, following `/switch`
+    ping_target: Mutex<String>,
+    command_timeout_ms: Option<u64>,
+    /// Active `/search` pattern, compiled once and applied to every output
+    search_pattern: Mutex<Option<Regex>>,
+    /// `/highlight` patterns with their color, in the order added
+    highlight_patterns: Mutex<Vec<(Regex, &'static str)>>,
+    /// Whether `--events` subscribed the channel events at startup
+    startup_events: bool,
+    subscriptions: Mutex<EventSubscriptionTracker>,
+    pending_jobs: PendingJobs,
+    scheduled_tasks: Mutex<Vec<ScheduledTask>>,
+    loaded_config: Option<LoadedConfig>,
+    /// Queue of the interactive session, fed by `/source` and `/schedule`
+    source_tx: Option<mpsc::UnboundedSender<String>>,
+    /// Function key bindings, changed by `/set-macro`
+    macros: Mutex<HashMap<String, String>>,
+    /// Tells the readline thread to rebind a key after `/set-macro`
+    macro_update_tx: Option<mpsc::UnboundedSender<(String, String)>>,
+    audit_log: Option<AuditLog>,
+    inter_command_delay_ms: Option<u64>,
+    allow_recursive_source: bool,
+    /// Commands `/source` refuses without `--yes`, unless `--no-confirm`
+    dangerous_commands: Vec<String>,
+    confirm_dangerous: bool,
     printer: Printer,
 }
 
 impl CommandProcessor {
     /// Create new command processor
-    pub fn new(color_mode: ColorMode, debug_level: EslDebugLevel) -> Self {
+    pub fn new(config: &AppConfig) -> Self {
         Self {
-            color_mode,
-            debug_level,
+            color_mode: config.color,
+            debug_level: config.debug,
+            json_pretty: config.json_pretty,
+            output_format: Mutex::new(config.output_format),
+            output_width: config.output_width,
+            word_wrap: config.word_wrap,
+            dry_run: config.dry_run,
+            ping_target: Mutex::new(ping_target(&config.host, config.port)),
+            command_timeout_ms: config.command_timeout_ms,
+            search_pattern: Mutex::new(None),
+            highlight_patterns: Mutex::new(Vec::new()),
+            startup_events: config.events,
+            subscriptions: Mutex::new(EventSubscriptionTracker::for_startup(config.events)),
+            pending_jobs: PendingJobs::default(),
+            scheduled_tasks: Mutex::new(Vec::new()),
+            loaded_config: config
+                .loaded_config
+                .clone(),
+            source_tx: None,
+            macros: Mutex::new(crate::readline::build_macros(config)),
+            macro_update_tx: None,
+            audit_log: config
+                .audit_log
+                .clone(),
+            inter_command_delay_ms: config.inter_command_delay_ms,
+            allow_recursive_source: config.allow_recursive_source,
+            dangerous_commands: config
+                .dangerous_commands
+                .clone(),
+            confirm_dangerous: config.confirm_dangerous,
             printer: Printer::none(),
         }
     }
 
     /// Check if colors should be disabled
     fn no_color(&self) -> bool {
-        self.color_mode == ColorMode::Never
+        match self.color_mode {
+            ColorMode::Never => true,
+            ColorMode::Auto => !std::io::stdout().is_terminal() || no_color_requested(),
+            ColorMode::Tag | ColorMode::Line | ColorMode::Bright => false,
+        }
+    }
+
+    /// Width available for table output
+    fn table_width(&self) -> usize {
+        crate::table::output_width(self.output_width)
+    }
+
+    fn output_format(&self) -> OutputFormat {
+        self.output_format
+            .lock()
+            .map(|fmt| *fmt)
+            .unwrap_or_default()
+    }
+
+    /// Label `/ping` with the connection commands now go to
+    pub fn set_ping_target(&self, host: &str, port: u16) {
+        if let Ok(mut target) = self
+            .ping_target
+            .lock()
+        {
+            *target = ping_target(host, port);
+        }
+    }
+
+    /// `/output-format [mode]`: show or change how API responses are rendered
+    fn set_output_format(&self, mode: Option<&str>) -> String {
+        let Some(mode) = mode else {
+            return format!("Output format: {}", self.output_format());
+        };
+        let fmt: OutputFormat = match mode.parse() {
+            Ok(fmt) => fmt,
+            Err(e) => return e,
+        };
+        if let Ok(mut current) = self
+            .output_format
+            .lock()
+        {
+            *current = fmt;
+        }
+        format!("Output format set to {}", fmt)
+    }
+
+    /// `/bgapi` jobs, for the event consumer to report completions
+    pub fn pending_jobs(&self) -> PendingJobs {
+        self.pending_jobs
+            .clone()
+    }
+
+    /// Queue `/source` commands to the interactive session through `tx`
+    pub fn set_source_tx(&mut self, tx: mpsc::UnboundedSender<String>) {
+        self.source_tx = Some(tx);
+    }
+
+    /// Send `/set-macro` changes to the readline thread through `tx`
+    pub fn set_macro_update_tx(&mut self, tx: mpsc::UnboundedSender<(String, String)>) {
+        self.macro_update_tx = Some(tx);
+    }
+
+    /// Current function key bindings
+    pub fn macros(&self) -> HashMap<String, String> {
+        self.macros
+            .lock()
+            .map(|macros| macros.clone())
+            .unwrap_or_default()
     }
 
     /// Set external printer for coordinated output
@@ -183,9 +1291,34 @@ impl CommandProcessor {
         self.printer = printer;
     }
 
-    fn print_message(&self, message: &str) {
+    /// Print command output through the coordinated printer
+    pub fn print_message(&self, message: &str) {
+        let searched = match self
+            .search_pattern
+            .lock()
+        {
+            Ok(guard) => guard
+                .as_ref()
+                .map(|re| apply_search(message, re, !self.no_color())),
+            Err(_) => None,
+        };
+        let mut message = searched.unwrap_or_else(|| message.to_string());
+        if !self.no_color() {
+            if let Ok(patterns) = self
+                .highlight_patterns
+                .lock()
+            {
+                if !patterns.is_empty() {
+                    message = apply_highlights(&message, &patterns);
+                }
+            }
+        }
+        if self.word_wrap && std::io::stdout().is_terminal() {
+            message =
+                crate::table::word_wrap(&message, crate::table::wrap_width(self.output_width));
+        }
         self.printer
-            .print(message.to_string());
+            .print(message);
     }
 
     fn print_error(&self, message: &str) {
@@ -215,53 +1348,94 @@ impl CommandProcessor {
     /// returns an Err carrying the reply text (without "API Error:" prefix —
     /// callers add their own framing).
     async fn api_body(&self, client: &EslClient, command: &str) -> Result<String> {
-        let response = client
-            .api(command)
-            .await?;
-        if !response.is_success() {
-            let reply = response
-                .reply_text()
-                .unwrap_or("unknown error");
-            return Err(anyhow!("{}", reply));
-        }
-        Ok(response
-            .body()
-            .unwrap_or_default()
-            .to_string())
+        api_body(client, command).await
     }
 
     /// Execute a FreeSWITCH command
     pub async fn execute_command(&self, client: &EslClient, command: &str) -> Result<()> {
+        self.execute_command_output(client, command)
+            .await?;
+        Ok(())
+    }
+
+    /// Execute a FreeSWITCH command, returning what it printed so `-x`
+    /// can stop on failures
+    pub async fn execute_command_output(
+        &self,
+        client: &EslClient,
+        command: &str,
+    ) -> Result<CommandOutput> {
         self.debug_level
             .debug_print(EslDebugLevel::Debug5, || {
                 format!("execute_command called with: '{}'", command)
             });
 
-        if let Some(result) = self
-            .handle_special_command(client, command)
+        if self.dry_run && !is_dry_run_passthrough(command) {
+            let label = if self.no_color() {
+                "[DRY RUN]".normal()
+            } else {
+                "[DRY RUN]"
+                    .yellow()
+                    .bold()
+            };
+            self.print_message(&format!("{} Would execute: {}", label, command));
+            return Ok(CommandOutput::default());
+        }
+
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(command);
+        }
+
+        if let Some(result) = self
+            .handle_special_command(client, command)
             .await?
         {
             self.print_message(&result);
-            return Ok(());
+            return Ok(CommandOutput {
+                failed: false,
+                body: result,
+            });
         }
 
-        match self
+        let result = self
             .api_body(client, command)
-            .await
-        {
+            .await;
+        self.show_api_result(result)
+    }
+
+    /// Print the result of an API call the way `execute_command` does
+    pub fn show_api_result(&self, result: Result<String>) -> Result<CommandOutput> {
+        match result {
             Ok(body) => {
                 if !body
                     .trim()
                     .is_empty()
                 {
-                    self.print_message(&body);
+                    let shown = match self.output_format() {
+                        OutputFormat::Raw if self.json_pretty => {
+                            pretty_json(&body).unwrap_or_else(|| body.clone())
+                        }
+                        fmt => {
+                            let color_mode = if self.no_color() {
+                                ColorMode::Never
+                            } else {
+                                self.color_mode
+                            };
+                            transform_output(&body, fmt, color_mode, self.table_width())
+                        }
+                    };
+                    self.print_message(&shown);
                 }
+                Ok(CommandOutput {
+                    failed: body.starts_with("-ERR"),
+                    body,
+                })
             }
             Err(e)
                 if e.downcast_ref::<EslError>()
                     .is_some() =>
             {
-                return Err(e);
+                Err(e)
             }
             Err(e) => {
                 let error_msg = if !self.no_color() {
@@ -276,10 +1450,12 @@ impl CommandProcessor {
                     format!("API Error: {}", e)
                 };
                 self.print_error(&error_msg);
+                Ok(CommandOutput {
+                    failed: true,
+                    body: e.to_string(),
+                })
             }
         }
-
-        Ok(())
     }
 
     /// Handle special CLI commands that need custom processing
@@ -311,6 +1487,146 @@ impl CommandProcessor {
                 self.handle_log_command(client, &parts[1..])
                     .await
             }
+            "/dtmf" => {
+                let dtmf = build_dtmf_command(&parts[1..])?;
+                self.api_body(client, &dtmf)
+                    .await
+                    .map(Some)
+            }
+            "/channel-vars" => match parts[1..] {
+                [uuid] => self
+                    .show_channel_vars(client, uuid, None)
+                    .await
+                    .map(Some),
+                [uuid, pattern] => match RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                {
+                    Ok(re) => self
+                        .show_channel_vars(client, uuid, Some(&re))
+                        .await
+                        .map(Some),
+                    Err(e) => Ok(Some(format!("Invalid variable pattern: {}", e))),
+                },
+                _ => Ok(Some("Usage: /channel-vars <uuid> [regex]".to_string())),
+            },
+            "/export-channels" | "/export-calls" => self
+                .handle_export_command(client, &parts)
+                .await
+                .map(Some),
+            "/export-db" => self
+                .handle_export_db_command(client, command)
+                .await
+                .map(Some),
+            "/ping" => self
+                .handle_ping_command(client, &parts[1..])
+                .await
+                .map(Some),
+            "/subscribe" => self
+                .handle_subscribe_command(client, &parts[1..])
+                .await
+                .map(Some),
+            "/unsubscribe" => self
+                .handle_unsubscribe_command(client, &parts[1..])
+                .await
+                .map(Some),
+            "/subscriptions" => Ok(Some(self.list_subscriptions())),
+            "/bgapi" => self
+                .handle_bgapi_command(client, command)
+                .await
+                .map(Some),
+            "/jobs" => Ok(Some(self.list_jobs())),
+            "/macros" => Ok(Some(self.list_macros())),
+            // The readline thread, which owns the history, handles these
+            "/clear-history" | "/history-size" => {
+                Err(anyhow!("History is only available in interactive mode"))
+            }
+            "/output-format" => Ok(Some(
+                self.set_output_format(
+                    parts
+                        .get(1)
+                        .copied(),
+                ),
+            )),
+            "/set-macro" => self
+                .set_macro(command)
+                .map(Some),
+            // The readline thread confirms and turns it into /hupall-force
+            "/hupall" => Ok(Some(
+                "/hupall needs an interactive session; use /hupall-force".to_string(),
+            )),
+            "/hupall-force" => self
+                .api_body(
+                    client,
+                    &hupall_command(
+                        parts
+                            .get(1)
+                            .copied(),
+                    ),
+                )
+                .await
+                .map(|reply| {
+                    Some(
+                        reply
+                            .trim_end()
+                            .to_string(),
+                    )
+                }),
+            "/reloadxml-diff" => self
+                .reloadxml_diff(client)
+                .await
+                .map(Some),
+            "/originate-wizard" => Ok(Some(
+                "/originate-wizard needs an interactive session".to_string(),
+            )),
+            "/api-search" => match parts.get(1) {
+                Some(query) => self
+                    .show_api(client, Some(*query))
+                    .await
+                    .map(Some),
+                None => Ok(Some("Usage: /api-search <query>".to_string())),
+            },
+            "/version" => self
+                .show_versions(client)
+                .await
+                .map(Some),
+            "/save-config" => self
+                .save_config()
+                .map(Some),
+            "/create-profile" => self
+                .create_profile(command)
+                .map(Some),
+            "/delete-profile" => self
+                .delete_profile(&parts[1..])
+                .map(Some),
+            "/search" => Ok(Some(self.handle_search_command(command))),
+            "/nosearch" => {
+                if let Ok(mut pattern) = self
+                    .search_pattern
+                    .lock()
+                {
+                    *pattern = None;
+                }
+                Ok(Some("Search cleared".to_string()))
+            }
+            "/source" => self
+                .source_file(command)
+                .map(Some),
+            "/schedule" => self
+                .schedule_command(command)
+                .map(Some),
+            "/unschedule-all" => Ok(Some(format!(
+                "Cancelled {} scheduled commands",
+                self.unschedule_all()
+            ))),
+            "/highlight" => Ok(Some(self.handle_highlight_command(command))),
+            "/nohighlight" => Ok(Some(
+                self.remove_highlight(
+                    parts
+                        .get(1)
+                        .copied(),
+                ),
+            )),
             _ => match parts[0]
                 .to_lowercase()
                 .as_str()
@@ -319,6 +1635,14 @@ impl CommandProcessor {
                     self.handle_log_command(client, &parts[1..])
                         .await
                 }
+                "show" => {
+                    self.handle_show_command(client, &parts[1..])
+                        .await
+                }
+                "conference" => {
+                    self.handle_conference_command(client, &parts[1..])
+                        .await
+                }
                 "uptime" => {
                     let body = self
                         .api_body(client, "status")
@@ -330,6 +1654,483 @@ impl CommandProcessor {
         }
     }
 
+    /// `/subscribe <type>...`: add event types to the current subscription
+    async fn handle_subscribe_command(&self, client: &EslClient, args: &[&str]) -> Result<String> {
+        if args.is_empty() {
+            return Ok("Usage: /subscribe <event_type>...".to_string());
+        }
+        let types = args
+            .iter()
+            .map(|name| parse_event_type(name))
+            .collect::<Result<Vec<_>>>()?;
+        client
+            .subscribe_events(EventFormat::Plain, &types)
+            .await?;
+        if let Ok(mut tracker) = self
+            .subscriptions
+            .lock()
+        {
+            for event_type in &types {
+                tracker.insert(*event_type);
+            }
+        }
+        Ok(format!("Subscribed to {}", join_event_types(&types)))
+    }
+
+    /// `/unsubscribe <type>...`: drop event types from the subscription
+    async fn handle_unsubscribe_command(
+        &self,
+        client: &EslClient,
+        args: &[&str],
+    ) -> Result<String> {
+        if args.is_empty() {
+            return Ok("Usage: /unsubscribe <event_type>...".to_string());
+        }
+        let types = args
+            .iter()
+            .map(|name| parse_event_type(name))
+            .collect::<Result<Vec<_>>>()?;
+        if types.contains(&EslEventType::Heartbeat) {
+            return Err(anyhow!(
+                "HEARTBEAT is needed to detect a stalled connection and cannot be unsubscribed"
+            ));
+        }
+        client
+            .nixevent(&types)
+            .await?;
+        if let Ok(mut tracker) = self
+            .subscriptions
+            .lock()
+        {
+            for event_type in &types {
+                tracker.remove(*event_type);
+            }
+        }
+        Ok(format!("Unsubscribed from {}", join_event_types(&types)))
+    }
+
+    /// `/save-config`: write the loaded configuration back to its file
+    fn save_config(&self) -> Result<String> {
+        let Some(loaded) = &self.loaded_config else {
+            return Err(anyhow!("No configuration file to save to"));
+        };
+        let config = loaded
+            .config
+            .lock()
+            .map_err(|_| anyhow!("Configuration is unavailable"))?;
+        config.save(&loaded.path)?;
+        Ok(format!(
+            "Configuration saved to {}",
+            loaded
+                .path
+                .display()
+        ))
+    }
+
+    /// Apply `f` to the loaded configuration, then save it
+    fn edit_config<T>(&self, f: impl FnOnce(&mut FsCliConfig) -> Result<T>) -> Result<T> {
+        let Some(loaded) = &self.loaded_config else {
+            return Err(anyhow!("No configuration file to save to"));
+        };
+        let mut config = loaded
+            .config
+            .lock()
+            .map_err(|_| anyhow!("Configuration is unavailable"))?;
+        let result = f(&mut config)?;
+        config.save(&loaded.path)?;
+        Ok(result)
+    }
+
+    /// `/create-profile <name> <host> <port> <password>`, as assembled by the
+    /// readline thread after prompting. The password is the rest of the line.
+    fn create_profile(&self, command: &str) -> Result<String> {
+        let mut fields = command
+            .trim()
+            .splitn(5, ' ');
+        fields.next();
+        let (Some(name), Some(host), Some(port), Some(password)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Ok("Usage: /create-profile <name>".to_string());
+        };
+        let port: u16 = port
+            .parse()
+            .ok()
+            .filter(|p| *p > 0)
+            .ok_or_else(|| anyhow!("Invalid port: {}", port))?;
+
+        self.edit_config(|config| {
+            if config
+                .fs_cli
+                .contains_key(name)
+            {
+                return Err(anyhow!("Profile '{}' already exists", name));
+            }
+            config
+                .fs_cli
+                .insert(
+                    name.to_string(),
+                    ProfileConfig {
+                        host: Some(host.to_string()),
+                        port: Some(port),
+                        password: Some(password.to_string()),
+                        ..ProfileConfig::default()
+                    },
+                );
+            Ok(())
+        })?;
+        Ok(format!(
+            "Created profile '{}': host {}, port {}, password ****\nConnect with: fs_cli {}",
+            name, host, port, name
+        ))
+    }
+
+    /// `/delete-profile <name>`; the readline thread has already confirmed
+    fn delete_profile(&self, args: &[&str]) -> Result<String> {
+        let [name] = args else {
+            return Ok("Usage: /delete-profile <name>".to_string());
+        };
+        self.edit_config(|config| {
+            config
+                .fs_cli
+                .remove(*name)
+                .map(|_| ())
+                .ok_or_else(|| anyhow!("Profile '{}' not found", name))
+        })?;
+        Ok(format!("Deleted profile '{}'", name))
+    }
+
+    /// `/bgapi <command>`: start a background job; the event consumer
+    /// prints its result when the BACKGROUND_JOB event arrives
+    async fn handle_bgapi_command(&self, client: &EslClient, command: &str) -> Result<String> {
+        let cmd = command
+            .trim_start()
+            .trim_start_matches("/bgapi")
+            .trim();
+        if cmd.is_empty() {
+            return Ok("Usage: /bgapi <command>".to_string());
+        }
+
+        let subscribed = self
+            .subscriptions
+            .lock()
+            .map(|tracker| tracker.contains(EslEventType::BackgroundJob))
+            .unwrap_or(false);
+        if !subscribed {
+            client
+                .subscribe_events(EventFormat::Plain, &[EslEventType::BackgroundJob])
+                .await?;
+            if let Ok(mut tracker) = self
+                .subscriptions
+                .lock()
+            {
+                tracker.insert(EslEventType::BackgroundJob);
+            }
+        }
+
+        let started = Instant::now();
+        let response = client
+            .bgapi(cmd)
+            .await?;
+        let reply = response
+            .reply_text()
+            .unwrap_or_default();
+        if !response.is_success() {
+            return Err(anyhow!("{}", reply));
+        }
+        let uuid = job_uuid_from_reply(reply)
+            .ok_or_else(|| anyhow!("No Job-UUID in bgapi reply: {}", reply))?
+            .to_string();
+        // The job's event may have beaten the reply on a fast command
+        if let Some(body) = register_job(&self.pending_jobs, &uuid, cmd, started) {
+            return Ok(format_job_done(
+                &body,
+                cmd,
+                started.elapsed(),
+                self.color_mode,
+            ));
+        }
+        Ok(format!("Job {} started: {}", uuid, cmd))
+    }
+
+    /// `/macros`: function key bindings as a table, F1 to F12
+    fn list_macros(&self) -> String {
+        let macros = self.macros();
+        let rows: Vec<Vec<String>> = (1..=12)
+            .filter_map(|i| {
+                macros
+                    .get(&format!("f{}", i))
+                    .map(|command| vec![format!("F{}", i), command.clone()])
+            })
+            .collect();
+        format_table(
+            &["Key", "Command"],
+            &rows,
+            self.color_mode,
+            self.table_width(),
+        )
+    }
+
+    /// `/set-macro <f1-f12> <command>`: rebind a function key for this
+    /// session
+    fn set_macro(&self, command: &str) -> Result<String> {
+        let usage = "Usage: /set-macro <f1-f12> <command>";
+        let args = command
+            .trim_start()
+            .trim_start_matches("/set-macro")
+            .trim();
+        let Some((key, macro_command)) = args.split_once(char::is_whitespace) else {
+            return Ok(usage.to_string());
+        };
+        let Some(number) = crate::readline::function_key_number(key) else {
+            return Err(anyhow!("Invalid function key: {} (must be f1-f12)", key));
+        };
+        let key = format!("f{}", number);
+        let macro_command = macro_command
+            .trim()
+            .to_string();
+        if let Ok(mut macros) = self
+            .macros
+            .lock()
+        {
+            macros.insert(key.clone(), macro_command.clone());
+        }
+        if let Some(tx) = &self.macro_update_tx {
+            tx.send((key.clone(), macro_command.clone()))
+                .ok();
+        }
+        Ok(format!("F{} = {}", number, macro_command))
+    }
+
+    /// `/version`: this client's version and the server's
+    async fn show_versions(&self, client: &EslClient) -> Result<String> {
+        let server = self
+            .api_body(client, "version")
+            .await?;
+        let color_mode = if self.no_color() {
+            ColorMode::Never
+        } else {
+            self.color_mode
+        };
+        Ok(format_versions(&server, color_mode, self.table_width()))
+    }
+
+    /// `/jobs`: pending `/bgapi` jobs with their running time, and
+    /// `/schedule` commands with the time left before they run
+    fn list_jobs(&self) -> String {
+        let mut lines = Vec::new();
+        if let Ok(jobs) = self
+            .pending_jobs
+            .lock()
+        {
+            lines.extend(
+                jobs.pending()
+                    .into_iter()
+                    .map(|(uuid, cmd, started)| {
+                        format!(
+                            "  {}  {}  ({})",
+                            uuid,
+                            cmd,
+                            format_duration(
+                                started
+                                    .elapsed()
+                                    .as_secs()
+                            )
+                        )
+                    }),
+            );
+        }
+        if let Ok(mut scheduled) = self
+            .scheduled_tasks
+            .lock()
+        {
+            scheduled.retain(|task| {
+                !task
+                    .handle
+                    .is_finished()
+            });
+            scheduled.sort_by_key(|task| task.due);
+            lines.extend(
+                scheduled
+                    .iter()
+                    .map(|task| {
+                        format!(
+                            "  scheduled  {}  (in {}ms)",
+                            task.command,
+                            task.due
+                                .saturating_duration_since(Instant::now())
+                                .as_millis()
+                        )
+                    }),
+            );
+        }
+        if lines.is_empty() {
+            return "No pending jobs".to_string();
+        }
+        format!("Pending jobs:\n{}", lines.join("\n"))
+    }
+
+    /// `/schedule <delay_ms> <command>`: queue `command` to the interactive
+    /// session once the delay has passed
+    fn schedule_command(&self, command: &str) -> Result<String> {
+        let usage = "Usage: /schedule <delay_ms> <command>";
+        let args = command
+            .trim_start()
+            .trim_start_matches("/schedule")
+            .trim();
+        let Some((delay, cmd)) = args.split_once(char::is_whitespace) else {
+            return Ok(usage.to_string());
+        };
+        let cmd = cmd.trim();
+        let Ok(delay_ms) = delay.parse::<u64>() else {
+            return Ok(usage.to_string());
+        };
+        let Some(tx) = self
+            .source_tx
+            .clone()
+        else {
+            return Err(anyhow!("/schedule is only available in interactive mode"));
+        };
+        let Ok(mut scheduled) = self
+            .scheduled_tasks
+            .lock()
+        else {
+            return Err(anyhow!("Scheduled task list is unavailable"));
+        };
+        scheduled.retain(|task| {
+            !task
+                .handle
+                .is_finished()
+        });
+        if scheduled.len() >= MAX_SCHEDULED_TASKS {
+            return Err(anyhow!(
+                "Too many scheduled commands (max {}); use /unschedule-all",
+                MAX_SCHEDULED_TASKS
+            ));
+        }
+
+        let delay = Duration::from_millis(delay_ms);
+        let queued = cmd.to_string();
+        let handle = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            tx.send(queued)
+                .ok();
+        });
+        scheduled.push(ScheduledTask {
+            command: cmd.to_string(),
+            due: Instant::now() + delay,
+            handle,
+        });
+        Ok(format!("Scheduled in {}ms: {}", delay_ms, cmd))
+    }
+
+    /// Abort all pending `/schedule` commands, returning how many there were
+    pub fn unschedule_all(&self) -> usize {
+        let Ok(mut scheduled) = self
+            .scheduled_tasks
+            .lock()
+        else {
+            return 0;
+        };
+        scheduled
+            .drain(..)
+            .filter(|task| {
+                !task
+                    .handle
+                    .is_finished()
+            })
+            .map(|task| {
+                task.handle
+                    .abort()
+            })
+            .count()
+    }
+
+    fn list_subscriptions(&self) -> String {
+        let names = match self
+            .subscriptions
+            .lock()
+        {
+            Ok(tracker) => tracker.names(),
+            Err(_) => Vec::new(),
+        };
+        if names.is_empty() {
+            return "No event subscriptions".to_string();
+        }
+        format!("Subscribed events:\n  {}", names.join("\n  "))
+    }
+
+    /// Replay `/subscribe` and `/unsubscribe` changes on a new connection,
+    /// after the startup subscriptions were set up again
+    pub async fn restore_subscriptions(&self, client: &EslClient) {
+        let (added, removed) = match self
+            .subscriptions
+            .lock()
+        {
+            Ok(tracker) => tracker.changes_since_startup(self.startup_events),
+            Err(_) => return,
+        };
+        if !added.is_empty() {
+            if let Err(e) = client
+                .subscribe_events(EventFormat::Plain, &added)
+                .await
+            {
+                warn!("Failed to restore event subscriptions: {}", e);
+            }
+        }
+        if !removed.is_empty() {
+            if let Err(e) = client
+                .nixevent(&removed)
+                .await
+            {
+                warn!("Failed to restore event unsubscriptions: {}", e);
+            }
+        }
+    }
+
+    /// `/ping [count]`: time `count` round trips of the `status` API,
+    /// printing each as it completes, and return the summary
+    async fn handle_ping_command(&self, client: &EslClient, args: &[&str]) -> Result<String> {
+        let count = match args {
+            [] => DEFAULT_PING_COUNT,
+            [n] => match n.parse::<u32>() {
+                Ok(n) if n > 0 => n,
+                _ => return Ok("Usage: /ping [count]  (count >= 1)".to_string()),
+            },
+            _ => return Ok("Usage: /ping [count]  (count >= 1)".to_string()),
+        };
+        let limit = Duration::from_millis(
+            self.command_timeout_ms
+                .unwrap_or(DEFAULT_PING_TIMEOUT_MS),
+        );
+
+        let target = self
+            .ping_target
+            .lock()
+            .map(|target| target.clone())
+            .unwrap_or_default();
+        let mut rtts = Vec::new();
+        for seq in 1..=count {
+            let started = Instant::now();
+            match tokio::time::timeout(limit, client.api("status")).await {
+                Ok(Ok(_)) => {
+                    let rtt = started.elapsed();
+                    self.print_message(&format!(
+                        "status from {}: seq={} time={:.2}ms",
+                        target,
+                        seq,
+                        rtt.as_secs_f64() * 1000.0
+                    ));
+                    rtts.push(rtt);
+                }
+                Ok(Err(e)) => return Err(e.into()),
+                Err(_) => self.print_message(&format!("Request timeout for seq={}", seq)),
+            }
+        }
+
+        Ok(format_ping_summary(&target, count, &rtts))
+    }
+
     /// Handle /log command with various log levels
     async fn handle_log_command(
         &self,
@@ -357,100 +2158,1620 @@ impl CommandProcessor {
         }
     }
 
-    /// Extract uptime information from status output
-    fn extract_uptime(&self, status_output: &str) -> String {
-        for line in status_output.lines() {
-            if line.contains("UP")
-                && (line.contains("years") || line.contains("days") || line.contains("hours"))
+    /// Run a `show ... as json` command and return its rows
+    async fn api_json_rows(
+        &self,
+        client: &EslClient,
+        command: &str,
+    ) -> Result<Vec<HashMap<String, String>>> {
+        let body = self
+            .api_body(client, command)
+            .await?;
+        let parsed: ShowRows = serde_json::from_str(&body)
+            .map_err(|e| anyhow!("Failed to parse JSON from '{}': {}", command, e))?;
+        Ok(parsed.rows)
+    }
+
+    /// Render selected `show` sub-commands as tables. Anything not handled
+    /// here (including explicit `as json`/`as xml` forms) goes to FreeSWITCH
+    /// unchanged.
+    async fn handle_show_command(
+        &self,
+        client: &EslClient,
+        args: &[&str],
+    ) -> Result<Option<String>> {
+        match args {
+            [what, rest @ ..] if what.eq_ignore_ascii_case("channels") => match rest {
+                [] => self
+                    .show_channels(client, false)
+                    .await
+                    .map(Some),
+                [sort] if sort.eq_ignore_ascii_case("sort-by=age") => self
+                    .show_channels(client, true)
+                    .await
+                    .map(Some),
+                _ => Ok(None),
+            },
+            [what] if what.eq_ignore_ascii_case("api") => self
+                .show_api(client, None)
+                .await
+                .map(Some),
+            [what, filter] if what.eq_ignore_ascii_case("api") => self
+                .show_api(client, Some(*filter))
+                .await
+                .map(Some),
+            [what, name]
+                if what.eq_ignore_ascii_case("modules") && !name.eq_ignore_ascii_case("as") =>
             {
-                return line
-                    .trim()
-                    .to_string();
+                self.show_module(client, name)
+                    .await
+                    .map(Some)
+            }
+            [what] if what.eq_ignore_ascii_case("interfaces") => self
+                .show_interfaces(client, None)
+                .await
+                .map(Some),
+            [what, filter]
+                if what.eq_ignore_ascii_case("interfaces")
+                    && !filter.eq_ignore_ascii_case("as") =>
+            {
+                self.show_interfaces(client, Some(*filter))
+                    .await
+                    .map(Some)
             }
+            [what] if what.eq_ignore_ascii_case("version") => self
+                .show_versions(client)
+                .await
+                .map(Some),
+            [what] if what.eq_ignore_ascii_case("tasks") => self
+                .show_tasks(client)
+                .await
+                .map(Some),
+            [what] if what.eq_ignore_ascii_case("calls") => self
+                .show_calls(client)
+                .await
+                .map(Some),
+            [what] if what.eq_ignore_ascii_case("registrations") => self
+                .show_registrations(client, None)
+                .await
+                .map(Some),
+            [what, "--profile", profile] if what.eq_ignore_ascii_case("registrations") => self
+                .show_registrations(client, Some(*profile))
+                .await
+                .map(Some),
+            _ => Ok(None),
         }
-        "Uptime information not found".to_string()
     }
 
-    /// Show help information with the effective (merged) function key bindings.
-    pub fn show_help(&self, macros: &HashMap<String, String>) {
-        let mut fnkey_lines = String::new();
-        for i in 1u8..=12 {
-            let key = format!("f{}", i);
-            if let Some(cmd) = macros.get(&key) {
-                fnkey_lines.push_str(&format!("  F{:<3} = {}\n", i, cmd));
+    /// `show api [filter]`: API commands sorted by name, limited to names
+    /// containing `filter` (ignoring case), which is emphasized
+    async fn show_api(&self, client: &EslClient, filter: Option<&str>) -> Result<String> {
+        let commands = self
+            .api_json_rows(client, "show api as json")
+            .await?;
+        let mut rows = filter_api_commands(&commands, filter);
+        if let Some(filter) = filter.filter(|_| !self.no_color()) {
+            let pattern = RegexBuilder::new(&regex::escape(filter))
+                .case_insensitive(true)
+                .build()?;
+            for row in &mut rows {
+                row[0] = pattern
+                    .replace_all(&row[0], |caps: &regex::Captures| {
+                        caps[0]
+                            .yellow()
+                            .bold()
+                            .to_string()
+                    })
+                    .into_owned();
             }
         }
+        Ok(format!(
+            "{}\n\n{} total.",
+            format_table(
+                &["Command", "Description"],
+                &rows,
+                self.color_mode,
+                self.table_width(),
+            ),
+            rows.len()
+        ))
+    }
 
-        let help_text = format!(
-            r#"
-FreeSWITCH CLI Commands:
-
-Basic Commands:
-  status                    - Show system status
-  version                   - Show FreeSWITCH version
-  uptime                    - Show system uptime
-
-Show Commands:
-  show channels             - List active channels
-  show channels count       - Show channel count
-  show calls                - Show active calls
-  show registrations        - Show SIP registrations
-  show modules              - List loaded modules
-  show interfaces           - Show interfaces
-
-Control Commands:
-  reload [module]           - Reload module or XML config
-  originate <url> <dest>    - Originate a call
-
-Function Key Shortcuts (customizable in config):
-{}
-Built-in Commands:
-  /help                     - Show this help
-  /quit, /exit, /bye        - Exit the CLI
-  /history                  - Show command history
-  /clear                    - Clear screen
-
-Configuration:
-  Profiles can be configured in ~/.config/fs_cli.yaml or /etc/freeswitch/fs_cli.yaml
-  Use --config to specify a custom configuration file path
-  Use --list-profiles to see available profiles
-  Default configuration is created automatically if missing
-
-You can execute any FreeSWITCH API command directly.
-Use Tab for command completion and Up/Down arrows for history.
-"#,
-            fnkey_lines
-        );
+    /// `/reloadxml-diff`: `reloadxml`, then show which lines of the XML
+    /// registry (as dumped by `xml_locate`) it changed
+    async fn reloadxml_diff(&self, client: &EslClient) -> Result<String> {
+        let before = self
+            .api_body(client, "xml_locate")
+            .await?;
+        let reply = self
+            .api_body(client, "reloadxml")
+            .await?;
+        if reply.starts_with("-ERR") {
+            return Ok(reply
+                .trim_end()
+                .to_string());
+        }
+        let after = self
+            .api_body(client, "xml_locate")
+            .await?;
+        let changes = diff_lines(&before, &after);
+        if changes.is_empty() {
+            return Ok("No configuration changes detected".to_string());
+        }
+        let color = !self.no_color();
+        let (mut removed, mut added) = (0, 0);
+        let mut lines: Vec<String> = changes
+            .iter()
+            .map(|(sign, line)| {
+                let text = format!("{} {}", sign, line);
+                match sign {
+                    '-' => {
+                        removed += 1;
+                        if color {
+                            text.red()
+                                .to_string()
+                        } else {
+                            text
+                        }
+                    }
+                    _ => {
+                        added += 1;
+                        if color {
+                            text.green()
+                                .to_string()
+                        } else {
+                            text
+                        }
+                    }
+                }
+            })
+            .collect();
+        lines.push(format!("{} lines removed, {} added", removed, added));
+        Ok(lines.join("\n"))
+    }
 
-        let formatted_help = if !self.no_color() {
-            format!("{}", help_text.cyan())
+    /// `show modules <name>`: whether the module is loaded, where from, and
+    /// the APIs and applications it registers
+    async fn show_module(&self, client: &EslClient, name: &str) -> Result<String> {
+        let exists = self
+            .api_body(client, &format!("module_exists {}", name))
+            .await?;
+        let loaded = exists.trim() == "true";
+        let interfaces = if loaded {
+            self.api_json_rows(client, "show modules as json")
+                .await?
         } else {
-            help_text
+            Vec::new()
         };
-        self.print_message(&formatted_help);
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let summary = summarize_module(&interfaces, name);
 
-    #[test]
-    fn log_level_parse_all_valid() {
-        for level in LogLevel::all_variants() {
-            let parsed: Result<LogLevel, _> = level
-                .as_str()
-                .parse();
-            assert!(parsed.is_ok(), "failed to parse '{}'", level.as_str());
-            assert_eq!(parsed.unwrap(), *level);
+        let label = |text: &str| {
+            let text = format!("{:<14}", text);
+            if self.no_color() {
+                text
+            } else {
+                text.bold()
+                    .to_string()
+            }
+        };
+        let list = |names: &[String]| {
+            if names.is_empty() {
+                "none".to_string()
+            } else {
+                names.join(", ")
+            }
+        };
+        let mut lines = vec![
+            format!("{}{}", label("Module:"), name),
+            format!("{}{}", label("Loaded:"), if loaded { "yes" } else { "no" }),
+        ];
+        if loaded {
+            lines.push(format!(
+                "{}{}",
+                label("Path:"),
+                summary
+                    .path
+                    .as_deref()
+                    .unwrap_or("unknown")
+            ));
+            lines.push(format!("{}{}", label("APIs:"), list(&summary.apis)));
+            lines.push(format!(
+                "{}{}",
+                label("Applications:"),
+                list(&summary.applications)
+            ));
+            if !summary
+                .other
+                .is_empty()
+            {
+                lines.push(format!("{}{}", label("Other:"), list(&summary.other)));
+            }
         }
+        Ok(lines.join("\n"))
     }
 
-    #[test]
-    fn log_level_parse_invalid_returns_error() {
-        let result: Result<LogLevel, _> = "hello".parse();
-        assert!(result.is_err());
-    }
+    /// `show tasks` with the time until each task's next run. Overdue tasks
+    /// are red, tasks due within the second bold.
+    async fn show_tasks(&self, client: &EslClient) -> Result<String> {
+        let body = self
+            .api_body(client, "show tasks as json")
+            .await?;
+        let parsed: TasksResponse = serde_json::from_str(&body)
+            .map_err(|e| anyhow!("Failed to parse tasks JSON: {}", e))?;
+        let now = now_epoch();
+        let rows: Vec<Vec<String>> = parsed
+            .rows
+            .iter()
+            .map(|task| {
+                let next_run_in = task.next_run_in(now);
+                let row = vec![
+                    task.task_id
+                        .clone(),
+                    task.task_name
+                        .clone(),
+                    task.task_group
+                        .clone(),
+                    task.task_interval
+                        .clone(),
+                    format_next_run(next_run_in),
+                ];
+                match next_run_in {
+                    _ if self.no_color() => row,
+                    secs if secs < 0 => row
+                        .into_iter()
+                        .map(|cell| {
+                            cell.red()
+                                .to_string()
+                        })
+                        .collect(),
+                    0 => row
+                        .into_iter()
+                        .map(|cell| {
+                            cell.bold()
+                                .to_string()
+                        })
+                        .collect(),
+                    _ => row,
+                }
+            })
+            .collect();
+        Ok(format!(
+            "{}\n\n{} total.",
+            format_table(
+                &["ID", "Task", "Group", "Interval", "Next Run"],
+                &rows,
+                self.color_mode,
+                self.table_width(),
+            ),
+            rows.len()
+        ))
+    }
+
+    /// `show interfaces [type|module]`, sorted by type then name
+    async fn show_interfaces(&self, client: &EslClient, filter: Option<&str>) -> Result<String> {
+        let body = self
+            .api_body(client, "show interfaces as json")
+            .await?;
+        let parsed: InterfacesResponse = serde_json::from_str(&body)
+            .map_err(|e| anyhow!("Failed to parse interfaces JSON: {}", e))?;
+        let mut interfaces: Vec<_> = parsed
+            .rows
+            .into_iter()
+            .filter(|interface| filter.is_none_or(|f| interface.matches(f)))
+            .collect();
+        interfaces.sort_by(|a, b| (&a.interface_type, &a.name).cmp(&(&b.interface_type, &b.name)));
+        let rows: Vec<Vec<String>> = interfaces
+            .into_iter()
+            .map(|interface| vec![interface.interface_type, interface.name, interface.ikey])
+            .collect();
+        Ok(format!(
+            "{}\n\n{} total.",
+            format_table(
+                &["Type", "Name", "Module"],
+                &rows,
+                self.color_mode,
+                self.table_width(),
+            ),
+            rows.len()
+        ))
+    }
+
+    /// `show registrations` with time left before expiry, optionally limited
+    /// to one SIP profile
+    async fn show_registrations(
+        &self,
+        client: &EslClient,
+        profile: Option<&str>,
+    ) -> Result<String> {
+        let body = self
+            .api_body(client, "show registrations as json")
+            .await?;
+        let parsed: RegistrationsResponse = serde_json::from_str(&body)
+            .map_err(|e| anyhow!("Failed to parse registrations JSON: {}", e))?;
+        let now = now_epoch();
+        let rows: Vec<Vec<String>> = parsed
+            .rows
+            .iter()
+            .filter(|reg| profile.is_none_or(|p| reg.is_on_profile(p)))
+            .map(|reg| {
+                let expires_in = reg.expires_in(now);
+                let status = if expires_in < 0 {
+                    "Expired"
+                } else {
+                    "Registered"
+                };
+                let row = vec![
+                    reg.user
+                        .clone(),
+                    reg.host
+                        .clone(),
+                    reg.contact
+                        .clone(),
+                    format!("{}:{}", reg.network_ip, reg.network_port),
+                    status.to_string(),
+                    format_expires_in(expires_in),
+                ];
+                if self.no_color() || expires_in >= REGISTRATION_EXPIRING_SECS {
+                    row
+                } else {
+                    let color = if expires_in < 0 {
+                        Color::Red
+                    } else {
+                        Color::Yellow
+                    };
+                    row.into_iter()
+                        .map(|cell| {
+                            cell.color(color)
+                                .to_string()
+                        })
+                        .collect()
+                }
+            })
+            .collect();
+        Ok(format!(
+            "{}\n\n{} total.",
+            format_table(
+                &[
+                    "User",
+                    "Realm",
+                    "Contact",
+                    "Network",
+                    "Status",
+                    "Expires In"
+                ],
+                &rows,
+                self.color_mode,
+                self.table_width(),
+            ),
+            rows.len()
+        ))
+    }
+
+    /// Handle `/export-channels <file>` and `/export-calls <file>` (CSV)
+    async fn handle_export_command(&self, client: &EslClient, parts: &[&str]) -> Result<String> {
+        let [what, path] = parts else {
+            return Ok(format!("Usage: {} <file>", parts[0]));
+        };
+        let path = std::path::Path::new(path);
+        let written = if *what == "/export-channels" {
+            let body = self
+                .api_body(client, "show channels as json")
+                .await?;
+            let channels: ChannelsResponse = serde_json::from_str(&body)
+                .map_err(|e| anyhow!("Failed to parse channels JSON: {}", e))?;
+            crate::export::write_csv_file(
+                path,
+                &crate::export::CHANNEL_COLUMNS,
+                &crate::export::channel_rows(&channels.rows),
+            )?
+        } else {
+            let calls = self
+                .api_json_rows(client, "show calls as json")
+                .await?;
+            crate::export::write_csv_file(
+                path,
+                &crate::export::CALL_COLUMNS,
+                &crate::export::call_rows(&calls),
+            )?
+        };
+        Ok(format!("Wrote {} rows to {}", written, path.display()))
+    }
+
+    /// `/export-db [file]`: upsert current channels into a SQLite database
+    async fn handle_export_db_command(&self, client: &EslClient, command: &str) -> Result<String> {
+        // Everything after the command is the path, spaces included
+        let path = command
+            .trim()
+            .strip_prefix("/export-db")
+            .unwrap_or_default()
+            .trim();
+        let path = if path.is_empty() {
+            crate::export::default_channel_db_path()
+        } else {
+            std::path::PathBuf::from(path)
+        };
+        let body = self
+            .api_body(client, "show channels as json")
+            .await?;
+        let channels: ChannelsResponse = serde_json::from_str(&body)
+            .map_err(|e| anyhow!("Failed to parse channels JSON: {}", e))?;
+        let written = crate::export::write_channels_db(&path, &channels.rows, now_epoch() as i64)?;
+        Ok(format!(
+            "Exported {} channels to {}",
+            written,
+            path.display()
+        ))
+    }
+
+    /// Render `conference <name> list [count]` as a member table. Other
+    /// conference sub-commands go to FreeSWITCH unchanged.
+    async fn handle_conference_command(
+        &self,
+        client: &EslClient,
+        args: &[&str],
+    ) -> Result<Option<String>> {
+        let (name, count_only) = match args {
+            [name, list] if list.eq_ignore_ascii_case("list") => (*name, false),
+            [name, list, count]
+                if list.eq_ignore_ascii_case("list") && count.eq_ignore_ascii_case("count") =>
+            {
+                (*name, true)
+            }
+            _ => return Ok(None),
+        };
+        // `conference list` (all conferences) is not a per-conference listing
+        if name.eq_ignore_ascii_case("list") {
+            return Ok(None);
+        }
+
+        let body = self
+            .api_body(client, &format!("conference {} list", name))
+            .await?;
+        if body.starts_with("-ERR") {
+            return Ok(Some(
+                body.trim_end()
+                    .to_string(),
+            ));
+        }
+        let members = parse_conference_members(&body);
+        if count_only {
+            return Ok(Some(format!(
+                "Conference {}: {} participants",
+                name,
+                members.len()
+            )));
+        }
+
+        let rows: Vec<Vec<String>> = members
+            .iter()
+            .map(|member| {
+                let row = vec![
+                    member
+                        .id
+                        .clone(),
+                    member
+                        .cid_name
+                        .clone(),
+                    member
+                        .cid_num
+                        .clone(),
+                    member
+                        .state()
+                        .to_string(),
+                    member
+                        .energy
+                        .clone(),
+                    member
+                        .flags
+                        .join("|"),
+                ];
+                if member.has_floor() && !self.no_color() {
+                    row.into_iter()
+                        .map(|cell| {
+                            cell.green()
+                                .bold()
+                                .to_string()
+                        })
+                        .collect()
+                } else {
+                    row
+                }
+            })
+            .collect();
+        Ok(Some(format!(
+            "{}\n\n{} participants.",
+            format_table(
+                &["ID", "CID Name", "CID Number", "State", "Energy", "Flags"],
+                &rows,
+                self.color_mode,
+                self.table_width(),
+            ),
+            rows.len()
+        )))
+    }
+
+    /// `/channel-vars <uuid> [regex]`: `uuid_dump` as a Key/Value table,
+    /// grouped by variable prefix, or only the keys matching `filter`
+    async fn show_channel_vars(
+        &self,
+        client: &EslClient,
+        uuid: &str,
+        filter: Option<&Regex>,
+    ) -> Result<String> {
+        let body = self
+            .api_body(client, &format!("uuid_dump {}", uuid))
+            .await?;
+        let mut vars = parse_uuid_dump(&body);
+        if let Some(filter) = filter {
+            vars.retain(|(key, _)| filter.is_match(key));
+            vars.sort();
+        }
+        if vars.is_empty() {
+            return Ok(match filter {
+                Some(filter) => format!("No variables of {} match /{}/", uuid, filter),
+                None => format!("No variables for {}", uuid),
+            });
+        }
+
+        let mut rows = Vec::with_capacity(vars.len());
+        let mut current_group = None;
+        for (key, value) in &vars {
+            let group = channel_var_group(key);
+            if filter.is_none() && current_group.as_ref() != Some(&group) {
+                let label = format!("── {} ──", group);
+                rows.push(vec![
+                    if self.no_color() {
+                        label
+                    } else {
+                        label
+                            .bold()
+                            .to_string()
+                    },
+                    String::new(),
+                ]);
+                current_group = Some(group);
+            }
+            rows.push(if self.no_color() {
+                vec![key.clone(), value.clone()]
+            } else {
+                vec![
+                    key.blue()
+                        .to_string(),
+                    highlight_channel_value(value),
+                ]
+            });
+        }
+        Ok(format!(
+            "{}\n\n{} variables.",
+            format_table(
+                &["Key", "Value"],
+                &rows,
+                self.color_mode,
+                self.table_width()
+            ),
+            vars.len()
+        ))
+    }
+
+    /// Yellow for rows older than an hour, red past four hours
+    fn color_by_age(&self, row: Vec<String>, age: u64) -> Vec<String> {
+        let color = match age {
+            _ if self.no_color() => return row,
+            age if age > VERY_LONG_CALL_SECS => Color::Red,
+            age if age > LONG_CALL_SECS => Color::Yellow,
+            _ => return row,
+        };
+        row.into_iter()
+            .map(|cell| {
+                cell.color(color)
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// `show channels` with a computed Age column, optionally oldest first
+    async fn show_channels(&self, client: &EslClient, sort_by_age: bool) -> Result<String> {
+        let body = self
+            .api_body(client, "show channels as json")
+            .await?;
+        let channels: ChannelsResponse = serde_json::from_str(&body)
+            .map_err(|e| anyhow!("Failed to parse channels JSON: {}", e))?;
+        let now = now_epoch();
+        let mut channels: Vec<_> = channels
+            .rows
+            .iter()
+            .map(|channel| (channel, channel.age_secs(now)))
+            .collect();
+        if sort_by_age {
+            channels.sort_by_key(|&(_, age)| std::cmp::Reverse(age));
+        }
+        let rows: Vec<Vec<String>> = channels
+            .iter()
+            .map(|(channel, age)| {
+                let row = vec![
+                    channel
+                        .uuid
+                        .clone(),
+                    channel
+                        .name
+                        .clone(),
+                    channel
+                        .state
+                        .clone(),
+                    channel
+                        .cid_name
+                        .clone(),
+                    channel
+                        .cid_num
+                        .clone(),
+                    format_duration(*age),
+                ];
+                self.color_by_age(row, *age)
+            })
+            .collect();
+        Ok(format!(
+            "{}\n\n{} total.",
+            format_table(
+                &["UUID", "Name", "State", "CID Name", "CID Number", "Age"],
+                &rows,
+                self.color_mode,
+                self.table_width(),
+            ),
+            rows.len()
+        ))
+    }
+
+    /// `show calls` with a computed Duration column
+    async fn show_calls(&self, client: &EslClient) -> Result<String> {
+        let calls = self
+            .api_json_rows(client, "show calls as json")
+            .await?;
+        let now = now_epoch();
+        let field = |call: &HashMap<String, String>, key: &str| {
+            call.get(key)
+                .cloned()
+                .unwrap_or_default()
+        };
+        let rows: Vec<Vec<String>> = calls
+            .iter()
+            .map(|call| {
+                let created: u64 = call
+                    .get("created_epoch")
+                    .and_then(|e| {
+                        e.parse()
+                            .ok()
+                    })
+                    .unwrap_or(now);
+                let duration = now.saturating_sub(created);
+                let row = vec![
+                    field(call, "uuid"),
+                    field(call, "direction"),
+                    field(call, "name"),
+                    field(call, "cid_num"),
+                    field(call, "dest"),
+                    field(call, "callstate"),
+                    format_duration(duration),
+                ];
+                self.color_by_age(row, duration)
+            })
+            .collect();
+        Ok(format!(
+            "{}\n\n{} total.",
+            format_table(
+                &[
+                    "UUID",
+                    "Direction",
+                    "Name",
+                    "CID Number",
+                    "Destination",
+                    "State",
+                    "Duration"
+                ],
+                &rows,
+                self.color_mode,
+                self.table_width(),
+            ),
+            rows.len()
+        ))
+    }
+
+    /// Handle /search: compile the pattern (case-insensitive) for later output
+    fn handle_search_command(&self, command: &str) -> String {
+        let pattern = command
+            .trim_start()
+            .trim_start_matches("/search")
+            .trim();
+        if pattern.is_empty() {
+            return "Usage: /search <regex>  (/nosearch to clear)".to_string();
+        }
+        match RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+        {
+            Ok(re) => {
+                if let Ok(mut current) = self
+                    .search_pattern
+                    .lock()
+                {
+                    *current = Some(re);
+                }
+                format!("Searching output for /{}/", pattern)
+            }
+            Err(e) => format!("Invalid search pattern: {}", e),
+        }
+    }
+
+    /// Handle /source: queue the file's commands to the session, spaced by
+    /// `inter_command_delay_ms`. Sourced commands skip the dangerous command
+    /// confirmation, like `-x`.
+    fn source_file(&self, command: &str) -> Result<String> {
+        let Some((yes, path)) = source_args(command.trim()).filter(|(_, p)| !p.is_empty()) else {
+            return Ok("Usage: /source [--yes] <file>".to_string());
+        };
+        let Some(tx) = self
+            .source_tx
+            .clone()
+        else {
+            return Err(anyhow!("/source is only available in interactive mode"));
+        };
+        let commands = read_source_file(Path::new(path), self.allow_recursive_source, 0)?;
+        // Nobody is asked while a file runs, so dangerous lines need --yes
+        if let Some(dangerous) = commands
+            .iter()
+            .find(|c| {
+                self.confirm_dangerous && !yes && is_dangerous_command(c, &self.dangerous_commands)
+            })
+        {
+            return Err(anyhow!(
+                "{} contains '{}', which may be destructive; run /source --yes {} to allow it",
+                path,
+                dangerous,
+                path
+            ));
+        }
+        let count = commands.len();
+        let delay = self
+            .inter_command_delay_ms
+            .map(Duration::from_millis);
+        tokio::spawn(async move {
+            for (i, command) in commands
+                .into_iter()
+                .enumerate()
+            {
+                if let (Some(delay), true) = (delay, i > 0) {
+                    tokio::time::sleep(delay).await;
+                }
+                if tx
+                    .send(command)
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+        Ok(format!("Sourcing {} commands from {}", count, path))
+    }
+
+    /// Handle /highlight: `list`, or add a pattern with an optional color
+    fn handle_highlight_command(&self, command: &str) -> String {
+        let args = command
+            .trim_start()
+            .trim_start_matches("/highlight")
+            .trim();
+        let usage = format!(
+            "Usage: /highlight <regex> [{}]  (/highlight list, /nohighlight [n])",
+            HIGHLIGHT_COLORS.join("|")
+        );
+        if args.is_empty() {
+            return usage;
+        }
+        let Ok(mut patterns) = self
+            .highlight_patterns
+            .lock()
+        else {
+            return "Highlight patterns unavailable".to_string();
+        };
+
+        if args == "list" {
+            if patterns.is_empty() {
+                return "No highlight patterns".to_string();
+            }
+            return patterns
+                .iter()
+                .enumerate()
+                .map(|(i, (re, color))| format!("{}. /{}/ {}", i + 1, re, color))
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        // A trailing color name picks the color; the rest is the pattern
+        let (pattern, color) = match args.rsplit_once(char::is_whitespace) {
+            Some((pattern, last)) => match HIGHLIGHT_COLORS
+                .iter()
+                .find(|c| c.eq_ignore_ascii_case(last))
+            {
+                Some(color) => (pattern.trim_end(), *color),
+                None => (args, HIGHLIGHT_COLORS[0]),
+            },
+            None => (args, HIGHLIGHT_COLORS[0]),
+        };
+        match RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+        {
+            Ok(re) => {
+                patterns.push((re, color));
+                format!(
+                    "Highlighting /{}/ in {} ({})",
+                    pattern,
+                    color,
+                    patterns.len()
+                )
+            }
+            Err(e) => format!("Invalid highlight pattern: {}", e),
+        }
+    }
+
+    /// Handle /nohighlight: remove pattern `n` (1-based), or all of them
+    fn remove_highlight(&self, index: Option<&str>) -> String {
+        let Ok(mut patterns) = self
+            .highlight_patterns
+            .lock()
+        else {
+            return "Highlight patterns unavailable".to_string();
+        };
+        let Some(index) = index else {
+            patterns.clear();
+            return "Highlights cleared".to_string();
+        };
+        match index.parse::<usize>() {
+            Ok(n) if (1..=patterns.len()).contains(&n) => {
+                let (re, _) = patterns.remove(n - 1);
+                format!("Removed highlight /{}/", re)
+            }
+            _ => format!("No highlight pattern {} (see /highlight list)", index),
+        }
+    }
+
+    /// Extract uptime information from status output
+    fn extract_uptime(&self, status_output: &str) -> String {
+        for line in status_output.lines() {
+            if line.contains("UP")
+                && (line.contains("years") || line.contains("days") || line.contains("hours"))
+            {
+                return line
+                    .trim()
+                    .to_string();
+            }
+        }
+        "Uptime information not found".to_string()
+    }
+
+    /// Show help information with the effective (merged) function key bindings.
+    pub fn show_help(&self) {
+        let help_text = self.help_text();
+        let formatted_help = if !self.no_color() {
+            format!("{}", help_text.cyan())
+        } else {
+            help_text
+        };
+        self.print_message(&formatted_help);
+    }
+
+    /// The `help` text, with the current function key bindings
+    pub fn help_text(&self) -> String {
+        let macros = self.macros();
+        let mut fnkey_lines = String::new();
+        for i in 1u8..=12 {
+            let key = format!("f{}", i);
+            if let Some(cmd) = macros.get(&key) {
+                fnkey_lines.push_str(&format!("  F{:<3} = {}\n", i, cmd));
+            }
+        }
+
+        format!(
+            r#"
+FreeSWITCH CLI Commands:
+
+Basic Commands:
+  status                    - Show system status
+  version                   - Show FreeSWITCH version
+  uptime                    - Show system uptime
+
+Show Commands:
+  show channels [sort-by=age]
+                            - List active channels with their age
+  show channels count       - Show channel count
+  show calls                - Show active calls
+  show tasks                - Show scheduler tasks with time to next run
+  show version              - Show fs_cli-rs and FreeSWITCH versions
+  show api [filter]         - List API commands, optionally only names containing filter
+  show registrations [--profile <name>]
+                            - Show SIP registrations with time to expiry
+  show modules [name]       - List loaded modules, or show one module's interfaces
+  show interfaces [type|module]
+                            - List interfaces, e.g. show interfaces mod_sofia
+
+Control Commands:
+  reload [module]           - Reload module or XML config
+  originate <url> <dest>    - Originate a call
+  conference <name> list [count]
+                            - Show conference members (or just their count)
+
+Function Key Shortcuts (customizable in config):
+{}
+Built-in Commands:
+  /help                     - Show this help
+  /quit, /exit, /bye        - Exit the CLI
+  /history [count]          - Show the last commands (default 20)
+  /clear-history            - Forget the command history
+  /history-size <entries>   - Limit how many commands the history keeps
+  /clear                    - Clear screen
+  /dtmf <uuid> <digits> [ms]
+                            - Send DTMF (0-9 * # A-D), 500 ms per digit by default
+  /export-channels <file>   - Write active channels to a CSV file
+  /export-calls <file>      - Write active calls to a CSV file
+  /export-db [file]         - Save active channels to SQLite (default ~/.fs_cli_channels.db)
+  /ping [count]             - Time round trips of the status API (default 5)
+  /search <regex>           - Highlight matches in subsequent output
+  /nosearch                 - Stop highlighting search matches
+  /api-search <query>       - Find API commands whose name contains query
+  /originate-wizard         - Build an originate command step by step
+  /hupall [cause]           - Hang up every channel after confirming (NORMAL_CLEARING by default)
+  /hupall-force [cause]     - Hang up every channel without confirmation
+  /reloadxml-diff           - Run reloadxml and show what changed in the XML configuration
+  /output-format [raw|json|table|xml]
+                            - Show or set how API responses are rendered
+  /channel-vars <uuid> [regex]
+                            - Show a channel's variables, or those whose name matches
+  /source [--yes] <file>    - Run the commands in a file (# starts a comment);
+                              --yes allows dangerous commands
+  /highlight <regex> [color]
+                            - Color matches in all output (cyan by default)
+  /highlight list           - List highlight patterns
+  /nohighlight [n]          - Remove highlight pattern n, or all
+  /connect <name> <host:port> [password]
+                            - Open another FreeSWITCH connection
+  /switch <name>            - Send commands to connection <name> (main = startup)
+  /disconnect <name>        - Close a named connection
+  /connections              - List connections (* = active)
+  /wait-event <type> [ms]   - Wait for an event such as CHANNEL_ANSWER (default 30000 ms)
+  /subscribe <type>...      - Subscribe to more event types, e.g. DTMF
+  /unsubscribe <type>...    - Stop receiving event types
+  /subscriptions            - List subscribed event types
+  /bgapi <command>          - Run a command in the background, printing its result when done
+  /macros                   - List function key bindings
+  /set-macro <fN> <command> - Bind a function key (F1-F12) for this session
+  /version                  - Show fs_cli-rs and FreeSWITCH versions
+  /jobs                     - List pending background jobs and scheduled commands
+  /schedule <ms> <command>  - Run a command after a delay
+  /unschedule-all           - Cancel all scheduled commands
+  /refresh                  - Refetch channels on the next UUID completion
+  /save-config              - Write the configuration back to its file
+  /create-profile <name>    - Add a connection profile (prompts for host, port, password)
+  /delete-profile <name>    - Remove a connection profile
+
+Configuration:
+  Profiles can be configured in ~/.config/fs_cli.yaml or /etc/freeswitch/fs_cli.yaml
+  Use --config to specify a custom configuration file path
+  Use --list-profiles to see available profiles
+  Default configuration is created automatically if missing
+
+You can execute any FreeSWITCH API command directly.
+Use Tab for command completion and Up/Down arrows for history.
+"#,
+            fnkey_lines
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ping_summary_without_loss() {
+        let rtts = [
+            Duration::from_millis(1),
+            Duration::from_millis(2),
+            Duration::from_millis(3),
+        ];
+        assert_eq!(
+            format_ping_summary("freeswitch@pbx:8021", 3, &rtts),
+            "PING freeswitch@pbx:8021: 3 packets sent, 3 received, \
+             min/avg/max = 1.00ms/2.00ms/3.00ms"
+        );
+    }
+
+    #[test]
+    fn ping_summary_with_loss() {
+        let rtts = [Duration::from_millis(4)];
+        assert_eq!(
+            format_ping_summary("freeswitch@pbx:8021", 4, &rtts),
+            "PING freeswitch@pbx:8021: 4 packets sent, 1 received, 75% packet loss, \
+             min/avg/max = 4.00ms/4.00ms/4.00ms"
+        );
+        assert_eq!(
+            format_ping_summary("freeswitch@pbx:8021", 2, &[]),
+            "PING freeswitch@pbx:8021: 2 packets sent, 0 received, 100% packet loss"
+        );
+    }
+
+    #[test]
+    fn ping_target_follows_switch() {
+        let processor = CommandProcessor::new(
+            &ProfileConfig::default()
+                .to_app_config()
+                .unwrap(),
+        );
+        processor.set_ping_target("pbx2", 8022);
+        assert_eq!(
+            *processor
+                .ping_target
+                .lock()
+                .unwrap(),
+            "freeswitch@pbx2:8022"
+        );
+    }
+
+    #[test]
+    fn dangerous_command_matching() {
+        let dangerous: Vec<String> = crate::config::DEFAULT_DANGEROUS_COMMANDS
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert!(is_dangerous_command("hupall", &dangerous));
+        assert!(is_dangerous_command("hupall NORMAL_CLEARING", &dangerous));
+        assert!(is_dangerous_command("/schedule 0 hupall", &dangerous));
+        assert!(is_dangerous_command(
+            "/schedule  5000  HUPALL USER_BUSY",
+            &dangerous
+        ));
+        assert!(!is_dangerous_command("/schedule 0 status", &dangerous));
+        assert!(is_dangerous_command("FSCTL   shutdown restart", &dangerous));
+        assert!(!is_dangerous_command("fsctl pause", &dangerous));
+        assert!(!is_dangerous_command("shutdownx", &dangerous));
+        assert!(!is_dangerous_command("show calls", &dangerous));
+    }
+
+    #[test]
+    fn dry_run_passthrough_only_client_side() {
+        assert!(is_dry_run_passthrough("/log debug"));
+        assert!(is_dry_run_passthrough("/search timeout"));
+        assert!(!is_dry_run_passthrough("/dtmf 0f2a 123"));
+        assert!(!is_dry_run_passthrough("hupall"));
+        assert!(!is_dry_run_passthrough("/logs"));
+        assert!(!is_dry_run_passthrough("/delete-profile lab"));
+    }
+
+    #[test]
+    fn special_commands_include_locally_rendered_api() {
+        assert!(is_special_command("/jobs"));
+        assert!(is_special_command("show channels"));
+        assert!(is_special_command("SHOW calls"));
+        assert!(is_special_command("log debug"));
+        assert!(is_special_command("uptime"));
+        assert!(!is_special_command("status"));
+        assert!(!is_special_command("sofia status"));
+        assert!(!is_special_command(""));
+    }
+
+    #[test]
+    fn color_mode_disabled_when_not_a_terminal() {
+        assert_eq!(ColorMode::Line.for_output(false), ColorMode::Never);
+        assert_eq!(ColorMode::Tag.for_output(false), ColorMode::Never);
+        assert_eq!(ColorMode::Never.for_output(false), ColorMode::Never);
+    }
+
+    #[test]
+    fn log_level_from_number() {
+        assert_eq!(LogLevel::from_number(0), Some(LogLevel::Console));
+        assert_eq!(LogLevel::from_number(3), Some(LogLevel::Err));
+        assert_eq!(LogLevel::from_number(7), Some(LogLevel::Debug));
+        assert_eq!(LogLevel::from_number(8), Some(LogLevel::Debug1));
+        assert_eq!(LogLevel::from_number(17), Some(LogLevel::Debug10));
+        assert_eq!(LogLevel::from_number(101), Some(LogLevel::Debug1));
+        assert_eq!(LogLevel::from_number(110), Some(LogLevel::Debug10));
+        assert_eq!(LogLevel::from_number(18), None);
+        assert_eq!(LogLevel::from_number(64), None);
+    }
+
+    #[test]
+    fn color_mode_auto_resolution() {
+        assert_eq!(ColorMode::Auto.resolve(true, false), ColorMode::Line);
+        assert_eq!(ColorMode::Auto.resolve(true, true), ColorMode::Never);
+        assert_eq!(ColorMode::Auto.resolve(false, false), ColorMode::Never);
+        // NO_COLOR only changes the default, not an explicit choice
+        assert_eq!(ColorMode::Tag.resolve(true, true), ColorMode::Tag);
+        assert_eq!("AUTO".parse::<ColorMode>(), Ok(ColorMode::Auto));
+    }
+
+    #[test]
+    fn color_mode_kept_on_terminal() {
+        assert_eq!(ColorMode::Line.for_output(true), ColorMode::Line);
+        assert_eq!(ColorMode::Tag.for_output(true), ColorMode::Tag);
+        assert_eq!(ColorMode::Never.for_output(true), ColorMode::Never);
+        assert_eq!(ColorMode::Bright.for_output(true), ColorMode::Bright);
+        assert_eq!("bright".parse::<ColorMode>(), Ok(ColorMode::Bright));
+        assert_eq!(ColorMode::Bright.to_string(), "bright");
+    }
+
+    #[test]
+    fn pretty_json_indents_channels_response() {
+        let body = r#"{"row_count":1,"rows":[{"uuid":"a1b2","name":"sofia/internal/1000@example.com","state":"CS_EXECUTE"}]}"#;
+        let pretty = pretty_json(body).unwrap();
+        assert!(pretty.contains('\n'));
+        assert!(pretty.contains("  \"row_count\": 1"));
+        assert!(pretty.contains("      \"uuid\": \"a1b2\""));
+    }
+
+    #[test]
+    fn output_format_parses_case_insensitively() {
+        assert_eq!("JSON".parse::<OutputFormat>(), Ok(OutputFormat::Json));
+        assert_eq!("table".parse::<OutputFormat>(), Ok(OutputFormat::Table));
+        assert!("yaml"
+            .parse::<OutputFormat>()
+            .is_err());
+    }
+
+    const SHOW_CALLS_CSV: &str =
+        "uuid,direction,cid_num\na1b2,inbound,1000\nc3d4,outbound,1001\n\n2 total.\n";
+
+    #[test]
+    fn versions_table_lists_client_and_server() {
+        let out = format_versions(
+            "FreeSWITCH Version 1.10.12-release (git a88d069 2024-08-02 21:02:27Z 64bit)\n",
+            ColorMode::Never,
+            120,
+        );
+        let lines: Vec<&str> = out
+            .lines()
+            .collect();
+        assert!(lines[0].starts_with("Component"));
+        let client = lines
+            .iter()
+            .find(|line| line.starts_with("fs_cli-rs"))
+            .unwrap();
+        assert!(client.contains(env!("CARGO_PKG_VERSION")));
+        let server = lines
+            .iter()
+            .find(|line| line.starts_with("FreeSWITCH"))
+            .unwrap();
+        assert!(server.contains("1.10.12-release"));
+    }
+
+    #[test]
+    fn transform_output_show_csv_to_json() {
+        let out = transform_output(SHOW_CALLS_CSV, OutputFormat::Json, ColorMode::Never, 120);
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(value[1]["cid_num"], "1001");
+        assert_eq!(value[0]["direction"], "inbound");
+    }
+
+    #[test]
+    fn transform_output_json_rows_to_table() {
+        let body =
+            r#"{"row_count":2,"rows":[{"uuid":"a1b2","state":"CS_EXECUTE"},{"uuid":"c3d4"}]}"#;
+        let out = transform_output(body, OutputFormat::Table, ColorMode::Never, 120);
+        let lines: Vec<&str> = out
+            .lines()
+            .collect();
+        assert!(lines[0].starts_with("uuid"));
+        assert!(lines[0].contains("state"));
+        assert!(out.contains("CS_EXECUTE"));
+        assert!(out.contains("c3d4"));
+    }
+
+    #[test]
+    fn transform_output_show_csv_to_table() {
+        let out = transform_output(SHOW_CALLS_CSV, OutputFormat::Table, ColorMode::Never, 120);
+        assert!(out.contains("direction"));
+        assert!(out.contains("outbound"));
+        assert!(!out.contains("total."));
+    }
+
+    #[test]
+    fn transform_output_indents_xml() {
+        let body = "<?xml version=\"1.0\"?><result><row id=\"1\"><name>sofia</name><empty/></row></result>";
+        let out = transform_output(body, OutputFormat::Xml, ColorMode::Never, 120);
+        assert_eq!(
+            out,
+            "<?xml version=\"1.0\"?>\n<result>\n  <row id=\"1\">\n    <name>sofia</name>\n    <empty/>\n  </row>\n</result>"
+        );
+    }
+
+    #[test]
+    fn transform_output_falls_back_to_raw() {
+        for fmt in [OutputFormat::Json, OutputFormat::Table, OutputFormat::Xml] {
+            assert_eq!(
+                transform_output("+OK accepted", fmt, ColorMode::Never, 120),
+                "+OK accepted"
+            );
+        }
+        assert_eq!(
+            transform_output("<a><b></a>", OutputFormat::Xml, ColorMode::Never, 120),
+            "<a><b></a>"
+        );
+        assert_eq!(
+            transform_output("{broken", OutputFormat::Table, ColorMode::Never, 120),
+            "{broken"
+        );
+    }
+
+    #[test]
+    fn pretty_json_ignores_non_json() {
+        assert!(pretty_json("+OK accepted").is_none());
+        assert!(pretty_json("UP 0 years, 1 day").is_none());
+        assert!(pretty_json("{not json").is_none());
+    }
+
+    fn interface(kind: &str, name: &str, module: &str) -> HashMap<String, String> {
+        HashMap::from([
+            ("type".to_string(), kind.to_string()),
+            ("name".to_string(), name.to_string()),
+            ("ikey".to_string(), module.to_string()),
+            (
+                "filename".to_string(),
+                format!("/usr/lib/freeswitch/mod/{}.so", module),
+            ),
+        ])
+    }
+
+    #[test]
+    fn summarize_module_groups_interfaces() {
+        let interfaces = vec![
+            interface("api", "sofia_contact", "mod_sofia"),
+            interface("endpoint", "sofia", "mod_sofia"),
+            interface("api", "sofia", "mod_sofia"),
+            interface("application", "sofia_sla", "mod_sofia"),
+            interface("api", "conference", "mod_conference"),
+        ];
+        let summary = summarize_module(&interfaces, "mod_sofia");
+        assert_eq!(
+            summary,
+            ModuleSummary {
+                path: Some("/usr/lib/freeswitch/mod/mod_sofia.so".to_string()),
+                apis: vec!["sofia".to_string(), "sofia_contact".to_string()],
+                applications: vec!["sofia_sla".to_string()],
+                other: vec!["endpoint sofia".to_string()],
+            }
+        );
+        assert_eq!(
+            summarize_module(&interfaces, "mod_nope"),
+            ModuleSummary::default()
+        );
+    }
+
+    #[test]
+    fn hupall_command_default_cause() {
+        assert_eq!(hupall_command(None), "hupall NORMAL_CLEARING");
+        assert_eq!(hupall_command(Some("user_busy")), "hupall USER_BUSY");
+    }
+
+    #[test]
+    fn diff_lines_reports_removed_then_added() {
+        let old = "<a>\n<b x=\"1\"/>\n<c/>\n</a>";
+        let new = "<a>\n<c/>\n<b x=\"2\"/>\n</a>";
+        assert_eq!(
+            diff_lines(old, new),
+            vec![('-', "<b x=\"1\"/>"), ('+', "<b x=\"2\"/>")]
+        );
+        assert!(diff_lines(old, old).is_empty());
+    }
+
+    #[test]
+    fn diff_lines_counts_repeated_lines() {
+        assert_eq!(diff_lines("x\nx\ny", "x\ny"), vec![('-', "x")]);
+        assert_eq!(diff_lines("", "x"), vec![('+', "x")]);
+    }
+
+    #[test]
+    fn format_expires_in_countdown_or_expired() {
+        assert_eq!(format_expires_in(0), "00:00:00");
+        assert_eq!(format_expires_in(3725), "01:02:05");
+        assert_eq!(format_expires_in(-1), "expired");
+    }
+
+    #[test]
+    fn format_next_run_future_and_overdue() {
+        assert_eq!(format_next_run(0), "+0m 0s");
+        assert_eq!(format_next_run(125), "+2m 5s");
+        assert_eq!(format_next_run(-61), "overdue by 1m 1s");
+    }
+
+    fn api_row(name: &str, description: &str) -> HashMap<String, String> {
+        HashMap::from([
+            ("name".to_string(), name.to_string()),
+            ("description".to_string(), description.to_string()),
+            ("syntax".to_string(), String::new()),
+        ])
+    }
+
+    #[test]
+    fn filter_api_commands_sorts_and_filters_ignoring_case() {
+        let commands = vec![
+            api_row("uuid_kill", "Kill channel"),
+            api_row("status", "Show current status"),
+            api_row("uuid_bridge", "Bridge channels"),
+        ];
+        let all = filter_api_commands(&commands, None);
+        let names: Vec<&str> = all
+            .iter()
+            .map(|row| row[0].as_str())
+            .collect();
+        assert_eq!(names, ["status", "uuid_bridge", "uuid_kill"]);
+
+        let filtered = filter_api_commands(&commands, Some("UUID"));
+        assert_eq!(
+            filtered,
+            vec![
+                vec!["uuid_bridge".to_string(), "Bridge channels".to_string()],
+                vec!["uuid_kill".to_string(), "Kill channel".to_string()],
+            ]
+        );
+        assert!(filter_api_commands(&commands, Some("nothing")).is_empty());
+    }
+
+    #[test]
+    fn apply_search_counts_matches_without_color() {
+        let re = RegexBuilder::new("mod_sofia")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        let out = apply_search("mod_sofia\nmod_conference\nMOD_SOFIA", &re, false);
+        assert_eq!(out, "(2 matches)\nmod_sofia\nmod_conference\nMOD_SOFIA");
+    }
+
+    #[test]
+    fn apply_search_wraps_matches_in_color() {
+        colored::control::set_override(true);
+        let re = Regex::new("sofia").unwrap();
+        let out = apply_search("reload mod_sofia", &re, true);
+        assert!(out.starts_with("(1 matches)\n"));
+        assert!(out.contains("\x1b["));
+        assert!(out.contains("sofia"));
+    }
+
+    #[test]
+    fn apply_highlights_colors_each_pattern() {
+        colored::control::set_override(true);
+        let patterns = vec![
+            (Regex::new("1000").unwrap(), "red"),
+            (Regex::new("a1b2").unwrap(), "cyan"),
+        ];
+        let out = apply_highlights("a1b2 sofia/internal/1000@example.com", &patterns);
+        assert!(out.contains(
+            &"1000"
+                .red()
+                .bold()
+                .to_string()
+        ));
+        assert!(out.contains(
+            &"a1b2"
+                .cyan()
+                .bold()
+                .to_string()
+        ));
+        assert!(out.contains(" sofia/internal/"));
+    }
+
+    #[test]
+    fn apply_highlights_skips_overlaps() {
+        colored::control::set_override(true);
+        let patterns = vec![
+            (Regex::new("internal").unwrap(), "green"),
+            (Regex::new("tern").unwrap(), "red"),
+        ];
+        let out = apply_highlights("sofia/internal", &patterns);
+        assert_eq!(
+            out,
+            format!(
+                "sofia/{}",
+                "internal"
+                    .green()
+                    .bold()
+            )
+        );
+    }
+
+    #[test]
+    fn highlight_commands() {
+        let processor = CommandProcessor::new(
+            &ProfileConfig::default()
+                .to_app_config()
+                .unwrap(),
+        );
+        assert!(processor
+            .handle_highlight_command("/highlight 1000")
+            .contains("cyan"));
+        assert!(processor
+            .handle_highlight_command("/highlight sofia internal Red")
+            .contains("/sofia internal/ in red"));
+        assert!(processor
+            .handle_highlight_command("/highlight (")
+            .starts_with("Invalid"));
+        assert_eq!(
+            processor.handle_highlight_command("/highlight list"),
+            "1. /1000/ cyan\n2. /sofia internal/ red"
+        );
+        assert!(processor
+            .remove_highlight(Some("3"))
+            .starts_with("No highlight pattern 3"));
+        assert_eq!(
+            processor.remove_highlight(Some("1")),
+            "Removed highlight /1000/"
+        );
+        assert_eq!(processor.remove_highlight(None), "Highlights cleared");
+        assert_eq!(
+            processor.handle_highlight_command("/highlight list"),
+            "No highlight patterns"
+        );
+    }
+
+    #[test]
+    fn source_file_skips_comments_and_blank_lines() {
+        let dir = std::env::temp_dir().join(format!("fs_cli_source_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("init.fs");
+        std::fs::write(
+            &script,
+            "# setup\n\nstatus\n  sofia status  \n#/log debug\n",
+        )
+        .unwrap();
+        let commands = read_source_file(&script, false, 0);
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(commands.unwrap(), vec!["status", "sofia status"]);
+    }
+
+    #[test]
+    fn source_args_yes_flag() {
+        assert_eq!(source_args("/source init.fs"), Some((false, "init.fs")));
+        assert_eq!(
+            source_args("/source --yes  init.fs"),
+            Some((true, "init.fs"))
+        );
+        assert_eq!(source_args("/source --yes"), Some((true, "")));
+        assert_eq!(
+            source_args("/source --yesterday.fs"),
+            Some((false, "--yesterday.fs"))
+        );
+        assert_eq!(source_args("/sourced x"), None);
+    }
+
+    #[tokio::test]
+    async fn source_file_refuses_dangerous_commands_without_yes() {
+        let dir = std::env::temp_dir().join(format!("fs_cli_source_danger_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("cleanup.fs");
+        std::fs::write(
+            &script,
+            "status
+hupall NORMAL_CLEARING
+",
+        )
+        .unwrap();
+        let mut processor = CommandProcessor::new(
+            &ProfileConfig::default()
+                .to_app_config()
+                .unwrap(),
+        );
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        processor.set_source_tx(tx);
+
+        let refused = processor.source_file(&format!("/source {}", script.display()));
+        let allowed = processor.source_file(&format!("/source --yes {}", script.display()));
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(refused
+            .unwrap_err()
+            .to_string()
+            .contains("hupall NORMAL_CLEARING"));
+        assert!(allowed.is_ok());
+        assert_eq!(
+            rx.recv()
+                .await
+                .as_deref(),
+            Some("status")
+        );
+        assert_eq!(
+            rx.recv()
+                .await
+                .as_deref(),
+            Some("hupall NORMAL_CLEARING")
+        );
+    }
+
+    #[test]
+    fn source_file_nesting() {
+        let dir = std::env::temp_dir().join(format!("fs_cli_source_nested_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let outer = dir.join("outer.fs");
+        std::fs::write(&outer, "status\n/source inner.fs\nversion\n").unwrap();
+        std::fs::write(dir.join("inner.fs"), "show calls\n").unwrap();
+        let looping = dir.join("loop.fs");
+        std::fs::write(&looping, "/source loop.fs\n").unwrap();
+
+        let rejected = read_source_file(&outer, false, 0);
+        let expanded = read_source_file(&outer, true, 0);
+        let too_deep = read_source_file(&looping, true, 0);
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(rejected
+            .unwrap_err()
+            .to_string()
+            .contains("--allow-recursive-source"));
+        assert_eq!(expanded.unwrap(), vec!["status", "show calls", "version"]);
+        assert!(too_deep
+            .unwrap_err()
+            .to_string()
+            .contains("nested more than 5 levels"));
+    }
+
+    #[test]
+    fn conference_members_parse() {
+        let body =
+            "1;sofia/internal/1000@example.com;0f2a;Alice;1000;hear|speak|talking|floor;0;0;300\n\
+                    2;sofia/internal/1001@example.com;1e3b;Bob;1001;hear;0;0;100\n";
+        let members = parse_conference_members(body);
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].cid_name, "Alice");
+        assert!(members[0].has_floor());
+        assert_eq!(members[0].state(), "talking");
+        assert_eq!(members[1].state(), "muted");
+        assert!(!members[1].has_floor());
+        assert_eq!(members[1].energy, "100");
+    }
+
+    #[test]
+    fn conference_members_skip_malformed_lines() {
+        assert!(parse_conference_members("-ERR Conference 3000 not found").is_empty());
+        assert!(parse_conference_members("").is_empty());
+    }
+
+    const UUID: &str = "0f2a4b6c-8d9e-4f01-a2b3-c4d5e6f7a8b9";
+
+    #[test]
+    fn dtmf_command_default_duration() {
+        assert_eq!(
+            build_dtmf_command(&[UUID, "123#"]).unwrap(),
+            format!("uuid_send_dtmf {} 123#@500", UUID)
+        );
+    }
+
+    #[test]
+    fn dtmf_command_explicit_duration() {
+        assert_eq!(
+            build_dtmf_command(&[UUID, "*9AD", "250"]).unwrap(),
+            format!("uuid_send_dtmf {} *9AD@250", UUID)
+        );
+    }
+
+    #[test]
+    fn dtmf_command_rejects_bad_input() {
+        assert!(build_dtmf_command(&["not-a-uuid", "1"]).is_err());
+        assert!(build_dtmf_command(&[&UUID.to_uppercase(), "1"]).is_err());
+        assert!(build_dtmf_command(&[UUID, "12E"]).is_err());
+        assert!(build_dtmf_command(&[UUID, "1", "0"]).is_err());
+        assert!(build_dtmf_command(&[UUID, "1", "fast"]).is_err());
+        assert!(build_dtmf_command(&[UUID]).is_err());
+    }
+
+    #[test]
+    fn log_level_parse_all_valid() {
+        for level in LogLevel::all_variants() {
+            let parsed: Result<LogLevel, _> = level
+                .as_str()
+                .parse();
+            assert!(parsed.is_ok(), "failed to parse '{}'", level.as_str());
+            assert_eq!(parsed.unwrap(), *level);
+        }
+    }
+
+    #[test]
+    fn log_level_parse_invalid_returns_error() {
+        let result: Result<LogLevel, _> = "hello".parse();
+        assert!(result.is_err());
+    }
 
     #[test]
     fn log_level_parse_case_insensitive() {
@@ -476,4 +3797,190 @@ mod tests {
         assert_eq!(LogLevel::Debug as u8, 7);
         assert_eq!(LogLevel::NoLog as u8, 18);
     }
+
+    #[test]
+    fn create_and_delete_profile_saves_config() {
+        let dir = std::env::temp_dir().join(format!("fs_cli_profile_test_{}", std::process::id()));
+        let path = dir.join("fs_cli.yaml");
+        let mut config = ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        config.loaded_config = Some(LoadedConfig {
+            path: path.clone(),
+            config: std::sync::Arc::new(Mutex::new(FsCliConfig::default())),
+        });
+        let processor = CommandProcessor::new(&config);
+
+        let created = processor.create_profile("/create-profile lab 10.0.0.5 8022 pass word");
+        let duplicate = processor.create_profile("/create-profile lab 10.0.0.6 8021 x");
+        let (saved, _) = FsCliConfig::load(Some(path.clone()), None).unwrap();
+        let deleted = processor.delete_profile(&["lab"]);
+        let missing = processor.delete_profile(&["lab"]);
+        let (after_delete, _) = FsCliConfig::load(Some(path.clone()), None).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(created
+            .unwrap()
+            .contains("password ****"));
+        assert!(duplicate.is_err());
+        let lab = saved
+            .get_profile("lab")
+            .unwrap();
+        assert_eq!(lab.host, Some("10.0.0.5".to_string()));
+        assert_eq!(lab.port, Some(8022));
+        assert_eq!(lab.password, Some("pass word".to_string()));
+        assert!(deleted.is_ok());
+        assert!(missing.is_err());
+        assert!(after_delete
+            .get_profile("lab")
+            .is_err());
+    }
+
+    #[test]
+    fn create_profile_rejects_bad_port() {
+        let processor = CommandProcessor::new(
+            &ProfileConfig::default()
+                .to_app_config()
+                .unwrap(),
+        );
+        assert!(processor
+            .create_profile("/create-profile lab host 0 pw")
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn schedule_limits_and_unschedules() {
+        let mut processor = CommandProcessor::new(
+            &ProfileConfig::default()
+                .to_app_config()
+                .unwrap(),
+        );
+        assert!(processor
+            .schedule_command("/schedule 100 status")
+            .is_err());
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        processor.set_source_tx(tx);
+        assert!(processor
+            .schedule_command("/schedule soon status")
+            .unwrap()
+            .starts_with("Usage"));
+        processor
+            .schedule_command("/schedule 0 status")
+            .unwrap();
+        assert_eq!(
+            rx.recv()
+                .await,
+            Some("status".to_string())
+        );
+        processor.unschedule_all();
+
+        for _ in 0..MAX_SCHEDULED_TASKS {
+            processor
+                .schedule_command("/schedule 60000 fsctl pause")
+                .unwrap();
+        }
+        assert!(processor
+            .schedule_command("/schedule 60000 version")
+            .is_err());
+        assert!(processor
+            .list_jobs()
+            .contains("scheduled  fsctl pause  (in "));
+        assert_eq!(processor.unschedule_all(), MAX_SCHEDULED_TASKS);
+        assert_eq!(processor.list_jobs(), "No pending jobs");
+    }
+
+    #[test]
+    fn uuid_dump_parsed_and_grouped() {
+        let body = "Channel-State: CS_EXECUTE\n\
+                    variable_sip_from_user: 1000\n\
+                    Caller-ANI: 1000\n\
+                    variable_sip_h_X-Foo: a=b: c\n\
+                    variable_rtp_use_codec_rate=8000\n\
+                    Unique-ID: 0f2a\n\
+                    \n";
+        let vars = parse_uuid_dump(body);
+        let keys: Vec<&str> = vars
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "Caller-ANI",
+                "Channel-State",
+                "variable_rtp_use_codec_rate",
+                "variable_sip_from_user",
+                "variable_sip_h_X-Foo",
+                "Unique-ID"
+            ]
+        );
+        assert_eq!(vars[4].1, "a=b: c");
+        assert_eq!(channel_var_group("Unique-ID"), "unique");
+        assert_eq!(channel_var_group("answered"), "other");
+    }
+
+    #[test]
+    fn channel_values_highlighted_by_kind() {
+        colored::control::set_override(true);
+        assert_eq!(
+            highlight_channel_value("7f4de4bc-17d7-11dd-b7a0-db4edd065621"),
+            "7f4de4bc-17d7-11dd-b7a0-db4edd065621"
+                .magenta()
+                .to_string()
+        );
+        assert_eq!(
+            highlight_channel_value("10.0.0.5:5060"),
+            "10.0.0.5:5060"
+                .cyan()
+                .to_string()
+        );
+        assert_eq!(
+            highlight_channel_value("1712345678"),
+            "1712345678"
+                .yellow()
+                .to_string()
+        );
+        assert_eq!(highlight_channel_value("CS_EXECUTE"), "CS_EXECUTE");
+    }
+
+    #[test]
+    fn set_macro_updates_map_and_readline() {
+        let mut processor = CommandProcessor::new(
+            &ProfileConfig::default()
+                .to_app_config()
+                .unwrap(),
+        );
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        processor.set_macro_update_tx(tx);
+
+        assert_eq!(
+            processor
+                .set_macro("/set-macro F3 sofia status")
+                .unwrap(),
+            "F3 = sofia status"
+        );
+        assert_eq!(
+            processor
+                .macros()
+                .get("f3")
+                .map(String::as_str),
+            Some("sofia status")
+        );
+        assert_eq!(
+            rx.try_recv()
+                .unwrap(),
+            ("f3".to_string(), "sofia status".to_string())
+        );
+        assert!(processor
+            .list_macros()
+            .contains("sofia status"));
+        assert!(processor
+            .set_macro("/set-macro f13 status")
+            .is_err());
+        assert!(processor
+            .set_macro("/set-macro f3")
+            .unwrap()
+            .starts_with("Usage"));
+    }
 }
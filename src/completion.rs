@@ -1,11 +1,10 @@
 //! Tab completion support for fs_cli-rs
 
-use crate::console_complete::Completion;
+use crate::commands::LogLevel;
+use crate::console_complete::{translate_client_alias, Completion};
 use crate::esl_debug::EslDebugLevel;
 use crate::readline::CompletionRequest;
-use rustyline::completion::{
-    extract_word, longest_common_prefix, Completer, FilenameCompleter, Pair,
-};
+use rustyline::completion::{extract_word, Completer, FilenameCompleter, Pair};
 use rustyline::highlight::{CmdKind, Highlighter, MatchingBracketHighlighter};
 use rustyline::hint::{Hinter, HistoryHinter};
 use rustyline::validate::{self, MatchingBracketValidator, Validator};
@@ -29,6 +28,44 @@ fn add_trailing_space(candidates: &mut [Pair]) {
     }
 }
 
+/// Completion for a console_complete `write=` directive, replacing the line
+/// from position 0. `text` is the whole new line when it extends what was
+/// typed, and is appended to it otherwise.
+fn write_directive_pair(typed: &str, text: &str) -> Pair {
+    let mut replacement = if text.starts_with(typed) {
+        text.to_string()
+    } else {
+        format!("{}{}", typed, text)
+    };
+    if !replacement.ends_with(' ') {
+        replacement.push(' ');
+    }
+    Pair {
+        display: text.to_string(),
+        replacement,
+    }
+}
+
+/// Longest prefix shared by all `words`, cut on a char boundary. Empty when
+/// `words` is empty.
+fn find_common_prefix(words: &[&str]) -> String {
+    let Some((first, rest)) = words.split_first() else {
+        return String::new();
+    };
+    let mut len = first.len();
+    for word in rest {
+        len = first
+            .char_indices()
+            .zip(word.chars())
+            .take_while(|((_, a), b)| a == b)
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0)
+            .min(len);
+    }
+    first[..len].to_string()
+}
+
 const FS_COMMANDS: &[&str] = &[
     // Basic commands
     "status",
@@ -108,6 +145,55 @@ const FS_COMMANDS: &[&str] = &[
     "global_setvar",
 ];
 
+/// Client-side commands with a short description, offered after `/`
+const BUILTIN_COMMANDS: &[(&str, &str)] = &[
+    ("/api-search", "find API commands by name"),
+    ("/bgapi", "run a command in the background"),
+    ("/bye", "exit"),
+    ("/channel-vars", "show a channel's variables"),
+    ("/clear", "clear the screen"),
+    ("/clear-history", "forget the command history"),
+    ("/connect", "open another connection"),
+    ("/connections", "list connections"),
+    ("/create-profile", "save a connection profile"),
+    ("/delete-profile", "remove a connection profile"),
+    ("/disconnect", "close a connection"),
+    ("/dtmf", "send DTMF to a channel"),
+    ("/exit", "exit"),
+    ("/export-calls", "write calls to CSV"),
+    ("/export-channels", "write channels to CSV"),
+    ("/export-db", "write channels to SQLite"),
+    ("/help", "show help"),
+    ("/highlight", "highlight a pattern"),
+    ("/history", "show command history"),
+    ("/history-size", "limit the command history"),
+    ("/hupall", "hang up every channel after confirming"),
+    ("/hupall-force", "hang up every channel"),
+    ("/jobs", "list background jobs"),
+    ("/log", "set the log level"),
+    ("/macros", "list function key bindings"),
+    ("/nohighlight", "remove highlights"),
+    ("/nosearch", "clear the search"),
+    ("/originate-wizard", "build an originate step by step"),
+    ("/output-format", "raw, json, table or xml"),
+    ("/ping", "check the connection"),
+    ("/quit", "exit"),
+    ("/refresh", "clear the completion cache"),
+    ("/reloadxml-diff", "reloadxml and show what changed"),
+    ("/save-config", "write the configuration file"),
+    ("/schedule", "run a command after a delay"),
+    ("/search", "emphasize a pattern"),
+    ("/set-macro", "bind a function key"),
+    ("/source", "run commands from a file"),
+    ("/subscribe", "subscribe to events"),
+    ("/subscriptions", "list subscribed events"),
+    ("/switch", "change the active connection"),
+    ("/unschedule-all", "cancel scheduled commands"),
+    ("/unsubscribe", "unsubscribe from events"),
+    ("/version", "show client and server versions"),
+    ("/wait-event", "wait for an event"),
+];
+
 /// FreeSWITCH CLI completer with command suggestions
 pub struct FsCliCompleter {
     filename_completer: FilenameCompleter,
@@ -176,6 +262,52 @@ impl FsCliCompleter {
         Ok((pos, matches))
     }
 
+    /// Built-in commands while typing the first word of a `/` command.
+    /// Returns `None` for any other position.
+    fn complete_builtin_command(&self, line: &str, pos: usize) -> Option<(usize, Vec<Pair>)> {
+        let before_cursor = line.get(..pos)?;
+        let partial = before_cursor.trim_start();
+        if !partial.starts_with('/') || partial.contains(' ') {
+            return None;
+        }
+        let candidates = BUILTIN_COMMANDS
+            .iter()
+            .filter(|(command, _)| command.starts_with(partial))
+            .map(|(command, description)| Pair {
+                display: format!("{} ({})", command, description),
+                replacement: format!("{} ", command),
+            })
+            .collect();
+        Some((pos - partial.len(), candidates))
+    }
+
+    /// Log levels for `/log <level>`, known without asking FreeSWITCH.
+    /// Returns `None` when the cursor is not on the `/log` argument.
+    fn complete_log_level(&self, line: &str, pos: usize) -> Option<(usize, Vec<Pair>)> {
+        let before_cursor = line.get(..pos)?;
+        let partial = before_cursor
+            .trim_start()
+            .strip_prefix("/log ")?
+            .trim_start();
+        if partial.contains(' ') {
+            return None;
+        }
+        let candidates = LogLevel::all_variants()
+            .iter()
+            .map(LogLevel::as_str)
+            .filter(|level| {
+                level
+                    .get(..partial.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(partial))
+            })
+            .map(|level| Pair {
+                display: level.to_string(),
+                replacement: format!("{} ", level),
+            })
+            .collect();
+        Some((pos - partial.len(), candidates))
+    }
+
     /// Get ESL-based completions from FreeSWITCH
     fn get_esl_completions(&self, line: &str, pos: usize) -> Vec<Completion> {
         self.debug_level
@@ -250,10 +382,19 @@ impl Completer for FsCliCompleter {
         pos: usize,
         ctx: &Context<'_>,
     ) -> rustyline::Result<(usize, Vec<Self::Candidate>)> {
-        // Skip ESL completion for client-side commands (starting with /)
+        if let Some(completions) = self
+            .complete_builtin_command(line, pos)
+            .or_else(|| self.complete_log_level(line, pos))
+        {
+            return Ok(completions);
+        }
+
+        // Skip ESL completion for client-side commands (starting with /),
+        // except those that borrow a FreeSWITCH command's completion
         if !line
             .trim_start()
             .starts_with('/')
+            || translate_client_alias(line, pos).is_some()
         {
             // Try ESL completion first for FreeSWITCH commands
             let esl_completions = self.get_esl_completions(line, pos);
@@ -264,20 +405,20 @@ impl Completer for FsCliCompleter {
 
                 for completion in esl_completions {
                     match completion {
+                        // Replaces the line up to the cursor, not just the word
                         Completion::Write(text) => {
+                            return Ok((0, vec![write_directive_pair(&line[..pos], &text)]));
+                        }
+                        // Matched by UUID or caller ID in ChannelProvider
+                        Completion::Uuid {
+                            replacement,
+                            display,
+                        } => {
                             candidates.push(Pair {
-                                display: text.clone(),
-                                replacement: text,
+                                display,
+                                replacement,
                             });
                         }
-                        Completion::Uuid { uuid, display } => {
-                            if uuid.starts_with(current_word) {
-                                candidates.push(Pair {
-                                    display,
-                                    replacement: format!("{} ", uuid),
-                                });
-                            }
-                        }
                         Completion::Candidate(s) => {
                             if s.starts_with(current_word) {
                                 candidates.push(Pair {
@@ -294,14 +435,21 @@ impl Completer for FsCliCompleter {
                 } else if candidates.len() > 1 {
                     // Compute LCP of replacement values; if it extends beyond what the
                     // user already typed, complete to it so multiple matches narrow down.
-                    // rustyline's longest_common_prefix uses replacement(), not display(),
-                    // giving a cleaner boundary on UUID completions.
-                    let lcp = longest_common_prefix(&candidates).map(|s| s.to_string());
-                    if let Some(lcp) = lcp {
-                        if lcp.len() > current_word.len() {
-                            for candidate in &mut candidates {
-                                candidate.replacement = lcp.clone();
-                            }
+                    // Replacements rather than displays give a cleaner boundary on
+                    // UUID completions.
+                    let replacements: Vec<&str> = candidates
+                        .iter()
+                        .map(|c| {
+                            c.replacement
+                                .as_str()
+                        })
+                        .collect();
+                    let lcp = find_common_prefix(&replacements);
+                    // Caller ID matches can share a UUID prefix unrelated
+                    // to what was typed; only extend the typed word.
+                    if lcp.len() > current_word.len() && lcp.starts_with(current_word) {
+                        for candidate in &mut candidates {
+                            candidate.replacement = lcp.clone();
                         }
                     }
                 }
@@ -379,3 +527,219 @@ impl Validator for FsCliCompleter {
             .validate_while_typing()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rustyline::history::DefaultHistory;
+
+    /// Completer whose ESL channel has no listener, so any completion that
+    /// reaches FreeSWITCH comes back empty
+    fn offline_completer() -> FsCliCompleter {
+        let (tx, _) = mpsc::unbounded_channel();
+        FsCliCompleter::new(tx, EslDebugLevel::None)
+    }
+
+    fn complete(line: &str) -> (usize, Vec<String>) {
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let (start, pairs) = offline_completer()
+            .complete(line, line.len(), &ctx)
+            .unwrap();
+        (
+            start,
+            pairs
+                .into_iter()
+                .map(|pair| pair.replacement)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn write_directive_replaces_whole_line() {
+        let (tx, mut rx) = mpsc::unbounded_channel::<CompletionRequest>();
+        let responder = std::thread::spawn(move || {
+            let request = rx
+                .blocking_recv()
+                .unwrap();
+            request
+                .response_tx
+                .send(vec![Completion::Write(
+                    "sofia profile internal".to_string(),
+                )])
+                .unwrap();
+        });
+        let completer = FsCliCompleter::new(tx, EslDebugLevel::None);
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let line = "sofia profile int";
+        let (start, pairs) = completer
+            .complete(line, line.len(), &ctx)
+            .unwrap();
+        responder
+            .join()
+            .unwrap();
+
+        assert_eq!(start, 0);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].replacement, "sofia profile internal ");
+    }
+
+    #[test]
+    fn esl_completions_time_out_without_reply() {
+        // The request stays queued, unanswered, with its reply sender alive
+        let (tx, mut rx) = mpsc::unbounded_channel::<CompletionRequest>();
+        let completer = FsCliCompleter::new(tx, EslDebugLevel::None);
+        let started = std::time::Instant::now();
+        let completions = completer.get_esl_completions("sofia ", 6);
+
+        assert!(completions.is_empty());
+        assert!(started.elapsed() >= Duration::from_millis(500));
+        assert!(rx
+            .try_recv()
+            .is_ok());
+    }
+
+    #[test]
+    fn write_directive_appends_to_unrelated_input() {
+        assert_eq!(
+            write_directive_pair("sofia ", "status").replacement,
+            "sofia status "
+        );
+    }
+
+    #[test]
+    fn builtin_commands_complete_after_slash() {
+        assert_eq!(
+            complete("/sub"),
+            (
+                0,
+                vec!["/subscribe ".to_string(), "/subscriptions ".to_string()]
+            )
+        );
+        assert_eq!(
+            complete("/")
+                .1
+                .len(),
+            BUILTIN_COMMANDS.len()
+        );
+        assert!(complete("/nosuch")
+            .1
+            .is_empty());
+    }
+
+    #[test]
+    fn builtin_commands_sorted() {
+        assert!(BUILTIN_COMMANDS
+            .windows(2)
+            .all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn builtin_commands_cover_help() {
+        let processor = crate::commands::CommandProcessor::new(
+            &crate::config::ProfileConfig::default()
+                .to_app_config()
+                .unwrap(),
+        );
+        let help = processor.help_text();
+        let documented = help
+            .lines()
+            .filter_map(|line| {
+                line.trim_start()
+                    .strip_prefix('/')
+            })
+            .flat_map(|line| {
+                line.split(" - ")
+                    .next()
+                    .unwrap_or("")
+                    .split(", ")
+            })
+            .filter_map(|entry| {
+                entry
+                    .split_whitespace()
+                    .next()
+            });
+        for name in documented {
+            let name = format!("/{}", name.trim_start_matches('/'));
+            assert!(
+                BUILTIN_COMMANDS
+                    .iter()
+                    .any(|(builtin, _)| *builtin == name),
+                "{} is missing from BUILTIN_COMMANDS",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn log_levels_complete_case_insensitively() {
+        assert_eq!(complete("/log WA"), (5, vec!["warn ".to_string()]));
+        let (start, all) = complete("/log ");
+        assert_eq!(start, 5);
+        assert_eq!(all.len(), LogLevel::all_variants().len());
+        assert!(complete("/log debug x")
+            .1
+            .is_empty());
+    }
+
+    #[test]
+    fn common_prefix_empty_slice() {
+        assert_eq!(find_common_prefix(&[]), "");
+    }
+
+    #[test]
+    fn common_prefix_single() {
+        assert_eq!(find_common_prefix(&["sofia"]), "sofia");
+    }
+
+    #[test]
+    fn common_prefix_identical() {
+        assert_eq!(find_common_prefix(&["status", "status"]), "status");
+    }
+
+    #[test]
+    fn common_prefix_none_shared() {
+        assert_eq!(find_common_prefix(&["status", "version"]), "");
+    }
+
+    #[test]
+    fn common_prefix_multi_char() {
+        assert_eq!(
+            find_common_prefix(&["sofia_contact", "sofia_dig"]),
+            "sofia_"
+        );
+    }
+
+    #[test]
+    fn common_prefix_unicode_chars() {
+        // 'é' and 'è' share their first UTF-8 byte; the prefix must not
+        // split the character
+        assert_eq!(find_common_prefix(&["conférence", "confèrence"]), "conf");
+        assert_eq!(find_common_prefix(&["通话一", "通话二"]), "通话");
+    }
+
+    #[test]
+    fn common_prefix_many() {
+        let words: Vec<String> = (0..12)
+            .map(|i| format!("uui{}", i))
+            .collect();
+        let words: Vec<&str> = words
+            .iter()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(find_common_prefix(&words), "uui");
+    }
+
+    proptest! {
+        #[test]
+        fn common_prefix_prefixes_every_input(words in prop::collection::vec(".*", 0..8)) {
+            let words: Vec<&str> = words.iter().map(String::as_str).collect();
+            let prefix = find_common_prefix(&words);
+            for word in &words {
+                prop_assert!(word.starts_with(&prefix));
+            }
+        }
+    }
+}
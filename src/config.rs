@@ -1,77 +1,300 @@
 //! Configuration management for fs_cli-rs
 
-use crate::commands::{ColorMode, LogLevel};
+use crate::audit::AuditLog;
+use crate::channel_info::DEFAULT_CHANNEL_CACHE_TTL_MS;
+use crate::commands::{ColorMode, LogLevel, OutputFormat};
 use crate::esl_debug::EslDebugLevel;
 use anyhow::{Context, Result};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tracing::warn;
 
+/// Default for `execute_max_concurrent`
+pub const DEFAULT_EXECUTE_MAX_CONCURRENT: usize = 10;
+
+/// Default for `execute_max_commands`
+pub const DEFAULT_EXECUTE_MAX_COMMANDS: usize = 1000;
+
+/// Default for `max_auto_complete_uuid`
+pub const DEFAULT_MAX_AUTO_COMPLETE_UUID: u32 = 50;
+
+/// Default for `log_burst_size`
+pub const DEFAULT_LOG_BURST_SIZE: u32 = 100;
+
+/// Default for `log_source_max_len`
+pub const DEFAULT_LOG_SOURCE_MAX_LEN: usize = 30;
+
+/// Commands that ask for confirmation before running interactively
+pub const DEFAULT_DANGEROUS_COMMANDS: &[&str] =
+    &["hupall", "fsctl shutdown", "fsctl crash", "shutdown"];
+
+/// Which earlier history entries make a new one a duplicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupeMode {
+    /// Skip a command identical to the previous one
+    #[default]
+    Adjacent,
+    /// Skip a command already anywhere in history
+    All,
+}
+
 /// Top-level configuration structure matching the YAML format
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FsCliConfig {
     pub fs_cli: HashMap<String, ProfileConfig>,
+
+    /// More configuration files whose profiles are merged in, relative to
+    /// this file's directory. Profiles defined here win over included ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<PathBuf>>,
+
+    /// Profiles that came from `include` files, left out by `save`
+    #[serde(skip)]
+    included: HashSet<String>,
+}
+
+/// The configuration file fs_cli started from, shared so commands can edit
+/// and save it
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    /// File read at startup, or where the default configuration was created
+    pub path: PathBuf,
+    pub config: Arc<Mutex<FsCliConfig>>,
 }
 
-/// Configuration for a single profile
+/// Configuration for a single profile. Unknown keys are an error so typos
+/// like `passord` don't go unnoticed.
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct ProfileConfig {
+    /// Profile whose settings are used for every field left unset here
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+
     /// FreeSWITCH hostname or IP address
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub host: Option<String>,
 
     /// FreeSWITCH ESL port
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub port: Option<u16>,
 
     /// ESL password
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
 
     /// Username for authentication (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
 
-    /// ESL debug level (0-7, higher = more verbose)
+    /// ESL debug level (0-10, higher = more verbose)
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub debug: Option<EslDebugLevel>,
 
     /// Color mode for output
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<ColorMode>,
 
     /// History file path
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub history_file: Option<PathBuf>,
 
     /// Connection timeout in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<u64>,
 
     /// Retry connection on failure
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub retry: Option<bool>,
 
     /// Reconnect on connection loss
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reconnect: Option<bool>,
 
     /// Subscribe to events on startup
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub events: Option<bool>,
 
     /// Log level for FreeSWITCH logs
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub log_level: Option<LogLevel>,
 
     /// Disable automatic log subscription on startup
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub quiet: Option<bool>,
 
     /// Function key macros
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub macros: Option<HashMap<String, String>>,
 
     /// Maximum number of channels to show in auto-complete
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_auto_complete_uuid: Option<u32>,
+
+    /// How long channel lists for UUID completion are reused, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_ms: Option<u64>,
+
+    /// Commands (or command prefixes) that require confirmation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dangerous_commands: Option<Vec<String>>,
+
+    /// First delay between reconnection attempts in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reconnect_base_delay_ms: Option<u64>,
+
+    /// Upper bound for the reconnection delay in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reconnect_max_delay_ms: Option<u64>,
+
+    /// Double the delay after each failed attempt (with jitter)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reconnect_backoff: Option<bool>,
+
+    /// Delay between reconnection attempts in milliseconds when
+    /// `reconnect_backoff` is off
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reconnect_delay_ms: Option<u64>,
+
+    /// Give up after this many failed reconnection attempts (unlimited when
+    /// unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reconnect_max_attempts: Option<u32>,
+
+    /// Timeout for individual API commands in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_timeout_ms: Option<u64>,
+
+    /// End an interactive session after this many milliseconds without
+    /// user input
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_ms: Option<u64>,
+
+    /// Prompt with `{host}`, `{port}`, `{user}`, `{profile}`, `{log_level}`
+    /// and `{color_mode}` substituted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_template: Option<String>,
+
+    /// Connect through this Unix socket instead of host/port
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unix_socket: Option<PathBuf>,
+
+    /// Keep a separate history file per profile when `history_file` is unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_profile_history: Option<bool>,
+
+    /// Skip duplicate history entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_dedupe: Option<bool>,
+
+    /// What counts as a duplicate: adjacent or all
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_dedupe_mode: Option<DedupeMode>,
+
+    /// Maximum number of history entries kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_max_size: Option<usize>,
+
+    /// Prefix log lines with the time they were received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_timestamps: Option<bool>,
+
+    /// chrono format for log timestamps (default `%H:%M:%S%.3f`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_timestamp_format: Option<String>,
+
+    /// IANA timezone for log timestamps, e.g. `UTC`; local time when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_timezone: Option<String>,
+
+    /// Collapse log lines repeating a recently shown one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_dedupe: Option<bool>,
+
+    /// Number of recent log lines checked by `log_dedupe`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_dedupe_window: Option<u32>,
+
+    /// Most log lines shown per second; extra lines are held back
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_rate_limit: Option<u32>,
+
+    /// Lines allowed above `log_rate_limit` before throttling starts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_burst_size: Option<u32>,
+
+    /// Prefix log lines with the FreeSWITCH source file and function
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_log_source: Option<bool>,
+
+    /// Longest source shown by `show_log_source`; longer ones are cut
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_source_max_len: Option<usize>,
+
+    /// Log level name to color name, e.g. `warning: bright_magenta`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_colors: Option<HashMap<String, String>>,
+
+    /// Upper bound on `-x` commands running at once with `--parallel`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execute_max_concurrent: Option<usize>,
+
+    /// Most commands `-x` and `--execute-file` may run in one invocation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execute_max_commands: Option<usize>,
+
+    /// Show the number of active channels in the prompt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_channel_count: Option<bool>,
+
+    /// Wrap output lines wider than the terminal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub word_wrap: Option<bool>,
+
+    /// Show the connection in the terminal title, when the terminal
+    /// supports it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set_terminal_title: Option<bool>,
+
+    /// How often the prompt channel count is refreshed, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_count_interval_ms: Option<u64>,
+
+    /// Connect over TLS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<bool>,
+
+    /// Skip server certificate verification
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_insecure: Option<bool>,
+
+    /// PEM CA certificate to trust instead of the system store
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_ca_cert: Option<String>,
+
+    /// PEM client certificate, with `tls_client_key`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_client_cert: Option<String>,
+
+    /// PEM private key for `tls_client_cert`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_client_key: Option<String>,
 }
 
 impl Default for ProfileConfig {
     fn default() -> Self {
         Self {
+            parent: None,
             host: Some("localhost".to_string()),
             port: Some(8021),
             password: Some("ClueCon".to_string()),
             user: None,
             debug: Some(EslDebugLevel::None),
-            color: Some(ColorMode::Line),
+            color: Some(ColorMode::Auto),
             history_file: None,
             timeout: Some(2000),
             retry: Some(false),
@@ -80,7 +303,43 @@ impl Default for ProfileConfig {
             log_level: Some(LogLevel::Debug),
             quiet: Some(false),
             macros: Some(Self::default_macros()),
-            max_auto_complete_uuid: Some(32),
+            max_auto_complete_uuid: Some(DEFAULT_MAX_AUTO_COMPLETE_UUID),
+            cache_ttl_ms: Some(DEFAULT_CHANNEL_CACHE_TTL_MS),
+            dangerous_commands: Some(Self::default_dangerous_commands()),
+            reconnect_base_delay_ms: Some(1000),
+            reconnect_max_delay_ms: Some(30000),
+            reconnect_backoff: Some(true),
+            reconnect_delay_ms: Some(1000),
+            reconnect_max_attempts: None,
+            command_timeout_ms: None,
+            idle_timeout_ms: None,
+            prompt_template: None,
+            unix_socket: None,
+            per_profile_history: Some(true),
+            history_dedupe: Some(true),
+            history_dedupe_mode: Some(DedupeMode::Adjacent),
+            history_max_size: None,
+            log_timestamps: Some(false),
+            log_timestamp_format: None,
+            log_timezone: None,
+            log_dedupe: Some(false),
+            log_dedupe_window: Some(10),
+            log_rate_limit: None,
+            log_burst_size: Some(DEFAULT_LOG_BURST_SIZE),
+            show_log_source: Some(false),
+            log_source_max_len: Some(DEFAULT_LOG_SOURCE_MAX_LEN),
+            log_colors: None,
+            execute_max_concurrent: Some(DEFAULT_EXECUTE_MAX_CONCURRENT),
+            execute_max_commands: Some(DEFAULT_EXECUTE_MAX_COMMANDS),
+            show_channel_count: Some(false),
+            channel_count_interval_ms: Some(5000),
+            word_wrap: Some(true),
+            set_terminal_title: Some(true),
+            tls: Some(false),
+            tls_insecure: Some(false),
+            tls_ca_cert: None,
+            tls_client_cert: None,
+            tls_client_key: None,
         }
     }
 }
@@ -92,11 +351,110 @@ impl ProfileConfig {
             .map(|(k, v)| (k.to_string(), v.to_string()))
             .collect()
     }
+
+    fn default_dangerous_commands() -> Vec<String> {
+        DEFAULT_DANGEROUS_COMMANDS
+            .iter()
+            .map(|c| c.to_string())
+            .collect()
+    }
 }
 
+/// How many `parent` links are followed before giving up on a cycle
+const MAX_PROFILE_DEPTH: usize = 5;
+
 impl ProfileConfig {
+    /// Fill every field unset in this profile from `parent`. Macros are
+    /// merged, with this profile's bindings winning.
+    pub fn merge_from_parent(&self, parent: &ProfileConfig) -> ProfileConfig {
+        let macros = match (&parent.macros, &self.macros) {
+            (None, None) => None,
+            (inherited, own) => {
+                let mut merged = inherited
+                    .clone()
+                    .unwrap_or_default();
+                merged.extend(
+                    own.clone()
+                        .unwrap_or_default(),
+                );
+                Some(merged)
+            }
+        };
+        macro_rules! inherit {
+            ($child:expr, $parent:expr; $($field:ident),* $(,)?) => {
+                ProfileConfig {
+                    parent: $child.parent.clone(),
+                    macros,
+                    $($field: $child.$field.clone().or_else(|| $parent.$field.clone()),)*
+                }
+            };
+        }
+        inherit!(
+            self, parent;
+            host, port, password, user, debug, color, history_file, timeout, retry, reconnect,
+            events, log_level, quiet, max_auto_complete_uuid, cache_ttl_ms, dangerous_commands,
+            reconnect_base_delay_ms, reconnect_max_delay_ms, reconnect_backoff, reconnect_delay_ms,
+            reconnect_max_attempts, command_timeout_ms, idle_timeout_ms, prompt_template,
+            unix_socket, per_profile_history, history_dedupe, history_dedupe_mode,
+            history_max_size, log_timestamps, log_timestamp_format, log_timezone, log_dedupe,
+            log_dedupe_window, log_rate_limit, log_burst_size, show_log_source,
+            log_source_max_len, log_colors, execute_max_concurrent, execute_max_commands,
+            show_channel_count, word_wrap, set_terminal_title, channel_count_interval_ms, tls,
+            tls_insecure, tls_ca_cert, tls_client_cert, tls_client_key
+        )
+    }
+
+    /// Check values the YAML types alone don't constrain and return every
+    /// problem found. `debug`, `color` and `log_level` are typed, so invalid
+    /// values for those are already rejected when the file is parsed.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.port == Some(0) {
+            errors.push("port must be between 1 and 65535".to_string());
+        }
+        if self.timeout == Some(0) {
+            errors.push("timeout must be greater than 0".to_string());
+        }
+        if let Some(max) = self
+            .max_auto_complete_uuid
+            .filter(|max| !(1..=10_000).contains(max))
+        {
+            errors.push(format!(
+                "max_auto_complete_uuid must be between 1 and 10000, got {}",
+                max
+            ));
+        }
+        if self.log_rate_limit == Some(0) {
+            errors.push("log_rate_limit must be greater than 0".to_string());
+        }
+        if let Some(history_file) = &self.history_file {
+            if let Some(parent) = history_file
+                .parent()
+                .filter(|p| {
+                    !p.as_os_str()
+                        .is_empty()
+                })
+            {
+                if !parent.is_dir() {
+                    errors.push(format!(
+                        "history_file directory {} does not exist",
+                        parent.display()
+                    ));
+                }
+            }
+        }
+        errors
+    }
+
     /// Convert to typed values for application use
     pub fn to_app_config(&self) -> Result<AppConfig> {
+        crate::log_display::LogTimestamp::new(
+            self.log_timestamp_format
+                .as_deref(),
+            self.log_timezone
+                .as_deref(),
+        )?;
+
         Ok(AppConfig {
             host: self
                 .host
@@ -117,7 +475,7 @@ impl ProfileConfig {
                 .unwrap_or_default(),
             color: self
                 .color
-                .unwrap_or(ColorMode::Line),
+                .unwrap_or_default(),
             history_file: self
                 .history_file
                 .clone(),
@@ -135,7 +493,7 @@ impl ProfileConfig {
                 .unwrap_or(false),
             log_level: self
                 .log_level
-                .unwrap_or(LogLevel::Debug),
+                .unwrap_or_default(),
             quiet: self
                 .quiet
                 .unwrap_or(false),
@@ -146,7 +504,127 @@ impl ProfileConfig {
             execute: Vec::new(), // Always empty from config, filled by CLI args
             max_auto_complete_uuid: self
                 .max_auto_complete_uuid
-                .unwrap_or(32),
+                .unwrap_or(DEFAULT_MAX_AUTO_COMPLETE_UUID),
+            cache_ttl_ms: self
+                .cache_ttl_ms
+                .unwrap_or(DEFAULT_CHANNEL_CACHE_TTL_MS),
+            json_pretty: true,
+            output_format: OutputFormat::Raw,
+            output_width: None,
+            mock_port: None,
+            mock_fixtures: None,
+            dry_run: false,
+            execute_fail_fast: false,
+            exit_on_error_pattern: None,
+            execute_parallel: false,
+            inter_command_delay_ms: None,
+            allow_recursive_source: false,
+            debug_structured: false,
+            debug_output_file: None,
+            execute_max_concurrent: self
+                .execute_max_concurrent
+                .unwrap_or(DEFAULT_EXECUTE_MAX_CONCURRENT),
+            execute_max_commands: self
+                .execute_max_commands
+                .unwrap_or(DEFAULT_EXECUTE_MAX_COMMANDS),
+            show_channel_count: self
+                .show_channel_count
+                .unwrap_or(false),
+            channel_count_interval_ms: self
+                .channel_count_interval_ms
+                .unwrap_or(5000),
+            word_wrap: self
+                .word_wrap
+                .unwrap_or(true),
+            set_terminal_title: self
+                .set_terminal_title
+                .unwrap_or(true),
+            dangerous_commands: self
+                .dangerous_commands
+                .clone()
+                .unwrap_or_else(Self::default_dangerous_commands),
+            confirm_dangerous: true,
+            reconnect_base_delay_ms: self
+                .reconnect_base_delay_ms
+                .unwrap_or(1000),
+            reconnect_max_delay_ms: self
+                .reconnect_max_delay_ms
+                .unwrap_or(30000),
+            reconnect_backoff: self
+                .reconnect_backoff
+                .unwrap_or(true),
+            reconnect_delay_ms: self
+                .reconnect_delay_ms
+                .unwrap_or(1000),
+            reconnect_max_attempts: self.reconnect_max_attempts,
+            wait_for_connection: false,
+            command_timeout_ms: self.command_timeout_ms,
+            idle_timeout_ms: self.idle_timeout_ms,
+            prompt_template: self
+                .prompt_template
+                .clone(),
+            profile_name: "default".to_string(),
+            unix_socket: self
+                .unix_socket
+                .clone(),
+            per_profile_history: self
+                .per_profile_history
+                .unwrap_or(true),
+            no_history: false,
+            history_dedupe: self
+                .history_dedupe
+                .unwrap_or(true),
+            history_dedupe_mode: self
+                .history_dedupe_mode
+                .unwrap_or_default(),
+            history_max_size: self.history_max_size,
+            log_timestamps: self
+                .log_timestamps
+                .unwrap_or(false),
+            log_timestamp_format: self
+                .log_timestamp_format
+                .clone(),
+            log_timezone: self
+                .log_timezone
+                .clone(),
+            log_dedupe: self
+                .log_dedupe
+                .unwrap_or(false),
+            log_dedupe_window: self
+                .log_dedupe_window
+                .unwrap_or(10),
+            log_rate_limit: self.log_rate_limit,
+            log_burst_size: self
+                .log_burst_size
+                .unwrap_or(DEFAULT_LOG_BURST_SIZE),
+            show_log_source: self
+                .show_log_source
+                .unwrap_or(false),
+            log_source_max_len: self
+                .log_source_max_len
+                .unwrap_or(DEFAULT_LOG_SOURCE_MAX_LEN),
+            log_colors: self
+                .log_colors
+                .clone()
+                .unwrap_or_default(),
+            tls: self
+                .tls
+                .unwrap_or(false),
+            tls_insecure: self
+                .tls_insecure
+                .unwrap_or(false),
+            tls_ca_cert: self
+                .tls_ca_cert
+                .clone(),
+            tls_client_cert: self
+                .tls_client_cert
+                .clone(),
+            tls_client_key: self
+                .tls_client_key
+                .clone(),
+            loaded_config: None,
+            command_audit_file: None,
+            audit_log: None,
         })
     }
 }
@@ -170,11 +648,405 @@ pub struct AppConfig {
     pub macros: HashMap<String, String>,
     pub execute: Vec<String>,
     pub max_auto_complete_uuid: u32,
+    pub cache_ttl_ms: u64,
+    /// Re-indent JSON API responses; off in `-x` mode so scripts get raw output
+    pub json_pretty: bool,
+    /// Initial `/output-format` (`--output-format`)
+    pub output_format: OutputFormat,
+    /// Fixed width for table output; `None` queries the terminal
+    pub output_width: Option<u16>,
+    /// Serve a mock ESL server on this port and connect to it (`--mock`)
+    pub mock_port: Option<u16>,
+    /// Canned responses for the mock server
+    pub mock_fixtures: Option<PathBuf>,
+    /// Print FreeSWITCH commands instead of sending them (`--dry-run`)
+    pub dry_run: bool,
+    /// Stop `-x` at the first failing command (`--fail-fast`)
+    pub execute_fail_fast: bool,
+    /// Stop `-x` when a response body matches (`--exit-on-error-pattern`)
+    pub exit_on_error_pattern: Option<Regex>,
+    /// Run `-x` API commands concurrently (`--parallel`); ignored with
+    /// `execute_fail_fast`, which needs each result before the next command
+    pub execute_parallel: bool,
+    pub execute_max_concurrent: usize,
+    pub execute_max_commands: usize,
+    /// Pause between `/source` commands (`--source-delay`)
+    pub inter_command_delay_ms: Option<u64>,
+    /// Let `/source` files source other files (`--allow-recursive-source`)
+    pub allow_recursive_source: bool,
+    /// Print ESL debug messages as JSON lines (`--debug-json`)
+    pub debug_structured: bool,
+    /// Copy ESL debug messages to this file (`--debug-output-file`)
+    pub debug_output_file: Option<PathBuf>,
+    /// Show `[N ch]` in the prompt; it only changes when the prompt is
+    /// redrawn, i.e. after the next command
+    pub show_channel_count: bool,
+    pub channel_count_interval_ms: u64,
+    /// Wrap long output lines on a terminal; cleared by `--no-wrap`
+    pub word_wrap: bool,
+    /// Show the connection in the terminal title; cleared by `--no-title`
+    pub set_terminal_title: bool,
+    pub dangerous_commands: Vec<String>,
+    /// Ask before running `dangerous_commands`; cleared by `--no-confirm`
+    pub confirm_dangerous: bool,
+    pub reconnect_base_delay_ms: u64,
+    pub reconnect_max_delay_ms: u64,
+    pub reconnect_backoff: bool,
+    pub reconnect_delay_ms: u64,
+    pub reconnect_max_attempts: Option<u32>,
+    /// Retry the initial connection until FreeSWITCH is up, ignoring
+    /// `retry` and `reconnect_max_attempts` (`--wait-for-connection`)
+    pub wait_for_connection: bool,
+    pub command_timeout_ms: Option<u64>,
+    pub idle_timeout_ms: Option<u64>,
+    pub prompt_template: Option<String>,
+    /// Name of the profile this configuration was loaded from
+    pub profile_name: String,
+    /// Takes precedence over `host`/`port` when set
+    pub unix_socket: Option<PathBuf>,
+    /// Use `~/.fs_cli_history.<profile>`; ignored when `history_file` is set
+    pub per_profile_history: bool,
+    /// Don't read or write the history file (`--no-history`)
+    pub no_history: bool,
+    pub history_dedupe: bool,
+    pub history_dedupe_mode: DedupeMode,
+    pub history_max_size: Option<usize>,
+    pub log_timestamps: bool,
+    pub log_timestamp_format: Option<String>,
+    pub log_timezone: Option<String>,
+    pub log_dedupe: bool,
+    pub log_dedupe_window: u32,
+    /// Log lines per second before throttling; `None` shows every line
+    pub log_rate_limit: Option<u32>,
+    pub log_burst_size: u32,
+    pub show_log_source: bool,
+    pub log_source_max_len: usize,
+    /// Log level color overrides, applied by `log_display::set_log_colors`
+    pub log_colors: HashMap<String, String>,
+    pub tls: bool,
+    pub tls_insecure: bool,
+    pub tls_ca_cert: Option<String>,
+    pub tls_client_cert: Option<String>,
+    pub tls_client_key: Option<String>,
+    /// Configuration file for `/save-config`; `None` when there is no file
+    pub loaded_config: Option<LoadedConfig>,
+    /// Append every executed command here (`--audit-log`)
+    pub command_audit_file: Option<PathBuf>,
+    /// `command_audit_file`, opened by `Args::parse_and_merge`
+    pub audit_log: Option<AuditLog>,
+}
+
+/// Read `<prefix>_<name>` and parse it, naming the variable on failure
+fn env_value<T, E>(
+    prefix: &str,
+    name: &str,
+    parse: impl Fn(&str) -> Result<T, E>,
+) -> Result<Option<T>>
+where
+    E: std::fmt::Display,
+{
+    let var = format!("{}_{}", prefix, name);
+    match std::env::var(&var) {
+        Ok(value) => parse(value.trim())
+            .map(Some)
+            .map_err(|e| anyhow::anyhow!("Invalid {}={:?}: {}", var, value, e)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn parse_env_bool(value: &str) -> Result<bool, String> {
+    match value
+        .to_lowercase()
+        .as_str()
+    {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err("expected true or false".to_string()),
+    }
+}
+
+impl AppConfig {
+    /// Append the commands in `path` to `execute`, one per line, skipping
+    /// blank lines and `#` comments. Fails when the file cannot be read or
+    /// the total exceeds `execute_max_commands`.
+    pub fn add_execute_file(&mut self, path: &Path) -> Result<()> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read execute file {}", path.display()))?;
+        self.execute
+            .extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            );
+        self.check_execute_limit(path.display())
+    }
+
+    /// Fail when `execute` holds more than `execute_max_commands` commands,
+    /// naming `source` (`-x` or the execute file) as what added too many
+    pub fn check_execute_limit(&self, source: impl std::fmt::Display) -> Result<()> {
+        if self
+            .execute
+            .len()
+            > self.execute_max_commands
+        {
+            anyhow::bail!(
+                "{} brings the command count to {}, over execute_max_commands ({})",
+                source,
+                self.execute
+                    .len(),
+                self.execute_max_commands
+            );
+        }
+        Ok(())
+    }
+
+    /// Override settings from `<prefix>_HOST`, `_PORT`, `_PASSWORD`, `_USER`,
+    /// `_LOG_LEVEL`, `_COLOR`, `_DEBUG`, `_QUIET`, `_RECONNECT` and
+    /// `_TIMEOUT` environment variables
+    pub fn apply_env_overrides(&mut self, prefix: &str) -> Result<()> {
+        if let Some(host) = env_value(prefix, "HOST", |v| Ok::<_, String>(v.to_string()))? {
+            self.host = host;
+        }
+        if let Some(port) = env_value(prefix, "PORT", str::parse::<u16>)? {
+            self.port = port;
+        }
+        if let Some(password) = env_value(prefix, "PASSWORD", |v| Ok::<_, String>(v.to_string()))? {
+            self.password = password;
+        }
+        if let Some(user) = env_value(prefix, "USER", |v| Ok::<_, String>(v.to_string()))? {
+            self.user = Some(user);
+        }
+        if let Some(log_level) = env_value(prefix, "LOG_LEVEL", str::parse::<LogLevel>)? {
+            self.log_level = log_level;
+        }
+        if let Some(color) = env_value(prefix, "COLOR", str::parse::<ColorMode>)? {
+            self.color = color;
+        }
+        if let Some(debug) = env_value(prefix, "DEBUG", EslDebugLevel::parse_number_or_name)? {
+            self.debug = debug;
+        }
+        if let Some(quiet) = env_value(prefix, "QUIET", parse_env_bool)? {
+            self.quiet = quiet;
+        }
+        if let Some(reconnect) = env_value(prefix, "RECONNECT", parse_env_bool)? {
+            self.reconnect = reconnect;
+        }
+        if let Some(timeout) = env_value(prefix, "TIMEOUT", str::parse::<u64>)? {
+            self.timeout = timeout;
+        }
+        Ok(())
+    }
+
+    /// Convert back to a profile, e.g. to show the effective configuration
+    pub fn to_profile_config(&self) -> ProfileConfig {
+        ProfileConfig {
+            parent: None,
+            host: Some(
+                self.host
+                    .clone(),
+            ),
+            port: Some(self.port),
+            password: Some(
+                self.password
+                    .clone(),
+            ),
+            user: self
+                .user
+                .clone(),
+            debug: Some(self.debug),
+            color: Some(self.color),
+            history_file: self
+                .history_file
+                .clone(),
+            timeout: Some(self.timeout),
+            retry: Some(self.retry),
+            reconnect: Some(self.reconnect),
+            events: Some(self.events),
+            log_level: Some(self.log_level),
+            quiet: Some(self.quiet),
+            macros: Some(
+                self.macros
+                    .clone(),
+            ),
+            max_auto_complete_uuid: Some(self.max_auto_complete_uuid),
+            cache_ttl_ms: Some(self.cache_ttl_ms),
+            dangerous_commands: Some(
+                self.dangerous_commands
+                    .clone(),
+            ),
+            reconnect_base_delay_ms: Some(self.reconnect_base_delay_ms),
+            reconnect_max_delay_ms: Some(self.reconnect_max_delay_ms),
+            reconnect_backoff: Some(self.reconnect_backoff),
+            reconnect_delay_ms: Some(self.reconnect_delay_ms),
+            reconnect_max_attempts: self.reconnect_max_attempts,
+            command_timeout_ms: self.command_timeout_ms,
+            idle_timeout_ms: self.idle_timeout_ms,
+            prompt_template: self
+                .prompt_template
+                .clone(),
+            unix_socket: self
+                .unix_socket
+                .clone(),
+            per_profile_history: Some(self.per_profile_history),
+            history_dedupe: Some(self.history_dedupe),
+            history_dedupe_mode: Some(self.history_dedupe_mode),
+            history_max_size: self.history_max_size,
+            log_timestamps: Some(self.log_timestamps),
+            log_timestamp_format: self
+                .log_timestamp_format
+                .clone(),
+            log_timezone: self
+                .log_timezone
+                .clone(),
+            log_dedupe: Some(self.log_dedupe),
+            log_dedupe_window: Some(self.log_dedupe_window),
+            log_rate_limit: self.log_rate_limit,
+            log_burst_size: Some(self.log_burst_size),
+            show_log_source: Some(self.show_log_source),
+            log_source_max_len: Some(self.log_source_max_len),
+            log_colors: (!self
+                .log_colors
+                .is_empty())
+            .then(|| {
+                self.log_colors
+                    .clone()
+            }),
+            execute_max_concurrent: Some(self.execute_max_concurrent),
+            execute_max_commands: Some(self.execute_max_commands),
+            show_channel_count: Some(self.show_channel_count),
+            channel_count_interval_ms: Some(self.channel_count_interval_ms),
+            word_wrap: Some(self.word_wrap),
+            set_terminal_title: Some(self.set_terminal_title),
+            tls: Some(self.tls),
+            tls_insecure: Some(self.tls_insecure),
+            tls_ca_cert: self
+                .tls_ca_cert
+                .clone(),
+            tls_client_cert: self
+                .tls_client_cert
+                .clone(),
+            tls_client_key: self
+                .tls_client_key
+                .clone(),
+        }
+    }
+}
+
+/// Profile keys of older versions, or spelled like their command-line flag,
+/// and the key that replaced them
+const LEGACY_KEYS: &[(&str, &str)] = &[
+    ("connect_timeout", "timeout"),
+    ("fnkeys", "macros"),
+    ("tls_ca", "tls_ca_cert"),
+    ("tls_cert", "tls_client_cert"),
+    ("tls_key", "tls_client_key"),
+];
+
+/// Rename `LEGACY_KEYS` in configuration YAML. Only indented keys (inside a
+/// profile) are renamed; everything else is left as is.
+pub fn migrate_legacy_config(yaml: &str) -> String {
+    let key = Regex::new(r"^(\s+)([A-Za-z_]+):(\s|$)").expect("valid legacy key regex");
+    let mut migrated: Vec<String> = yaml
+        .lines()
+        .map(|line| {
+            let Some(caps) = key.captures(line) else {
+                return line.to_string();
+            };
+            match LEGACY_KEYS
+                .iter()
+                .find(|(old, _)| *old == &caps[2])
+            {
+                Some((_, new)) => format!(
+                    "{}{}{}",
+                    &caps[1],
+                    new,
+                    &line[caps[1].len() + caps[2].len()..]
+                ),
+                None => line.to_string(),
+            }
+        })
+        .collect();
+    if yaml.ends_with('\n') {
+        migrated.push(String::new());
+    }
+    migrated.join("\n")
+}
+
+/// `LEGACY_KEYS` as `old -> new` pairs, for parse errors
+fn legacy_keys_note() -> String {
+    LEGACY_KEYS
+        .iter()
+        .map(|(old, new)| format!("{} -> {}", old, new))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Replace the values of credential keys (`password`, `tls_key`,
+/// `tls_client_key`) in serialized YAML with `"****"`.
+///
+/// Continuation lines of multi-line values (block scalars or wrapped
+/// strings, i.e. anything indented deeper than the key) are dropped.
+pub fn redact_sensitive_fields(yaml: &str) -> String {
+    let key = Regex::new(r"^(\s*)(password|tls_key|tls_client_key):(\s|$)")
+        .expect("valid redaction regex");
+    let mut redacted = String::with_capacity(yaml.len());
+    let mut skip_deeper_than: Option<usize> = None;
+
+    for line in yaml.lines() {
+        let indent = line.len()
+            - line
+                .trim_start()
+                .len();
+        if let Some(key_indent) = skip_deeper_than {
+            if line
+                .trim()
+                .is_empty()
+                || indent > key_indent
+            {
+                continue;
+            }
+            skip_deeper_than = None;
+        }
+
+        if let Some(caps) = key.captures(line) {
+            let key_end = caps[1].len() + caps[2].len() + 1;
+            let value = line[key_end..].trim();
+            if value != "null" && value != "~" {
+                redacted.push_str(&line[..key_end]);
+                redacted.push_str(" \"****\"\n");
+                skip_deeper_than = Some(indent);
+                continue;
+            }
+        }
+
+        redacted.push_str(line);
+        redacted.push('\n');
+    }
+
+    redacted
 }
 
 impl FsCliConfig {
-    /// Load configuration from file or create default
-    pub fn load(config_path: Option<PathBuf>) -> Result<Self> {
+    /// Configuration with these profiles and no includes
+    pub fn new(fs_cli: HashMap<String, ProfileConfig>) -> Self {
+        Self {
+            fs_cli,
+            include: None,
+            included: HashSet::new(),
+        }
+    }
+
+    /// Load configuration from file or create default.
+    ///
+    /// Every profile is validated: problems in `active_profile` are an error,
+    /// those in other profiles only a warning. Also returns the file the
+    /// configuration belongs to, if any.
+    pub fn load(
+        config_path: Option<PathBuf>,
+        active_profile: Option<&str>,
+    ) -> Result<(Self, Option<PathBuf>)> {
         let config_paths = if let Some(path) = config_path {
             vec![path]
         } else {
@@ -184,11 +1056,11 @@ impl FsCliConfig {
         // Try to load from existing config files
         for path in &config_paths {
             if path.exists() {
-                let content = std::fs::read_to_string(path)
-                    .with_context(|| format!("Failed to read config file {}", path.display()))?;
-                let config: Self = serde_yaml::from_str(&content)
-                    .with_context(|| format!("Failed to parse config file {}", path.display()))?;
-                return Ok(config);
+                let config = Self::read_file(path, &mut HashSet::new())?;
+                config
+                    .check_profiles(active_profile)
+                    .with_context(|| format!("Invalid config file {}", path.display()))?;
+                return Ok((config, Some(path.clone())));
             }
         }
 
@@ -196,8 +1068,8 @@ impl FsCliConfig {
         let default_config = Self::default();
 
         // Create the config file if we have a writable directory
-        if let Some(config_dir) = dirs::config_dir() {
-            let config_path = config_dir.join("fs_cli.yaml");
+        let created_path = dirs::config_dir().map(|dir| dir.join("fs_cli.yaml"));
+        if let Some(config_path) = &created_path {
             if let Some(parent) = config_path.parent() {
                 if let Err(e) = std::fs::create_dir_all(parent) {
                     warn!(
@@ -208,7 +1080,7 @@ impl FsCliConfig {
                 }
                 match serde_yaml::to_string(&default_config) {
                     Ok(yaml_content) => {
-                        if let Err(e) = std::fs::write(&config_path, yaml_content) {
+                        if let Err(e) = std::fs::write(config_path, yaml_content) {
                             warn!(
                                 "Could not write default config to {}: {}",
                                 config_path.display(),
@@ -223,7 +1095,156 @@ impl FsCliConfig {
             }
         }
 
-        Ok(default_config)
+        Ok((default_config, created_path))
+    }
+
+    /// Parse configuration YAML, renaming legacy keys
+    /// (see [`migrate_legacy_config`]) when the strict parse fails.
+    /// Errors name the legacy keys that are renamed, so a rejected key can
+    /// be told apart from one that was migrated.
+    pub fn load_with_migration(content: &str, path: &Path) -> Result<Self> {
+        let strict_err = match serde_yaml::from_str(content) {
+            Ok(config) => return Ok(config),
+            Err(e) => e,
+        };
+        let migrated = migrate_legacy_config(content);
+        if migrated == content {
+            return Err(strict_err).with_context(|| {
+                format!(
+                    "Failed to parse config file {} (legacy keys renamed while loading: {})",
+                    path.display(),
+                    legacy_keys_note()
+                )
+            });
+        }
+        let config = serde_yaml::from_str(&migrated).with_context(|| {
+            format!(
+                "Failed to parse config file {} (even after renaming legacy keys: {})",
+                path.display(),
+                legacy_keys_note()
+            )
+        })?;
+        eprintln!(
+            "Warning: {} uses legacy keys, renamed while loading; /save-config writes the current names",
+            path.display()
+        );
+        Ok(config)
+    }
+
+    /// Parse `path` and merge the profiles of its `include` files.
+    /// `visited_paths` holds the files being read further up the include
+    /// chain, so a file including itself (directly or not) is an error.
+    fn read_file(path: &Path, visited_paths: &mut HashSet<PathBuf>) -> Result<Self> {
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        if !visited_paths.insert(canonical.clone()) {
+            anyhow::bail!("Circular include of config file {}", path.display());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let mut config = Self::load_with_migration(&content, path)?;
+
+        let base_dir = canonical
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        for include in config
+            .include
+            .clone()
+            .unwrap_or_default()
+        {
+            let include_path = base_dir.join(&include);
+            let included = Self::read_file(&include_path, visited_paths)
+                .with_context(|| format!("Included from {}", path.display()))?;
+            for (name, profile) in included.fs_cli {
+                if !config
+                    .fs_cli
+                    .contains_key(&name)
+                {
+                    config
+                        .included
+                        .insert(name.clone());
+                    config
+                        .fs_cli
+                        .insert(name, profile);
+                }
+            }
+        }
+
+        visited_paths.remove(&canonical);
+        Ok(config)
+    }
+
+    /// Write the configuration to `path` as YAML, replacing it atomically
+    /// through a temporary file in the same directory. Profiles from
+    /// `include` files are not written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut own = self.clone();
+        own.fs_cli
+            .retain(|name, _| {
+                !self
+                    .included
+                    .contains(name)
+            });
+        let yaml = serde_yaml::to_string(&own).context("Could not serialize configuration")?;
+        if let Some(parent) = path
+            .parent()
+            .filter(|p| {
+                !p.as_os_str()
+                    .is_empty()
+            })
+        {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Could not create {}", parent.display()))?;
+        }
+        let tmp = path.with_extension("yaml.tmp");
+        std::fs::write(&tmp, yaml).with_context(|| format!("Could not write {}", tmp.display()))?;
+
+        if let Err(e) = std::fs::rename(&tmp, path) {
+            // rename cannot replace across drives on Windows; copy instead
+            #[cfg(windows)]
+            {
+                warn!(
+                    "Could not rename {} ({}), copying instead",
+                    tmp.display(),
+                    e
+                );
+                let copied = std::fs::copy(&tmp, path);
+                std::fs::remove_file(&tmp).ok();
+                copied.with_context(|| format!("Could not write {}", path.display()))?;
+                return Ok(());
+            }
+            #[cfg(not(windows))]
+            {
+                std::fs::remove_file(&tmp).ok();
+                return Err(e).with_context(|| format!("Could not replace {}", path.display()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate each profile; see [`FsCliConfig::load`]
+    fn check_profiles(&self, active_profile: Option<&str>) -> Result<()> {
+        let mut names: Vec<&String> = self
+            .fs_cli
+            .keys()
+            .collect();
+        names.sort();
+        for name in names {
+            let errors = self.fs_cli[name].validate();
+            if errors.is_empty() {
+                continue;
+            }
+            if active_profile == Some(name.as_str()) {
+                anyhow::bail!("Profile '{}': {}", name, errors.join("; "));
+            }
+            for error in errors {
+                eprintln!("Warning: profile '{}': {}", name, error);
+            }
+        }
+        Ok(())
     }
 
     /// Get list of default configuration file paths to try
@@ -243,12 +1264,30 @@ impl FsCliConfig {
         paths
     }
 
-    /// Get a profile by name
+    /// Get a profile by name, with settings inherited through `parent`
     pub fn get_profile(&self, name: &str) -> Result<ProfileConfig> {
-        self.fs_cli
+        self.resolve_profile(name, 0)
+    }
+
+    fn resolve_profile(&self, name: &str, depth: usize) -> Result<ProfileConfig> {
+        let profile = self
+            .fs_cli
             .get(name)
-            .cloned()
-            .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", name))
+            .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", name))?;
+        let Some(parent_name) = &profile.parent else {
+            return Ok(profile.clone());
+        };
+        if depth >= MAX_PROFILE_DEPTH {
+            anyhow::bail!(
+                "Profile '{}' inherits through more than {} parents; is there a cycle?",
+                name,
+                MAX_PROFILE_DEPTH
+            );
+        }
+        let parent = self
+            .resolve_profile(parent_name, depth + 1)
+            .with_context(|| format!("Resolving parent of profile '{}'", name))?;
+        Ok(profile.merge_from_parent(&parent))
     }
 
     /// Get list of available profile names
@@ -265,7 +1304,7 @@ impl Default for FsCliConfig {
         let mut fs_cli = HashMap::new();
         fs_cli.insert("default".to_string(), ProfileConfig::default());
 
-        Self { fs_cli }
+        Self::new(fs_cli)
     }
 }
 
@@ -329,6 +1368,103 @@ fs_cli:
         assert!(!false_app_config.quiet);
     }
 
+    #[test]
+    fn test_profile_inherits_from_parent() {
+        let yaml_content = r#"
+fs_cli:
+  base:
+    host: pbx.example.com
+    password: secret
+    quiet: true
+    log_rate_limit: 200
+    show_log_source: true
+    log_colors:
+      err: magenta
+    macros:
+      f1: help
+      f2: status
+  child:
+    parent: base
+    host: staging.example.com
+    macros:
+      f2: show calls
+"#;
+        let config: FsCliConfig = serde_yaml::from_str(yaml_content).unwrap();
+        let child = config
+            .get_profile("child")
+            .unwrap();
+        assert_eq!(child.host, Some("staging.example.com".to_string()));
+        assert_eq!(child.password, Some("secret".to_string()));
+        assert_eq!(child.quiet, Some(true));
+        assert_eq!(child.log_rate_limit, Some(200));
+        assert_eq!(child.show_log_source, Some(true));
+        assert_eq!(
+            child
+                .log_colors
+                .unwrap()["err"],
+            "magenta"
+        );
+        assert_eq!(child.port, None);
+        let macros = child
+            .macros
+            .unwrap();
+        assert_eq!(macros["f1"], "help");
+        assert_eq!(macros["f2"], "show calls");
+    }
+
+    #[test]
+    fn test_profile_inherits_through_two_levels() {
+        let yaml_content = r#"
+fs_cli:
+  site:
+    password: secret
+    timeout: 5000
+  region:
+    parent: site
+    timeout: 3000
+  host:
+    parent: region
+    host: pbx1.example.com
+"#;
+        let config: FsCliConfig = serde_yaml::from_str(yaml_content).unwrap();
+        let profile = config
+            .get_profile("host")
+            .unwrap();
+        assert_eq!(profile.host, Some("pbx1.example.com".to_string()));
+        assert_eq!(profile.timeout, Some(3000));
+        assert_eq!(profile.password, Some("secret".to_string()));
+    }
+
+    #[test]
+    fn test_profile_parent_cycle_is_an_error() {
+        let yaml_content = r#"
+fs_cli:
+  a:
+    parent: b
+  b:
+    parent: a
+"#;
+        let config: FsCliConfig = serde_yaml::from_str(yaml_content).unwrap();
+        let err = config
+            .get_profile("a")
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("cycle"));
+    }
+
+    #[test]
+    fn test_profile_missing_parent_is_an_error() {
+        let yaml_content = r#"
+fs_cli:
+  a:
+    parent: nowhere
+"#;
+        let config: FsCliConfig = serde_yaml::from_str(yaml_content).unwrap();
+        let err = config
+            .get_profile("a")
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("Profile 'nowhere' not found"));
+    }
+
     #[test]
     fn test_typed_field_parsing() {
         let yaml_content = r#"
@@ -390,6 +1526,482 @@ fs_cli:
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_invalid_log_level_fails_load_with_yaml_error() {
+        let dir = scratch_dir("bad_log_level");
+        let path = dir.join("fs_cli.yaml");
+        std::fs::write(&path, "fs_cli:\n  default:\n    log_level: loudest\n").unwrap();
+        let err = FsCliConfig::load(Some(path), None).unwrap_err();
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(err
+            .chain()
+            .any(|e| e.is::<serde_yaml::Error>()));
+        assert!(format!("{:#}", err).contains("loudest"));
+    }
+
+    #[test]
+    fn test_unknown_profile_key_rejected() {
+        let err = serde_yaml::from_str::<FsCliConfig>("fs_cli:\n  p:\n    passord: x\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown field `passord`"), "{}", err);
+    }
+
+    fn assert_migrates(old: &str, new: &str) {
+        assert_eq!(
+            migrate_legacy_config(&format!("fs_cli:\n  p:\n    {}\n", old)),
+            format!("fs_cli:\n  p:\n    {}\n", new)
+        );
+    }
+
+    #[test]
+    fn test_migrate_connect_timeout() {
+        assert_migrates("connect_timeout: 5000", "timeout: 5000");
+    }
+
+    #[test]
+    fn test_migrate_fnkeys() {
+        assert_migrates("fnkeys:", "macros:");
+    }
+
+    #[test]
+    fn test_migrate_tls_ca() {
+        assert_migrates("tls_ca: /etc/ca.pem", "tls_ca_cert: /etc/ca.pem");
+    }
+
+    #[test]
+    fn test_migrate_tls_cert() {
+        assert_migrates("tls_cert: /etc/c.pem", "tls_client_cert: /etc/c.pem");
+    }
+
+    #[test]
+    fn test_migrate_tls_key() {
+        assert_migrates("tls_key: /etc/k.pem", "tls_client_key: /etc/k.pem");
+    }
+
+    #[test]
+    fn test_migrate_leaves_other_lines() {
+        // Values are untouched, and top-level keys are not in a profile
+        let yaml = "fs_cli:\n  p:\n    host: tls_key\n    password: \"tls_ca: x\"\n";
+        assert_eq!(migrate_legacy_config(yaml), yaml);
+        assert_eq!(
+            migrate_legacy_config("connect_timeout: 1"),
+            "connect_timeout: 1"
+        );
+    }
+
+    #[test]
+    fn test_load_with_migration() {
+        let path = Path::new("legacy.yaml");
+        let legacy = "fs_cli:\n  p:\n    connect_timeout: 5000\n    fnkeys:\n      f1: status\n";
+        let config = FsCliConfig::load_with_migration(legacy, path).unwrap();
+        let profile = config
+            .get_profile("p")
+            .unwrap();
+        assert_eq!(profile.timeout, Some(5000));
+        assert_eq!(
+            profile
+                .macros
+                .as_ref()
+                .and_then(|m| m.get("f1"))
+                .map(String::as_str),
+            Some("status")
+        );
+
+        let err =
+            FsCliConfig::load_with_migration("fs_cli:\n  p:\n    passord: x\n", path).unwrap_err();
+        let err = format!("{:#}", err);
+        assert!(err.contains("unknown field `passord`"), "{}", err);
+        assert!(err.contains("connect_timeout -> timeout"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_log_timestamp_settings_rejected() {
+        let profile = ProfileConfig {
+            log_timestamp_format: Some("%H:%M:%Q".to_string()),
+            ..ProfileConfig::default()
+        };
+        let err = profile
+            .to_app_config()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("log_timestamp_format"));
+
+        let profile = ProfileConfig {
+            log_timezone: Some("Nowhere/Special".to_string()),
+            ..ProfileConfig::default()
+        };
+        assert!(profile
+            .to_app_config()
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate_default_profile_is_clean() {
+        assert!(ProfileConfig::default()
+            .validate()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_validate_port_zero() {
+        let profile = ProfileConfig {
+            port: Some(0),
+            ..ProfileConfig::default()
+        };
+        assert_eq!(profile.validate(), vec!["port must be between 1 and 65535"]);
+    }
+
+    #[test]
+    fn test_validate_timeout_zero() {
+        let profile = ProfileConfig {
+            timeout: Some(0),
+            ..ProfileConfig::default()
+        };
+        assert_eq!(profile.validate(), vec!["timeout must be greater than 0"]);
+    }
+
+    #[test]
+    fn test_validate_max_auto_complete_uuid_range() {
+        for (max, valid) in [(0, false), (1, true), (10_000, true), (10_001, false)] {
+            let profile = ProfileConfig {
+                max_auto_complete_uuid: Some(max),
+                ..ProfileConfig::default()
+            };
+            assert_eq!(
+                profile
+                    .validate()
+                    .is_empty(),
+                valid,
+                "{}",
+                max
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_history_file_parent() {
+        let missing = ProfileConfig {
+            history_file: Some(PathBuf::from("/nonexistent-fs-cli-dir/history")),
+            ..ProfileConfig::default()
+        };
+        assert_eq!(
+            missing
+                .validate()
+                .len(),
+            1
+        );
+
+        let relative = ProfileConfig {
+            history_file: Some(PathBuf::from("history")),
+            ..ProfileConfig::default()
+        };
+        assert!(relative
+            .validate()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_validate_collects_all_errors() {
+        let profile = ProfileConfig {
+            port: Some(0),
+            timeout: Some(0),
+            ..ProfileConfig::default()
+        };
+        assert_eq!(
+            profile
+                .validate()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_check_profiles_errors_only_for_active() {
+        let yaml = r#"
+fs_cli:
+  default:
+    host: localhost
+  broken:
+    port: 0
+"#;
+        let config: FsCliConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config
+            .check_profiles(Some("default"))
+            .is_ok());
+        assert!(config
+            .check_profiles(None)
+            .is_ok());
+        let err = config
+            .check_profiles(Some("broken"))
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("port must be between 1 and 65535"));
+    }
+
+    #[test]
+    fn test_env_overrides_applied() {
+        // Unique prefix so parallel tests don't see each other's variables
+        let prefix = "FS_CLI_TEST_ENV_APPLIED";
+        let vars = [
+            ("HOST", "pbx.example.com"),
+            ("PORT", "8022"),
+            ("PASSWORD", "secret"),
+            ("USER", "admin@default"),
+            ("LOG_LEVEL", "warning"),
+            ("COLOR", "never"),
+            ("DEBUG", "info"),
+            ("QUIET", "yes"),
+            ("RECONNECT", "1"),
+            ("TIMEOUT", "5000"),
+        ];
+        for (name, value) in vars {
+            std::env::set_var(format!("{}_{}", prefix, name), value);
+        }
+
+        let mut app = ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        let result = app.apply_env_overrides(prefix);
+        for (name, _) in vars {
+            std::env::remove_var(format!("{}_{}", prefix, name));
+        }
+        result.unwrap();
+
+        assert_eq!(app.host, "pbx.example.com");
+        assert_eq!(app.port, 8022);
+        assert_eq!(app.password, "secret");
+        assert_eq!(app.user, Some("admin@default".to_string()));
+        assert_eq!(app.log_level, LogLevel::Warning);
+        assert_eq!(app.color, ColorMode::Never);
+        assert_eq!(app.debug, EslDebugLevel::Info);
+        assert!(app.quiet);
+        assert!(app.reconnect);
+        assert_eq!(app.timeout, 5000);
+    }
+
+    #[test]
+    fn test_env_overrides_absent_leave_config() {
+        let mut app = ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        app.apply_env_overrides("FS_CLI_TEST_ENV_UNSET")
+            .unwrap();
+        assert_eq!(app.host, "localhost");
+        assert_eq!(app.port, 8021);
+    }
+
+    #[test]
+    fn test_env_overrides_invalid_value_names_variable() {
+        let var = "FS_CLI_TEST_ENV_INVALID_PORT";
+        std::env::set_var(var, "not-a-port");
+        let mut app = ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        let result = app.apply_env_overrides("FS_CLI_TEST_ENV_INVALID");
+        std::env::remove_var(var);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains(var));
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let dir = std::env::temp_dir().join(format!("fs_cli_save_test_{}", std::process::id()));
+        let path = dir.join("fs_cli.yaml");
+        let mut config = FsCliConfig::default();
+        config
+            .fs_cli
+            .insert(
+                "prod".to_string(),
+                ProfileConfig {
+                    host: Some("pbx.example.com".to_string()),
+                    port: Some(8022),
+                    ..ProfileConfig::default()
+                },
+            );
+
+        config
+            .save(&path)
+            .unwrap();
+        assert!(!path
+            .with_extension("yaml.tmp")
+            .exists());
+        let (loaded, loaded_path) = FsCliConfig::load(Some(path.clone()), Some("prod")).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(loaded_path, Some(path));
+        let mut names = loaded.get_profile_names();
+        names.sort();
+        assert_eq!(names, vec!["default", "prod"]);
+        let prod = loaded
+            .get_profile("prod")
+            .unwrap();
+        assert_eq!(prod.host, Some("pbx.example.com".to_string()));
+        assert_eq!(prod.port, Some(8022));
+    }
+
+    #[test]
+    fn test_unset_profile_fields_not_serialized() {
+        let yaml = "parent: default\nhost: pbx.example.com\n";
+        let profile: ProfileConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(serde_yaml::to_string(&profile).unwrap(), yaml);
+    }
+
+    /// Fresh scratch directory for a test, removed first if left over
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fs_cli_{}_{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(dir.join("sites")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_include_nested_files() {
+        let dir = scratch_dir("include_nested");
+        std::fs::write(
+            dir.join("fs_cli.yaml"),
+            "include: [sites/dc1.yaml]\nfs_cli:\n  default:\n    host: main\n  shared:\n    host: primary\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("sites/dc1.yaml"),
+            "include: [dc2.yaml]\nfs_cli:\n  dc1:\n    host: dc1.example.com\n  shared:\n    host: dc1\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("sites/dc2.yaml"),
+            "fs_cli:\n  dc2:\n    host: dc2.example.com\n",
+        )
+        .unwrap();
+
+        let result = FsCliConfig::load(Some(dir.join("fs_cli.yaml")), Some("default"));
+        std::fs::remove_dir_all(&dir).ok();
+        let (config, _) = result.unwrap();
+
+        let mut names = config.get_profile_names();
+        names.sort();
+        assert_eq!(names, vec!["dc1", "dc2", "default", "shared"]);
+        let host = |name: &str| {
+            config
+                .get_profile(name)
+                .unwrap()
+                .host
+        };
+        assert_eq!(host("dc2"), Some("dc2.example.com".to_string()));
+        assert_eq!(host("shared"), Some("primary".to_string()));
+    }
+
+    #[test]
+    fn test_include_circular_is_error() {
+        let dir = scratch_dir("include_circular");
+        std::fs::write(
+            dir.join("fs_cli.yaml"),
+            "include: [sites/a.yaml]\nfs_cli: {}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("sites/a.yaml"), "include: [b.yaml]\nfs_cli: {}\n").unwrap();
+        std::fs::write(dir.join("sites/b.yaml"), "include: [a.yaml]\nfs_cli: {}\n").unwrap();
+
+        let result = FsCliConfig::load(Some(dir.join("fs_cli.yaml")), None);
+        std::fs::remove_dir_all(&dir).ok();
+        let err = format!("{:#}", result.unwrap_err());
+        assert!(err.contains("Circular include"), "{}", err);
+    }
+
+    #[test]
+    fn test_save_leaves_out_included_profiles() {
+        let dir = scratch_dir("include_save");
+        let path = dir.join("fs_cli.yaml");
+        std::fs::write(&path, "include: [sites/dc1.yaml]\nfs_cli:\n  default: {}\n").unwrap();
+        std::fs::write(dir.join("sites/dc1.yaml"), "fs_cli:\n  dc1: {}\n").unwrap();
+
+        let (config, _) = FsCliConfig::load(Some(path.clone()), None).unwrap();
+        config
+            .save(&path)
+            .unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        let (reloaded, _) = FsCliConfig::load(Some(path), None).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(!saved.contains("dc1:"));
+        assert!(saved.contains("sites/dc1.yaml"));
+        assert!(reloaded
+            .get_profile("dc1")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_redact_password_with_special_characters() {
+        let yaml =
+            "fs_cli:\n  prod:\n    host: pbx\n    password: 'p@ss: w0rd#!\"'\n    port: 8021\n";
+        let redacted = redact_sensitive_fields(yaml);
+        assert_eq!(
+            redacted,
+            "fs_cli:\n  prod:\n    host: pbx\n    password: \"****\"\n    port: 8021\n"
+        );
+    }
+
+    #[test]
+    fn test_redact_multiline_values() {
+        let yaml = "\
+fs_cli:
+  prod:
+    password: |
+      first line
+
+      second line
+    tls_key: /etc/ssl/private/fs.key
+    user: null
+    port: 8021
+";
+        let redacted = redact_sensitive_fields(yaml);
+        assert_eq!(
+            redacted,
+            "fs_cli:\n  prod:\n    password: \"****\"\n    tls_key: \"****\"\n    user: null\n    port: 8021\n"
+        );
+    }
+
+    #[test]
+    fn test_redact_tls_client_key_in_effective_config() {
+        let mut app = ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        app.tls_client_key = Some("/etc/ssl/private/client.key".to_string());
+        let effective = FsCliConfig::new(
+            [("prod".to_string(), app.to_profile_config())]
+                .into_iter()
+                .collect(),
+        );
+        let redacted = redact_sensitive_fields(&serde_yaml::to_string(&effective).unwrap());
+        assert!(redacted.contains("tls_client_key: \"****\""));
+        assert!(!redacted.contains("client.key"));
+    }
+
+    #[test]
+    fn test_redact_keeps_null_password() {
+        let yaml = "password: null\npasswords: kept\n";
+        assert_eq!(redact_sensitive_fields(yaml), yaml);
+    }
+
+    #[test]
+    fn test_app_config_round_trips_to_profile() {
+        let app = ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        let back = app
+            .to_profile_config()
+            .to_app_config()
+            .unwrap();
+        assert_eq!(back.host, app.host);
+        assert_eq!(back.password, app.password);
+        assert_eq!(back.color, app.color);
+        assert_eq!(back.macros, app.macros);
+    }
+
     #[test]
     fn test_default_config_round_trips() {
         let default_cfg = FsCliConfig::default();
@@ -398,7 +2010,7 @@ fs_cli:
         let profile = reparsed
             .get_profile("default")
             .unwrap();
-        assert_eq!(profile.color, Some(crate::commands::ColorMode::Line));
+        assert_eq!(profile.color, Some(crate::commands::ColorMode::Auto));
         assert_eq!(profile.log_level, Some(crate::commands::LogLevel::Debug));
         assert_eq!(profile.debug, Some(crate::esl_debug::EslDebugLevel::None));
     }
@@ -0,0 +1,270 @@
+//! Named secondary FreeSWITCH connections (`/connect`, `/switch`)
+//!
+//! The session's primary connection stays owned by the reconnection loop in
+//! `session.rs`; this only tracks the extra connections opened at runtime and
+//! which one commands are currently routed to.
+
+use freeswitch_esl_tokio::EslClient;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Label of the connection established at startup
+pub const PRIMARY_CONNECTION: &str = "main";
+
+struct NamedConnection {
+    client: EslClient,
+    host: String,
+    port: u16,
+    /// Drains the connection's event stream so it stays alive
+    event_task: JoinHandle<()>,
+}
+
+/// Registry of named connections and the active command target.
+pub struct ConnectionManager {
+    connections: HashMap<String, NamedConnection>,
+    active_connection: String,
+    primary_host: String,
+    primary_port: u16,
+    /// Host shown in the readline prompt, shared with the readline thread
+    prompt_host: Arc<RwLock<String>>,
+}
+
+impl ConnectionManager {
+    /// Create a manager whose active connection is the primary one
+    pub fn new(primary_host: &str, primary_port: u16, prompt_host: Arc<RwLock<String>>) -> Self {
+        Self {
+            connections: HashMap::new(),
+            active_connection: PRIMARY_CONNECTION.to_string(),
+            primary_host: primary_host.to_string(),
+            primary_port,
+            prompt_host,
+        }
+    }
+
+    /// Client commands should be sent to, falling back to `primary`
+    pub fn active_client<'c>(&'c self, primary: &'c EslClient) -> &'c EslClient {
+        self.connections
+            .get(&self.active_connection)
+            .map(|c| &c.client)
+            .unwrap_or(primary)
+    }
+
+    /// Name of the connection commands are routed to
+    pub fn active_name(&self) -> &str {
+        &self.active_connection
+    }
+
+    /// Host and port of the connection commands are routed to
+    pub fn active_address(&self) -> (&str, u16) {
+        match self
+            .connections
+            .get(&self.active_connection)
+        {
+            Some(conn) => (&conn.host, conn.port),
+            None => (&self.primary_host, self.primary_port),
+        }
+    }
+
+    /// Whether the primary (reconnect-managed) connection is active
+    pub fn is_primary_active(&self) -> bool {
+        self.active_connection == PRIMARY_CONNECTION
+    }
+
+    /// Store a new connection under `name` and make it active.
+    ///
+    /// An existing connection with the same name is replaced and returned so
+    /// the caller can disconnect it.
+    pub fn add(
+        &mut self,
+        name: &str,
+        host: &str,
+        port: u16,
+        client: EslClient,
+        event_task: JoinHandle<()>,
+    ) -> Option<EslClient> {
+        let previous = self
+            .connections
+            .insert(
+                name.to_string(),
+                NamedConnection {
+                    client,
+                    host: host.to_string(),
+                    port,
+                    event_task,
+                },
+            )
+            .map(|old| {
+                old.event_task
+                    .abort();
+                old.client
+            });
+        self.active_connection = name.to_string();
+        self.update_prompt_host();
+        previous
+    }
+
+    /// Route subsequent commands to `name`
+    pub fn switch(&mut self, name: &str) -> Result<(), String> {
+        if name != PRIMARY_CONNECTION
+            && !self
+                .connections
+                .contains_key(name)
+        {
+            return Err(format!("No connection named '{}'", name));
+        }
+        self.active_connection = name.to_string();
+        self.update_prompt_host();
+        Ok(())
+    }
+
+    /// Forget connection `name`, returning its client for disconnection.
+    ///
+    /// If it was active, commands go back to the primary connection.
+    pub fn remove(&mut self, name: &str) -> Option<EslClient> {
+        let removed = self
+            .connections
+            .remove(name)?;
+        removed
+            .event_task
+            .abort();
+        if self.active_connection == name {
+            self.active_connection = PRIMARY_CONNECTION.to_string();
+            self.update_prompt_host();
+        }
+        Some(removed.client)
+    }
+
+    /// Disconnect every secondary connection (session shutdown)
+    pub async fn disconnect_all(&mut self) {
+        let names: Vec<String> = self
+            .connections
+            .keys()
+            .cloned()
+            .collect();
+        for name in names {
+            if let Some(client) = self.remove(&name) {
+                client
+                    .disconnect()
+                    .await
+                    .ok();
+            }
+        }
+    }
+
+    /// One line per connection, `*` marking the active one
+    pub fn list(&self) -> String {
+        let mut lines =
+            vec![self.format_entry(PRIMARY_CONNECTION, &self.primary_host, self.primary_port)];
+        let mut names: Vec<&String> = self
+            .connections
+            .keys()
+            .collect();
+        names.sort();
+        for name in names {
+            let conn = &self.connections[name];
+            lines.push(self.format_entry(name, &conn.host, conn.port));
+        }
+        lines.join("\n")
+    }
+
+    fn format_entry(&self, name: &str, host: &str, port: u16) -> String {
+        let marker = if name == self.active_connection {
+            '*'
+        } else {
+            ' '
+        };
+        format!(
+            "{} {:<16} {}",
+            marker,
+            name,
+            crate::format_host_port(host, port)
+        )
+    }
+
+    fn update_prompt_host(&self) {
+        let host = match self
+            .connections
+            .get(&self.active_connection)
+        {
+            Some(conn) => conn
+                .host
+                .clone(),
+            None => crate::readline::prompt_host(&self.primary_host),
+        };
+        match self
+            .prompt_host
+            .write()
+        {
+            Ok(mut current) => *current = host,
+            Err(e) => warn!("Prompt host lock poisoned: {}", e),
+        }
+    }
+}
+
+/// Parse `host`, `host:port`, or `[v6addr]:port`, defaulting to port 8021.
+pub fn parse_host_port(address: &str) -> Option<(String, u16)> {
+    if let Some(rest) = address.strip_prefix('[') {
+        let (host, tail) = rest.split_once(']')?;
+        let port = match tail.strip_prefix(':') {
+            Some(port) => port
+                .parse()
+                .ok()?,
+            None if tail.is_empty() => 8021,
+            None => return None,
+        };
+        return Some((host.to_string(), port));
+    }
+    match address.rsplit_once(':') {
+        // A bare IPv6 address has several colons and no port
+        Some((host, _)) if host.contains(':') => Some((address.to_string(), 8021)),
+        Some((host, port)) if !host.is_empty() => Some((
+            host.to_string(),
+            port.parse()
+                .ok()?,
+        )),
+        Some(_) => None,
+        None if address.is_empty() => None,
+        None => Some((address.to_string(), 8021)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_host_port_variants() {
+        assert_eq!(
+            parse_host_port("pbx1:8022"),
+            Some(("pbx1".to_string(), 8022))
+        );
+        assert_eq!(parse_host_port("pbx1"), Some(("pbx1".to_string(), 8021)));
+        assert_eq!(
+            parse_host_port("[::1]:8022"),
+            Some(("::1".to_string(), 8022))
+        );
+        assert_eq!(parse_host_port("[::1]"), Some(("::1".to_string(), 8021)));
+        assert_eq!(parse_host_port("::1"), Some(("::1".to_string(), 8021)));
+    }
+
+    #[test]
+    fn active_address_falls_back_to_primary() {
+        let mut manager =
+            ConnectionManager::new("pbx1", 8022, Arc::new(RwLock::new(String::new())));
+        assert_eq!(manager.active_address(), ("pbx1", 8022));
+        assert!(manager
+            .switch("lab")
+            .is_err());
+        assert_eq!(manager.active_address(), ("pbx1", 8022));
+    }
+
+    #[test]
+    fn parse_host_port_rejects_garbage() {
+        assert_eq!(parse_host_port(""), None);
+        assert_eq!(parse_host_port(":8021"), None);
+        assert_eq!(parse_host_port("pbx1:notaport"), None);
+        assert_eq!(parse_host_port("[::1]x"), None);
+    }
+}
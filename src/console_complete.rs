@@ -5,16 +5,53 @@ use crate::esl_debug::EslDebugLevel;
 use freeswitch_esl_tokio::EslClient;
 
 /// Typed completion item returned from all completion sources
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Completion {
     /// Regular completion candidate (display and replacement are the same)
     Candidate(String),
-    /// UUID completion: display is full channel info, replacement is the UUID followed by a space
-    Uuid { uuid: String, display: String },
+    /// UUID completion: display is channel info, replacement is the UUID followed by a space.
+    /// Already filtered against the word being completed (by UUID or caller ID).
+    Uuid {
+        replacement: String,
+        display: String,
+    },
     /// Direct write directive — replaces the entire current token
     Write(String),
 }
 
+/// Client-side commands taking a channel UUID, mapped to the FreeSWITCH
+/// command whose completion they reuse.
+const UUID_COMMAND_ALIASES: &[(&str, &str)] = &[("/dtmf", "uuid_send_dtmf")];
+
+/// Commands whose first argument is a module name
+const MODULE_COMMANDS: &[&str] = &["load ", "unload ", "reload "];
+
+/// Sub-commands offered after `conference <name> `
+const CONFERENCE_SUBCOMMANDS: &[&str] =
+    &["list", "kick", "mute", "unmute", "play", "stop", "transfer"];
+
+/// Rewrite a client-side command line into the equivalent FreeSWITCH one for
+/// completion purposes, adjusting the cursor position accordingly.
+///
+/// Returns `None` when `line` does not start with an aliased command.
+pub fn translate_client_alias(line: &str, pos: usize) -> Option<(String, usize)> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    UUID_COMMAND_ALIASES
+        .iter()
+        .find_map(|(alias, target)| {
+            let rest = trimmed.strip_prefix(alias)?;
+            if !rest.starts_with(' ') {
+                return None;
+            }
+            let consumed = indent + alias.len();
+            Some((
+                format!("{}{}", target, rest),
+                pos.saturating_sub(consumed) + target.len(),
+            ))
+        })
+}
+
 /// Get console completions from FreeSWITCH using the console_complete API
 pub async fn get_console_complete(
     client: &EslClient,
@@ -23,6 +60,16 @@ pub async fn get_console_complete(
     debug_level: EslDebugLevel,
     channel_provider: &ChannelProvider,
 ) -> Vec<Completion> {
+    let translated = translate_client_alias(line, pos);
+    let (line, pos) = match &translated {
+        Some((translated_line, translated_pos)) => (translated_line.as_str(), *translated_pos),
+        None => (line, pos),
+    };
+
+    if let Some(completions) = get_dynamic_completions(client, line, pos, channel_provider).await {
+        return completions;
+    }
+
     let cmd = if pos > 0 && pos < line.len() {
         format!("console_complete c={};{}", pos, line)
     } else {
@@ -37,8 +84,12 @@ pub async fn get_console_complete(
     debug_level.debug_print(EslDebugLevel::Debug6, || format!("ESL API: {}", cmd));
 
     if is_uuid_command {
+        let partial = line[..pos.min(line.len())]
+            .rsplit(' ')
+            .next()
+            .unwrap_or("");
         match channel_provider
-            .get_uuid_completions(client)
+            .get_uuid_completions(client, partial)
             .await
         {
             Ok(Some(enhanced_completions)) => {
@@ -96,6 +147,75 @@ pub async fn get_console_complete(
     }
 }
 
+/// Completions for arguments FreeSWITCH's own console_complete can't list
+/// (e.g. profile names). Returns `None` to fall through to console_complete.
+async fn get_dynamic_completions(
+    client: &EslClient,
+    line: &str,
+    pos: usize,
+    channel_provider: &ChannelProvider,
+) -> Option<Vec<Completion>> {
+    let before_cursor = line
+        .get(..pos)
+        .unwrap_or(line)
+        .trim_start();
+
+    if let Some((name, partial)) = before_cursor
+        .strip_prefix("conference ")
+        .and_then(|rest| rest.split_once(' '))
+    {
+        if name == "list" || partial.contains(' ') {
+            return None;
+        }
+        return Some(
+            CONFERENCE_SUBCOMMANDS
+                .iter()
+                .map(|sub| Completion::Candidate(sub.to_string()))
+                .collect(),
+        );
+    }
+
+    let result = if completing_argument(before_cursor, "sofia profile ") {
+        channel_provider
+            .get_sofia_profile_completions(client)
+            .await
+    } else if completing_argument(before_cursor, "conference ") {
+        channel_provider
+            .get_conference_completions(client)
+            .await
+    } else if let Some(command) = MODULE_COMMANDS
+        .iter()
+        .find(|command| completing_argument(before_cursor, command))
+    {
+        let loaded_only = *command != "load ";
+        channel_provider
+            .get_module_completions(client, loaded_only)
+            .await
+    } else {
+        return None;
+    };
+
+    match result {
+        Ok(names) => Some(
+            names
+                .into_iter()
+                .map(Completion::Candidate)
+                .collect(),
+        ),
+        Err(e) => {
+            tracing::debug!("Dynamic completion lookup failed, falling back: {:#}", e);
+            None
+        }
+    }
+}
+
+/// Whether the cursor is on the first argument after `prefix`
+fn completing_argument(before_cursor: &str, prefix: &str) -> bool {
+    before_cursor
+        .strip_prefix(prefix)
+        .is_some_and(|partial| !partial.contains(' '))
+}
+
 /// Parse the console_complete response from FreeSWITCH
 pub fn parse_console_complete_response(body: &str) -> Vec<Completion> {
     let mut completions = Vec::new();
@@ -138,3 +258,102 @@ pub fn parse_console_complete_response(body: &str) -> Vec<Completion> {
 
     completions
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate_client_alias_dtmf() {
+        let line = "/dtmf 0f2a";
+        let (translated, pos) = translate_client_alias(line, line.len()).unwrap();
+        assert_eq!(translated, "uuid_send_dtmf 0f2a");
+        assert_eq!(pos, translated.len());
+    }
+
+    #[test]
+    fn completing_argument_only_first_word() {
+        assert!(completing_argument("reload ", "reload "));
+        assert!(completing_argument("reload mod_so", "reload "));
+        assert!(!completing_argument("reload mod_sofia x", "reload "));
+        assert!(!completing_argument("reloadxml", "reload "));
+    }
+
+    fn candidates(names: &[&str]) -> Vec<Completion> {
+        names
+            .iter()
+            .map(|n| Completion::Candidate(n.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_response_empty() {
+        assert!(parse_console_complete_response("").is_empty());
+        assert!(parse_console_complete_response("  \n\t\n").is_empty());
+    }
+
+    #[test]
+    fn parse_response_brackets() {
+        assert_eq!(
+            parse_console_complete_response("[status][version][reload]"),
+            candidates(&["status", "version", "reload"])
+        );
+    }
+
+    #[test]
+    fn parse_response_write_directive() {
+        assert_eq!(
+            parse_console_complete_response("write=1:sofia"),
+            vec![Completion::Write("sofia".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_response_brackets_win_over_write() {
+        assert_eq!(
+            parse_console_complete_response("[sofia][sofia_contact]\nwrite=1:sofia"),
+            candidates(&["sofia", "sofia_contact"])
+        );
+    }
+
+    #[test]
+    fn parse_response_trims_inside_brackets() {
+        assert_eq!(
+            parse_console_complete_response("[  status  ][ version]"),
+            candidates(&["status", "version"])
+        );
+    }
+
+    #[test]
+    fn parse_response_unicode() {
+        assert_eq!(
+            parse_console_complete_response("[conférence][通话]"),
+            candidates(&["conférence", "通话"])
+        );
+    }
+
+    #[test]
+    fn parse_response_multiple_lines() {
+        let body = "\n[show]      [shutdown]\n[sofia]\n\n[status][strftime]\n";
+        assert_eq!(
+            parse_console_complete_response(body),
+            candidates(&["show", "shutdown", "sofia", "status", "strftime"])
+        );
+    }
+
+    #[test]
+    fn parse_response_blank_brackets() {
+        assert!(parse_console_complete_response("[   ]").is_empty());
+        assert_eq!(
+            parse_console_complete_response("[ ][status]"),
+            candidates(&["status"])
+        );
+    }
+
+    #[test]
+    fn translate_client_alias_ignores_other_commands() {
+        assert!(translate_client_alias("/dtmfx 1", 8).is_none());
+        assert!(translate_client_alias("/log debug", 10).is_none());
+        assert!(translate_client_alias("uuid_kill ", 10).is_none());
+    }
+}
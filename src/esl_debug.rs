@@ -1,13 +1,76 @@
 //! ESL client-side debug logging functionality
 //!
-//! Implements debug levels similar to the original fs_cli -d option (0-7)
-//! for controlling ESL protocol message logging on the client side.
+//! Implements debug levels similar to the original fs_cli -d option (0-7),
+//! extended to 10 for internal tools, for controlling ESL protocol message
+//! logging on the client side.
 
+use anyhow::Context;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-/// ESL client-side debug levels (0-7)
+/// `--debug-json`: process-wide since levels are passed around by value
+static STRUCTURED: AtomicBool = AtomicBool::new(false);
+
+/// Print debug messages as JSON lines instead of `[ESL_DEBUG:N] msg`
+pub fn set_structured(enabled: bool) {
+    STRUCTURED.store(enabled, Ordering::Relaxed);
+}
+
+/// `--debug-output-file`: debug messages are copied here as well as stderr
+static OUTPUT_FILE: OnceLock<Mutex<BufWriter<File>>> = OnceLock::new();
+
+/// Also append debug messages to `path`. Only the first call takes effect.
+pub fn set_output_file(path: &Path) -> anyhow::Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Cannot open debug output file {}", path.display()))?;
+    if OUTPUT_FILE
+        .set(Mutex::new(BufWriter::new(file)))
+        .is_err()
+    {
+        anyhow::bail!("Debug output file already set");
+    }
+    Ok(())
+}
+
+fn write_output_file(line: &str) {
+    let Some(output) = OUTPUT_FILE.get() else {
+        return;
+    };
+    if let Ok(mut writer) = output.lock() {
+        // Flushed per line so the file is current if the process is killed
+        writeln!(writer, "{}", line)
+            .and_then(|()| writer.flush())
+            .ok();
+    }
+}
+
+/// One debug message as a JSON line:
+/// `{"ts":<unix_ms>,"level":<n>,"level_name":"<name>","msg":"<msg>"}`
+pub fn format_debug_json(level: EslDebugLevel, message: &str) -> String {
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    serde_json::json!({
+        "ts": ts,
+        "level": level as u8,
+        "level_name": level.name(),
+        "msg": message,
+    })
+    .to_string()
+}
+
+/// ESL client-side debug levels (0-10)
 /// Matches the original fs_cli esl_global_set_default_logger levels
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
 pub enum EslDebugLevel {
@@ -19,7 +82,10 @@ pub enum EslDebugLevel {
     Debug = 4,   // Basic debug output
     Debug5 = 5,  // More verbose debug
     Debug6 = 6,  // ESL protocol messages and communication
-    Debug7 = 7,  // Maximum debug (all messages)
+    Debug7 = 7,  // All messages fs_cli shows
+    Debug8 = 8,  // Levels 8-10 are for internal tools and trace everything
+    Debug9 = 9,
+    Debug10 = 10,
 }
 
 impl FromStr for EslDebugLevel {
@@ -28,13 +94,13 @@ impl FromStr for EslDebugLevel {
     fn from_str(s: &str) -> Result<Self, String> {
         let v: u8 = s
             .parse()
-            .map_err(|_| format!("Invalid ESL debug level: {} (must be 0-7)", s))?;
+            .map_err(|_| format!("Invalid ESL debug level: {} (must be 0-10)", s))?;
         Self::from_u8(v).map_err(|e| e.to_string())
     }
 }
 
 impl EslDebugLevel {
-    /// Create from u8 value (0-7)
+    /// Create from u8 value (0-10)
     pub fn from_u8(value: u8) -> anyhow::Result<Self> {
         match value {
             0 => Ok(EslDebugLevel::None),
@@ -45,13 +111,64 @@ impl EslDebugLevel {
             5 => Ok(EslDebugLevel::Debug5),
             6 => Ok(EslDebugLevel::Debug6),
             7 => Ok(EslDebugLevel::Debug7),
+            8 => Ok(EslDebugLevel::Debug8),
+            9 => Ok(EslDebugLevel::Debug9),
+            10 => Ok(EslDebugLevel::Debug10),
             _ => Err(anyhow::anyhow!(
-                "Invalid ESL debug level: {} (must be 0-7)",
+                "Invalid ESL debug level: {} (must be 0-10)",
                 value
             )),
         }
     }
 
+    /// Create from a level name (`none`, `error`, `warning`, `info`, `debug`,
+    /// `debug5`-`debug10`), case-insensitive
+    pub fn from_name(s: &str) -> anyhow::Result<Self> {
+        match s
+            .to_lowercase()
+            .as_str()
+        {
+            "none" => Ok(EslDebugLevel::None),
+            "error" => Ok(EslDebugLevel::Error),
+            "warning" => Ok(EslDebugLevel::Warning),
+            "info" => Ok(EslDebugLevel::Info),
+            "debug" => Ok(EslDebugLevel::Debug),
+            "debug5" => Ok(EslDebugLevel::Debug5),
+            "debug6" => Ok(EslDebugLevel::Debug6),
+            "debug7" => Ok(EslDebugLevel::Debug7),
+            "debug8" => Ok(EslDebugLevel::Debug8),
+            "debug9" => Ok(EslDebugLevel::Debug9),
+            "debug10" => Ok(EslDebugLevel::Debug10),
+            _ => Err(anyhow::anyhow!(
+                "Invalid ESL debug level: {} (must be 0-10 or none, error, warning, info, debug, debug5-debug10)",
+                s
+            )),
+        }
+    }
+
+    /// Parse either a number (0-10) or a level name
+    pub fn parse_number_or_name(s: &str) -> anyhow::Result<Self> {
+        s.parse::<Self>()
+            .or_else(|_| Self::from_name(s))
+    }
+
+    /// Level name as shown in debug output
+    pub fn name(&self) -> &'static str {
+        match self {
+            EslDebugLevel::None => "NONE",
+            EslDebugLevel::Error => "ERROR",
+            EslDebugLevel::Warning => "WARNING",
+            EslDebugLevel::Info => "INFO",
+            EslDebugLevel::Debug => "DEBUG",
+            EslDebugLevel::Debug5 => "DEBUG5",
+            EslDebugLevel::Debug6 => "DEBUG6",
+            EslDebugLevel::Debug7 => "DEBUG7",
+            EslDebugLevel::Debug8 => "DEBUG8",
+            EslDebugLevel::Debug9 => "DEBUG9",
+            EslDebugLevel::Debug10 => "DEBUG10",
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             EslDebugLevel::None => "0",
@@ -62,6 +179,9 @@ impl EslDebugLevel {
             EslDebugLevel::Debug5 => "5",
             EslDebugLevel::Debug6 => "6",
             EslDebugLevel::Debug7 => "7",
+            EslDebugLevel::Debug8 => "8",
+            EslDebugLevel::Debug9 => "9",
+            EslDebugLevel::Debug10 => "10",
         }
     }
 
@@ -78,13 +198,20 @@ impl EslDebugLevel {
             EslDebugLevel::Debug6 | EslDebugLevel::Debug7 => {
                 "fs_cli_rs=trace,freeswitch_esl_tokio=trace,rustyline=warn"
             }
+            EslDebugLevel::Debug8 | EslDebugLevel::Debug9 | EslDebugLevel::Debug10 => "trace",
         }
     }
 
     /// Debug print if level is high enough; `msg` is only evaluated when enabled
     pub fn debug_print(&self, level: EslDebugLevel, msg: impl FnOnce() -> String) {
         if *self >= level {
-            eprintln!("[ESL_DEBUG:{}] {}", level.as_str(), msg());
+            let line = if STRUCTURED.load(Ordering::Relaxed) {
+                format_debug_json(level, &msg())
+            } else {
+                format!("[ESL_DEBUG:{}] {}", level.name(), msg())
+            };
+            eprintln!("{}", line);
+            write_output_file(&line);
         }
     }
 }
@@ -101,9 +228,170 @@ impl Serialize for EslDebugLevel {
     }
 }
 
+/// Accepts a number (`debug: 3`) or a level name (`debug: info`)
+struct EslDebugLevelVisitor;
+
+impl serde::de::Visitor<'_> for EslDebugLevelVisitor {
+    type Value = EslDebugLevel;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a debug level 0-10 or a level name")
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        u8::try_from(v)
+            .map_err(|_| anyhow::anyhow!("Invalid ESL debug level: {} (must be 0-10)", v))
+            .and_then(EslDebugLevel::from_u8)
+            .map_err(E::custom)
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        match u64::try_from(v) {
+            Ok(v) => self.visit_u64(v),
+            Err(_) => Err(E::custom(format!(
+                "Invalid ESL debug level: {} (must be 0-10)",
+                v
+            ))),
+        }
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        EslDebugLevel::parse_number_or_name(v).map_err(E::custom)
+    }
+}
+
 impl<'de> Deserialize<'de> for EslDebugLevel {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
-        let v = u8::deserialize(d)?;
-        Self::from_u8(v).map_err(serde::de::Error::custom)
+        d.deserialize_any(EslDebugLevelVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_print_copies_to_output_file() {
+        let path = std::env::temp_dir().join(format!("fs_cli_debug_{}.log", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        set_output_file(&path).unwrap();
+        assert!(set_output_file(&path).is_err());
+        EslDebugLevel::Debug6.debug_print(EslDebugLevel::Debug6, || "ESL API: status".to_string());
+        EslDebugLevel::Debug6.debug_print(EslDebugLevel::Debug7, || "hidden".to_string());
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(content
+            .lines()
+            .any(|line| line == "[ESL_DEBUG:DEBUG6] ESL API: status"));
+        assert!(!content.contains("hidden"));
+    }
+
+    #[test]
+    fn from_name_case_insensitive() {
+        assert_eq!(
+            EslDebugLevel::from_name("none").unwrap(),
+            EslDebugLevel::None
+        );
+        assert_eq!(
+            EslDebugLevel::from_name("INFO").unwrap(),
+            EslDebugLevel::Info
+        );
+        assert_eq!(
+            EslDebugLevel::from_name("Debug7").unwrap(),
+            EslDebugLevel::Debug7
+        );
+        assert_eq!(
+            EslDebugLevel::from_name("DEBUG10").unwrap(),
+            EslDebugLevel::Debug10
+        );
+        assert!(EslDebugLevel::from_name("debug11").is_err());
+    }
+
+    #[test]
+    fn number_and_name_are_equivalent() {
+        assert_eq!(
+            EslDebugLevel::parse_number_or_name("4").unwrap(),
+            EslDebugLevel::parse_number_or_name("debug").unwrap()
+        );
+        assert_eq!(
+            EslDebugLevel::parse_number_or_name("2").unwrap(),
+            EslDebugLevel::Warning
+        );
+        assert!(EslDebugLevel::parse_number_or_name("11").is_err());
+        assert!(EslDebugLevel::parse_number_or_name("loud").is_err());
+    }
+
+    const ALL_LEVELS: [(&str, EslDebugLevel); 11] = [
+        ("none", EslDebugLevel::None),
+        ("error", EslDebugLevel::Error),
+        ("warning", EslDebugLevel::Warning),
+        ("info", EslDebugLevel::Info),
+        ("debug", EslDebugLevel::Debug),
+        ("debug5", EslDebugLevel::Debug5),
+        ("debug6", EslDebugLevel::Debug6),
+        ("debug7", EslDebugLevel::Debug7),
+        ("debug8", EslDebugLevel::Debug8),
+        ("debug9", EslDebugLevel::Debug9),
+        ("debug10", EslDebugLevel::Debug10),
+    ];
+
+    #[test]
+    fn debug_json_is_one_valid_object() {
+        let line = format_debug_json(EslDebugLevel::Debug6, "api \"status\"\nline two");
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], 6);
+        assert_eq!(value["level_name"], "DEBUG6");
+        assert_eq!(value["msg"], "api \"status\"\nline two");
+        assert!(value["ts"]
+            .as_u64()
+            .is_some_and(|ts| ts > 0));
+    }
+
+    #[test]
+    fn levels_above_seven_trace_everything() {
+        for level in [
+            EslDebugLevel::Debug8,
+            EslDebugLevel::Debug9,
+            EslDebugLevel::Debug10,
+        ] {
+            assert_eq!(level.tracing_filter(), "trace");
+            assert!(level > EslDebugLevel::Debug7);
+            assert_eq!(EslDebugLevel::from_u8(level as u8).unwrap(), level);
+        }
+        assert!(EslDebugLevel::from_u8(11)
+            .unwrap_err()
+            .to_string()
+            .contains("must be 0-10"));
+    }
+
+    #[test]
+    fn serde_round_trip_numeric() {
+        for (_, level) in ALL_LEVELS {
+            let yaml = serde_yaml::to_string(&level).unwrap();
+            assert_eq!(yaml.trim(), level.as_str());
+            assert_eq!(serde_yaml::from_str::<EslDebugLevel>(&yaml).unwrap(), level);
+        }
+    }
+
+    #[test]
+    fn serde_accepts_names() {
+        for (name, level) in ALL_LEVELS {
+            assert_eq!(serde_yaml::from_str::<EslDebugLevel>(name).unwrap(), level);
+            assert_eq!(
+                serde_yaml::from_str::<EslDebugLevel>(&format!("\"{}\"", level.as_str())).unwrap(),
+                level
+            );
+        }
+    }
+
+    #[test]
+    fn serde_rejects_unknown_level() {
+        let err = serde_yaml::from_str::<EslDebugLevel>("loud")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Invalid ESL debug level: loud"), "{}", err);
+        assert!(serde_yaml::from_str::<EslDebugLevel>("11").is_err());
+        assert!(serde_yaml::from_str::<EslDebugLevel>("-1").is_err());
     }
 }
@@ -0,0 +1,425 @@
+//! Runtime event subscriptions (`/subscribe`, `/unsubscribe`, `/subscriptions`)
+//! and `/bgapi` job tracking
+
+use crate::commands::ColorMode;
+use anyhow::{anyhow, Result};
+use colored::*;
+use freeswitch_esl_tokio::{EslEvent, EslEventType};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Most BACKGROUND_JOB events kept while no job claims them
+const MAX_UNCLAIMED_JOB_EVENTS: usize = 32;
+
+/// `/bgapi` jobs awaiting their BACKGROUND_JOB event, and events that
+/// arrived before their job was registered
+#[derive(Debug, Default)]
+pub struct JobTracker {
+    /// Job-UUID to (command, started at)
+    pending: HashMap<String, (String, Instant)>,
+    /// (Job-UUID, body) of events no pending job matched, oldest first
+    unclaimed: VecDeque<(String, String)>,
+}
+
+impl JobTracker {
+    /// Pending jobs as (Job-UUID, command, started at), oldest first
+    pub fn pending(&self) -> Vec<(&str, &str, Instant)> {
+        let mut jobs: Vec<_> = self
+            .pending
+            .iter()
+            .map(|(uuid, (command, started))| (uuid.as_str(), command.as_str(), *started))
+            .collect();
+        jobs.sort_by_key(|(_, _, started)| *started);
+        jobs
+    }
+}
+
+/// Shared between the command processor and the event consumer
+pub type PendingJobs = Arc<Mutex<JobTracker>>;
+
+/// Job-UUID from a bgapi reply such as `+OK Job-UUID: 7f4d...`
+pub fn job_uuid_from_reply(reply: &str) -> Option<&str> {
+    reply
+        .split_once("Job-UUID:")
+        .map(|(_, uuid)| uuid.trim())
+        .filter(|uuid| !uuid.is_empty())
+}
+
+/// Track the job `uuid` running `command` since `started`. The reply to
+/// bgapi can arrive after the job's event, so if that event was already
+/// received its body is returned and the job is not tracked.
+pub fn register_job(
+    jobs: &PendingJobs,
+    uuid: &str,
+    command: &str,
+    started: Instant,
+) -> Option<String> {
+    let mut jobs = jobs
+        .lock()
+        .ok()?;
+    if let Some(index) = jobs
+        .unclaimed
+        .iter()
+        .position(|(id, _)| id == uuid)
+    {
+        return jobs
+            .unclaimed
+            .remove(index)
+            .map(|(_, body)| body);
+    }
+    jobs.pending
+        .insert(uuid.to_string(), (command.to_string(), started));
+    None
+}
+
+/// If `event` finishes a tracked job, remove it and return its command and
+/// how long it ran. Other BACKGROUND_JOB events are kept for
+/// `register_job`.
+pub fn take_finished_job(event: &EslEvent, jobs: &PendingJobs) -> Option<(String, Duration)> {
+    if event.event_type() != Some(EslEventType::BackgroundJob) {
+        return None;
+    }
+    let uuid = event.header_str("Job-UUID")?;
+    let mut jobs = jobs
+        .lock()
+        .ok()?;
+    match jobs
+        .pending
+        .remove(uuid)
+    {
+        Some((command, started)) => Some((command, started.elapsed())),
+        None => {
+            if jobs
+                .unclaimed
+                .len()
+                >= MAX_UNCLAIMED_JOB_EVENTS
+            {
+                jobs.unclaimed
+                    .pop_front();
+            }
+            let body = event
+                .body()
+                .unwrap_or("")
+                .to_string();
+            jobs.unclaimed
+                .push_back((uuid.to_string(), body));
+            None
+        }
+    }
+}
+
+/// `[JOB DONE: <cmd> in Xms]` followed by the job's output
+pub fn format_job_done(
+    body: &str,
+    command: &str,
+    elapsed: Duration,
+    color_mode: ColorMode,
+) -> String {
+    let label = format!("[JOB DONE: {} in {}ms]", command, elapsed.as_millis());
+    let label = match color_mode {
+        ColorMode::Never => label,
+        _ => label
+            .green()
+            .to_string(),
+    };
+    let body = body.trim_end();
+    if body.is_empty() {
+        label
+    } else {
+        format!("{}\n{}", label, body)
+    }
+}
+
+/// Events subscribed at startup with `--events`
+pub const MONITOR_EVENTS: [EslEventType; 4] = [
+    EslEventType::ChannelCreate,
+    EslEventType::ChannelAnswer,
+    EslEventType::ChannelHangup,
+    EslEventType::Heartbeat,
+];
+
+/// Event names FreeSWITCH knows, used to list valid types on a typo
+const EVENT_TYPE_NAMES: &[&str] = &[
+    "CUSTOM",
+    "CLONE",
+    "CHANNEL_CREATE",
+    "CHANNEL_DESTROY",
+    "CHANNEL_STATE",
+    "CHANNEL_CALLSTATE",
+    "CHANNEL_ANSWER",
+    "CHANNEL_HANGUP",
+    "CHANNEL_HANGUP_COMPLETE",
+    "CHANNEL_EXECUTE",
+    "CHANNEL_EXECUTE_COMPLETE",
+    "CHANNEL_HOLD",
+    "CHANNEL_UNHOLD",
+    "CHANNEL_BRIDGE",
+    "CHANNEL_UNBRIDGE",
+    "CHANNEL_PROGRESS",
+    "CHANNEL_PROGRESS_MEDIA",
+    "CHANNEL_OUTGOING",
+    "CHANNEL_PARK",
+    "CHANNEL_UNPARK",
+    "CHANNEL_APPLICATION",
+    "CHANNEL_ORIGINATE",
+    "CHANNEL_UUID",
+    "API",
+    "LOG",
+    "INBOUND_CHAN",
+    "OUTBOUND_CHAN",
+    "STARTUP",
+    "SHUTDOWN",
+    "PUBLISH",
+    "UNPUBLISH",
+    "TALK",
+    "NOTALK",
+    "SESSION_CRASH",
+    "MODULE_LOAD",
+    "MODULE_UNLOAD",
+    "DTMF",
+    "MESSAGE",
+    "PRESENCE_IN",
+    "NOTIFY_IN",
+    "PRESENCE_OUT",
+    "PRESENCE_PROBE",
+    "MESSAGE_WAITING",
+    "MESSAGE_QUERY",
+    "ROSTER",
+    "CODEC",
+    "BACKGROUND_JOB",
+    "DETECTED_SPEECH",
+    "DETECTED_TONE",
+    "PRIVATE_COMMAND",
+    "HEARTBEAT",
+    "TRAP",
+    "ADD_SCHEDULE",
+    "DEL_SCHEDULE",
+    "EXE_SCHEDULE",
+    "RE_SCHEDULE",
+    "RELOADXML",
+    "NOTIFY",
+    "PHONE_FEATURE",
+    "PHONE_FEATURE_SUBSCRIBE",
+    "SEND_MESSAGE",
+    "RECV_MESSAGE",
+    "REQUEST_PARAMS",
+    "CHANNEL_DATA",
+    "GENERAL",
+    "COMMAND",
+    "SESSION_HEARTBEAT",
+    "CLIENT_DISCONNECTED",
+    "SERVER_DISCONNECTED",
+    "SEND_INFO",
+    "RECV_INFO",
+    "RECV_RTCP_MESSAGE",
+    "SEND_RTCP_MESSAGE",
+    "CALL_SECURE",
+    "NAT",
+    "RECORD_START",
+    "RECORD_STOP",
+    "PLAYBACK_START",
+    "PLAYBACK_STOP",
+    "CALL_UPDATE",
+    "FAILURE",
+    "SOCKET_DATA",
+    "MEDIA_BUG_START",
+    "MEDIA_BUG_STOP",
+    "CONFERENCE_DATA_QUERY",
+    "CONFERENCE_DATA",
+    "CALL_SETUP_REQ",
+    "CALL_SETUP_RESULT",
+    "CALL_DETAIL",
+    "DEVICE_STATE",
+    "TEXT",
+    "SHUTDOWN_REQUESTED",
+];
+
+/// Parse an event type name case-insensitively. The error lists the valid
+/// names.
+pub fn parse_event_type(name: &str) -> Result<EslEventType> {
+    name.to_uppercase()
+        .parse()
+        .map_err(|_| {
+            let valid: Vec<&str> = EVENT_TYPE_NAMES
+                .iter()
+                .copied()
+                .filter(|n| {
+                    n.parse::<EslEventType>()
+                        .is_ok()
+                })
+                .collect();
+            anyhow!(
+                "Unknown event type: {}\nValid types: {}",
+                name,
+                valid.join(", ")
+            )
+        })
+}
+
+/// Event types the current connection is subscribed to
+#[derive(Debug, Default)]
+pub struct EventSubscriptionTracker {
+    subscribed: Vec<EslEventType>,
+}
+
+impl EventSubscriptionTracker {
+    /// Tracker matching what `setup_subscriptions` subscribes at startup
+    pub fn for_startup(events: bool) -> Self {
+        let subscribed = if events {
+            MONITOR_EVENTS.to_vec()
+        } else {
+            vec![EslEventType::Heartbeat]
+        };
+        Self { subscribed }
+    }
+
+    pub fn contains(&self, event_type: EslEventType) -> bool {
+        self.subscribed
+            .contains(&event_type)
+    }
+
+    /// Returns false if already subscribed
+    pub fn insert(&mut self, event_type: EslEventType) -> bool {
+        if self.contains(event_type) {
+            return false;
+        }
+        self.subscribed
+            .push(event_type);
+        true
+    }
+
+    /// Returns false if not subscribed
+    pub fn remove(&mut self, event_type: EslEventType) -> bool {
+        let before = self
+            .subscribed
+            .len();
+        self.subscribed
+            .retain(|t| *t != event_type);
+        self.subscribed
+            .len()
+            != before
+    }
+
+    /// Subscribed type names, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .subscribed
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Differences from the startup subscriptions, to replay after a
+    /// reconnect: `(to_subscribe, to_unsubscribe)`
+    pub fn changes_since_startup(&self, events: bool) -> (Vec<EslEventType>, Vec<EslEventType>) {
+        let startup = Self::for_startup(events);
+        let added = self
+            .subscribed
+            .iter()
+            .copied()
+            .filter(|t| !startup.contains(*t))
+            .collect();
+        let removed = startup
+            .subscribed
+            .iter()
+            .copied()
+            .filter(|t| !self.contains(*t))
+            .collect();
+        (added, removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_event_type_ignores_case() {
+        assert_eq!(
+            parse_event_type("channel_answer").unwrap(),
+            EslEventType::ChannelAnswer
+        );
+        assert_eq!(
+            parse_event_type("Heartbeat").unwrap(),
+            EslEventType::Heartbeat
+        );
+    }
+
+    #[test]
+    fn parse_event_type_lists_valid_types() {
+        let err = parse_event_type("CHANEL_ANSWER")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Unknown event type: CHANEL_ANSWER"));
+        assert!(err.contains("CHANNEL_ANSWER"));
+    }
+
+    #[test]
+    fn job_uuid_parsed_from_reply() {
+        assert_eq!(
+            job_uuid_from_reply("+OK Job-UUID: 7f4de4bc-17d7-11dd-b7a0-db4edd065621"),
+            Some("7f4de4bc-17d7-11dd-b7a0-db4edd065621")
+        );
+        assert_eq!(job_uuid_from_reply("+OK Job-UUID: "), None);
+        assert_eq!(job_uuid_from_reply("-ERR no reply"), None);
+    }
+
+    fn job_event(uuid: &str) -> EslEvent {
+        let mut event = EslEvent::new();
+        event.set_header("Event-Name", "BACKGROUND_JOB");
+        event.set_header("Job-UUID", uuid);
+        event
+    }
+
+    #[test]
+    fn job_event_before_registration_is_kept() {
+        let jobs = PendingJobs::default();
+        assert_eq!(take_finished_job(&job_event("early"), &jobs), None);
+        assert_eq!(
+            register_job(&jobs, "early", "status", Instant::now()).as_deref(),
+            Some("")
+        );
+        assert!(jobs
+            .lock()
+            .unwrap()
+            .pending()
+            .is_empty());
+
+        assert_eq!(register_job(&jobs, "late", "status", Instant::now()), None);
+        let (command, _) = take_finished_job(&job_event("late"), &jobs).unwrap();
+        assert_eq!(command, "status");
+    }
+
+    #[test]
+    fn unclaimed_job_events_are_bounded() {
+        let jobs = PendingJobs::default();
+        for i in 0..=MAX_UNCLAIMED_JOB_EVENTS {
+            take_finished_job(&job_event(&format!("job-{}", i)), &jobs);
+        }
+        assert_eq!(register_job(&jobs, "job-0", "status", Instant::now()), None);
+        assert!(register_job(&jobs, "job-1", "status", Instant::now()).is_some());
+    }
+
+    #[test]
+    fn tracker_insert_remove() {
+        let mut tracker = EventSubscriptionTracker::for_startup(false);
+        assert_eq!(tracker.names(), vec!["HEARTBEAT"]);
+        assert!(tracker.insert(EslEventType::Dtmf));
+        assert!(!tracker.insert(EslEventType::Dtmf));
+        assert!(tracker.remove(EslEventType::Dtmf));
+        assert!(!tracker.remove(EslEventType::Dtmf));
+    }
+
+    #[test]
+    fn tracker_changes_since_startup() {
+        let mut tracker = EventSubscriptionTracker::for_startup(true);
+        tracker.remove(EslEventType::ChannelAnswer);
+        tracker.insert(EslEventType::Dtmf);
+        let (added, removed) = tracker.changes_since_startup(true);
+        assert_eq!(added, vec![EslEventType::Dtmf]);
+        assert_eq!(removed, vec![EslEventType::ChannelAnswer]);
+    }
+}
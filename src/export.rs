@@ -0,0 +1,252 @@
+//! Export channel and call snapshots to files (`/export-channels`, `/export-calls`,
+//! `/export-db`)
+
+use crate::channel_info::ChannelInfo;
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// `/export-db` target when no file is given
+pub const DEFAULT_CHANNEL_DB: &str = ".fs_cli_channels.db";
+
+/// Columns written by `/export-channels`
+pub const CHANNEL_COLUMNS: [&str; 6] = [
+    "uuid",
+    "name",
+    "state",
+    "cid_name",
+    "cid_num",
+    "created_epoch",
+];
+
+/// Columns written by `/export-calls`, taken from `show calls as json`
+pub const CALL_COLUMNS: [&str; 9] = [
+    "uuid",
+    "direction",
+    "name",
+    "callstate",
+    "cid_name",
+    "cid_num",
+    "dest",
+    "b_uuid",
+    "created_epoch",
+];
+
+/// Write `rows` under `headers` as CSV. The header row is always written.
+/// Returns the number of data rows.
+pub fn write_csv<W: std::io::Write>(
+    writer: W,
+    headers: &[&str],
+    rows: &[Vec<String>],
+) -> Result<usize> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(headers)?;
+    for row in rows {
+        csv.write_record(row)?;
+    }
+    csv.flush()?;
+    Ok(rows.len())
+}
+
+/// Create (or truncate) `path` and write `rows` to it as CSV
+pub fn write_csv_file(path: &Path, headers: &[&str], rows: &[Vec<String>]) -> Result<usize> {
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Cannot open {} for writing", path.display()))?;
+    write_csv(file, headers, rows).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Flatten channels into rows matching [`CHANNEL_COLUMNS`]
+pub fn channel_rows(channels: &[ChannelInfo]) -> Vec<Vec<String>> {
+    channels
+        .iter()
+        .map(|ch| {
+            vec![
+                ch.uuid
+                    .clone(),
+                ch.name
+                    .clone(),
+                ch.state
+                    .clone(),
+                ch.cid_name
+                    .clone(),
+                ch.cid_num
+                    .clone(),
+                ch.created_epoch
+                    .clone(),
+            ]
+        })
+        .collect()
+}
+
+/// Pick [`CALL_COLUMNS`] out of `show calls as json` rows
+pub fn call_rows(calls: &[HashMap<String, String>]) -> Vec<Vec<String>> {
+    calls
+        .iter()
+        .map(|call| {
+            CALL_COLUMNS
+                .iter()
+                .map(|col| {
+                    call.get(*col)
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `~/.fs_cli_channels.db`, or the current directory without a home
+pub fn default_channel_db_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(DEFAULT_CHANNEL_DB)
+}
+
+/// Upsert `channels` into the SQLite database at `path`, creating it and the
+/// `channels` table as needed. Returns the number of channels written.
+pub fn write_channels_db(path: &Path, channels: &[ChannelInfo], snapshot_ts: i64) -> Result<usize> {
+    let mut conn =
+        Connection::open(path).with_context(|| format!("Cannot open {}", path.display()))?;
+    write_channels(&mut conn, channels, snapshot_ts)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn write_channels(
+    conn: &mut Connection,
+    channels: &[ChannelInfo],
+    snapshot_ts: i64,
+) -> Result<usize> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS channels (
+            uuid TEXT PRIMARY KEY,
+            name TEXT,
+            state TEXT,
+            cid_name TEXT,
+            cid_num TEXT,
+            created_epoch INTEGER,
+            snapshot_ts INTEGER
+        )",
+    )?;
+    let tx = conn.transaction()?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO channels
+                (uuid, name, state, cid_name, cid_num, created_epoch, snapshot_ts)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(uuid) DO UPDATE SET
+                name = excluded.name,
+                state = excluded.state,
+                cid_name = excluded.cid_name,
+                cid_num = excluded.cid_num,
+                created_epoch = excluded.created_epoch,
+                snapshot_ts = excluded.snapshot_ts",
+        )?;
+        for ch in channels {
+            insert.execute(rusqlite::params![
+                ch.uuid,
+                ch.name,
+                ch.state,
+                ch.cid_name,
+                ch.cid_num,
+                ch.created_epoch
+                    .parse::<i64>()
+                    .ok(),
+                snapshot_ts,
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(channels.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_csv_headers_and_rows() {
+        let mut out = Vec::new();
+        let rows = vec![vec![
+            "0f2a".to_string(),
+            "sofia/internal/1000@example.com".to_string(),
+            "CS_EXECUTE".to_string(),
+            "Smith, Alice".to_string(),
+            "1000".to_string(),
+            "1700000000".to_string(),
+        ]];
+        let written = write_csv(&mut out, &CHANNEL_COLUMNS, &rows).unwrap();
+        assert_eq!(written, 1);
+        let text = String::from_utf8(out).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(
+            lines.next(),
+            Some("uuid,name,state,cid_name,cid_num,created_epoch")
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                "0f2a,sofia/internal/1000@example.com,CS_EXECUTE,\"Smith, Alice\",1000,1700000000"
+            )
+        );
+    }
+
+    #[test]
+    fn write_csv_empty_still_has_header() {
+        let mut out = Vec::new();
+        assert_eq!(write_csv(&mut out, &CALL_COLUMNS, &[]).unwrap(), 0);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "uuid,direction,name,callstate,cid_name,cid_num,dest,b_uuid,created_epoch\n"
+        );
+    }
+
+    #[test]
+    fn call_rows_missing_columns_are_empty() {
+        let mut call = HashMap::new();
+        call.insert("uuid".to_string(), "0f2a".to_string());
+        let rows = call_rows(&[call]);
+        assert_eq!(rows[0][0], "0f2a");
+        assert!(rows[0][1..]
+            .iter()
+            .all(String::is_empty));
+    }
+
+    fn channel(uuid: &str, state: &str) -> ChannelInfo {
+        ChannelInfo {
+            uuid: uuid.to_string(),
+            created: String::new(),
+            created_epoch: "1700000000".to_string(),
+            name: "sofia/internal/1000@example.com".to_string(),
+            state: state.to_string(),
+            cid_name: "Alice".to_string(),
+            cid_num: "1000".to_string(),
+        }
+    }
+
+    #[test]
+    fn write_channels_upserts_by_uuid() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let first = [channel("0f2a", "CS_ROUTING"), channel("1e3b", "CS_EXECUTE")];
+        assert_eq!(write_channels(&mut conn, &first, 100).unwrap(), 2);
+        assert_eq!(
+            write_channels(&mut conn, &[channel("0f2a", "CS_EXECUTE")], 200).unwrap(),
+            1
+        );
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM channels", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+        let (state, ts, created): (String, i64, i64) = conn
+            .query_row(
+                "SELECT state, snapshot_ts, created_epoch FROM channels WHERE uuid = '0f2a'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(state, "CS_EXECUTE");
+        assert_eq!(ts, 200);
+        assert_eq!(created, 1700000000);
+    }
+}
@@ -1,71 +1,641 @@
 //! Log display functionality for fs_cli-rs
 
-use crate::commands::ColorMode;
+use crate::commands::{ColorMode, LogLevel};
+use crate::config::AppConfig;
 use crate::printer::Printer;
+use anyhow::{anyhow, bail, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
 use colored::*;
 use freeswitch_esl_tokio::{EslEvent, EventHeader};
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 use tracing::debug;
 
+/// `log_timestamp_format` when none is configured
+pub const DEFAULT_LOG_TIMESTAMP_FORMAT: &str = "%H:%M:%S%.3f";
+
+/// Local receive time prefixed to log lines (`log_timestamps`)
+#[derive(Debug, Clone)]
+pub struct LogTimestamp {
+    format: String,
+    /// `None` uses the local timezone
+    timezone: Option<Tz>,
+}
+
+impl LogTimestamp {
+    /// Validate a chrono format string and optional IANA timezone name
+    pub fn new(format: Option<&str>, timezone: Option<&str>) -> Result<Self> {
+        let format = format.unwrap_or(DEFAULT_LOG_TIMESTAMP_FORMAT);
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            bail!("Invalid log_timestamp_format '{}'", format);
+        }
+        let timezone = timezone
+            .map(|name| {
+                name.parse::<Tz>()
+                    .map_err(|e| anyhow!("Invalid log_timezone '{}': {}", name, e))
+            })
+            .transpose()?;
+        Ok(Self {
+            format: format.to_string(),
+            timezone,
+        })
+    }
+
+    /// Timestamp settings from the configuration, `None` when disabled
+    pub fn from_config(config: &AppConfig) -> Result<Option<Self>> {
+        if !config.log_timestamps {
+            return Ok(None);
+        }
+        Self::new(
+            config
+                .log_timestamp_format
+                .as_deref(),
+            config
+                .log_timezone
+                .as_deref(),
+        )
+        .map(Some)
+    }
+
+    pub fn now(&self) -> String {
+        self.render(Utc::now())
+    }
+
+    fn render(&self, at: DateTime<Utc>) -> String {
+        match self.timezone {
+            Some(tz) => at
+                .with_timezone(&tz)
+                .format(&self.format)
+                .to_string(),
+            None => at
+                .with_timezone(&Local)
+                .format(&self.format)
+                .to_string(),
+        }
+    }
+}
+
+/// Whether `event` is a log line: `log/data`, or an event-framed log
+/// (`text/event-json` / `text/event-plain` carrying a `Log-Level` header) as
+/// sent by newer FreeSWITCH versions
 pub fn is_log_event(event: &EslEvent) -> bool {
+    let Some(content_type) = event.header_str("Content-Type") else {
+        return false;
+    };
+    if content_type.eq_ignore_ascii_case("log/data") {
+        return true;
+    }
+    (content_type.eq_ignore_ascii_case("text/event-json")
+        || content_type.eq_ignore_ascii_case("text/event-plain"))
+        && event
+            .header(EventHeader::LogLevel)
+            .is_some()
+}
+
+/// Log text of a log event: the body, or the `Content` header of
+/// event-framed logs
+pub fn log_event_body(event: &EslEvent) -> &str {
     event
-        .header_str("Content-Type")
-        .is_some_and(|ct| ct.eq_ignore_ascii_case("log/data"))
+        .body()
+        .filter(|body| {
+            !body
+                .trim()
+                .is_empty()
+        })
+        .or_else(|| event.header_str("Content"))
+        .unwrap_or("")
+}
+
+/// Log line settings shared by every event consumer
+#[derive(Debug, Clone, Default)]
+pub struct LogSettings {
+    pub timestamp: Option<LogTimestamp>,
+    /// Recent lines checked for repeats; `None` disables `log_dedupe`
+    pub dedupe_window: Option<usize>,
+    /// `(lines per second, burst size)`; `None` disables `log_rate_limit`
+    pub rate_limit: Option<(u32, u32)>,
+    /// Longest `(file.c:func)` shown; `None` disables `show_log_source`
+    pub source_max_len: Option<usize>,
+}
+
+impl LogSettings {
+    pub fn from_config(config: &AppConfig) -> Result<Self> {
+        Ok(Self {
+            timestamp: LogTimestamp::from_config(config)?,
+            dedupe_window: config
+                .log_dedupe
+                .then_some(config.log_dedupe_window as usize),
+            rate_limit: config
+                .log_rate_limit
+                .map(|limit| (limit, config.log_burst_size)),
+            source_max_len: config
+                .show_log_source
+                .then_some(config.log_source_max_len),
+        })
+    }
+}
+
+/// A run of repeats is summarized once no repeat arrives for this long
+const REPEAT_SUMMARY_DELAY: Duration = Duration::from_secs(2);
+
+/// Repeats [`LogDeduper`] counted without showing them
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatRun {
+    /// Level of the latest repeat, which colors the summary line
+    pub level: u32,
+    pub count: u32,
+    last_seen: Instant,
+}
+
+/// What to do with a log line, from [`LogDeduper::check`]
+#[derive(Debug, PartialEq)]
+pub enum Dedupe {
+    /// Hide it; it was counted in the current run
+    Repeat,
+    /// Show it, after the summary of the run it ended, if any
+    New(Option<RepeatRun>),
+}
+
+/// Collapses log lines repeating one of the last `window` lines into a
+/// single `[repeated N times]` line, shown when the run ends
+#[derive(Debug)]
+pub struct LogDeduper {
+    window: usize,
+    recent: VecDeque<String>,
+    run: Option<RepeatRun>,
+}
+
+impl LogDeduper {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            recent: VecDeque::with_capacity(window),
+            run: None,
+        }
+    }
+
+    /// Record a log body seen at `now`
+    pub fn check(&mut self, body: &str, level: u32, now: Instant) -> Dedupe {
+        let key = dedupe_key(body);
+        if self
+            .recent
+            .iter()
+            .any(|line| line == key)
+        {
+            let run = self
+                .run
+                .get_or_insert_with(|| RepeatRun {
+                    level,
+                    count: 0,
+                    last_seen: now,
+                });
+            run.level = level;
+            run.count += 1;
+            run.last_seen = now;
+            return Dedupe::Repeat;
+        }
+        if self.window > 0 {
+            if self
+                .recent
+                .len()
+                == self.window
+            {
+                self.recent
+                    .pop_front();
+            }
+            self.recent
+                .push_back(key.to_string());
+        }
+        Dedupe::New(
+            self.run
+                .take(),
+        )
+    }
+
+    /// End the current run if no repeat arrived for a while
+    pub fn expired(&mut self, now: Instant) -> Option<RepeatRun> {
+        let quiet = self
+            .run
+            .as_ref()?
+            .last_seen;
+        if now.saturating_duration_since(quiet) < REPEAT_SUMMARY_DELAY {
+            return None;
+        }
+        self.run
+            .take()
+    }
+
+    /// End the current run, when the log stream stops
+    pub fn finish(&mut self) -> Option<RepeatRun> {
+        self.run
+            .take()
+    }
+}
+
+/// Lines held back by [`LogRateLimiter`] beyond this are dropped, oldest first
+const MAX_RATE_LIMITED_LINES: usize = 10_000;
+
+/// Holds back log lines arriving faster than `limit` per second so a debug
+/// flood can't swamp the terminal.
+///
+/// Up to `burst` lines above the limit are shown before throttling starts;
+/// the allowance refills after a second under the limit. While throttled,
+/// lines are buffered and a `[Log rate limited: N messages suppressed]`
+/// notice is shown each second; the buffer is flushed once the rate drops
+/// back below the limit.
+#[derive(Debug)]
+pub struct LogRateLimiter {
+    limit: u32,
+    burst: u32,
+    burst_left: u32,
+    log_line_count: u64,
+    rate_window_start: Instant,
+    buffered: VecDeque<String>,
+    suppressed: u64,
+}
+
+impl LogRateLimiter {
+    pub fn new(limit: u32, burst: u32, now: Instant) -> Self {
+        Self {
+            limit,
+            burst,
+            burst_left: burst,
+            log_line_count: 0,
+            rate_window_start: now,
+            buffered: VecDeque::new(),
+            suppressed: 0,
+        }
+    }
+
+    /// Offer a formatted line. Returns the lines to print now, which may
+    /// include a rate notice or earlier buffered lines.
+    pub fn push(&mut self, line: String, now: Instant) -> Vec<String> {
+        let mut out = self.tick(now);
+        self.log_line_count += 1;
+        if self
+            .buffered
+            .is_empty()
+        {
+            if self.log_line_count <= u64::from(self.limit) {
+                out.push(line);
+                return out;
+            }
+            if self.burst_left > 0 {
+                self.burst_left -= 1;
+                out.push(line);
+                return out;
+            }
+        }
+        if self
+            .buffered
+            .len()
+            == MAX_RATE_LIMITED_LINES
+        {
+            self.buffered
+                .pop_front();
+        }
+        self.buffered
+            .push_back(line);
+        self.suppressed += 1;
+        out
+    }
+
+    /// Close the rate window once a second has passed. Returns the notice
+    /// while still over the limit, or the buffered lines once under it.
+    pub fn tick(&mut self, now: Instant) -> Vec<String> {
+        if now.duration_since(self.rate_window_start) < Duration::from_secs(1) {
+            return Vec::new();
+        }
+        let under_limit = self.log_line_count <= u64::from(self.limit);
+        self.log_line_count = 0;
+        self.rate_window_start = now;
+        if under_limit {
+            self.burst_left = self.burst;
+        }
+
+        if self
+            .buffered
+            .is_empty()
+        {
+            Vec::new()
+        } else if under_limit {
+            self.suppressed = 0;
+            self.buffered
+                .drain(..)
+                .collect()
+        } else if self.suppressed > 0 {
+            let notice = format!(
+                "[Log rate limited: {} messages suppressed]",
+                self.suppressed
+            );
+            self.suppressed = 0;
+            vec![notice]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Log body without FreeSWITCH's `YYYY-MM-DD HH:MM:SS.ffffff` prefix, which
+/// differs on every line
+fn dedupe_key(body: &str) -> &str {
+    let line = body.trim();
+    let mut parts = line.splitn(3, ' ');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(date), Some(time), Some(rest))
+            if time.contains(':') && NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() =>
+        {
+            rest
+        }
+        _ => line,
+    }
+}
+
+/// `file.c:func` from an event's `Log-File` and `Log-Func` headers, without
+/// the directory and cut to `max_len` characters. `None` without `Log-File`.
+pub fn log_source(event: &EslEvent, max_len: usize) -> Option<String> {
+    let file = event
+        .header_str("Log-File")
+        .filter(|file| !file.is_empty())?;
+    let file = file
+        .rsplit('/')
+        .next()
+        .unwrap_or(file);
+    let source = match event
+        .header_str("Log-Func")
+        .filter(|func| !func.is_empty())
+    {
+        Some(func) => format!("{}:{}", file, func),
+        None => file.to_string(),
+    };
+    if source
+        .chars()
+        .count()
+        <= max_len
+    {
+        return Some(source);
+    }
+    let mut truncated: String = source
+        .chars()
+        .take(max_len.saturating_sub(1))
+        .collect();
+    truncated.push('…');
+    Some(truncated)
 }
 
 /// Display a log event with appropriate formatting and colors.
-pub fn display_log_event(event: &EslEvent, color_mode: ColorMode, printer: &Printer) {
-    let log_level = event
+pub fn display_log_event(
+    event: &EslEvent,
+    color_mode: ColorMode,
+    settings: &LogSettings,
+    deduper: Option<&mut LogDeduper>,
+    rate_limiter: Option<&mut LogRateLimiter>,
+    printer: &Printer,
+) {
+    let level = event
         .header(EventHeader::LogLevel)
         .and_then(|raw| {
             raw.parse::<u32>()
                 .ok()
                 .or_else(|| {
-                    debug!("unparseable Log-Level {:?}, defaulting to 7", raw);
+                    debug!("unparseable Log-Level {:?}, defaulting to DEBUG", raw);
                     None
                 })
         })
-        .unwrap_or(7);
+        .unwrap_or(LogLevel::Debug as u32);
 
-    let message = event
-        .body()
-        .unwrap_or("");
-    if message
+    let body = log_event_body(event);
+    if body
         .trim()
         .is_empty()
     {
         return;
     }
+    let timestamp = settings
+        .timestamp
+        .as_ref()
+        .map(LogTimestamp::now);
+    let mut rate_limiter = rate_limiter;
+    match deduper.map(|d| d.check(body, level, Instant::now())) {
+        Some(Dedupe::Repeat) => return,
+        Some(Dedupe::New(Some(run))) => {
+            let summary = format_repeat_run(&run, color_mode, timestamp.as_deref());
+            print_log_line(summary, rate_limiter.as_deref_mut(), printer);
+        }
+        _ => {}
+    }
+    let source = settings
+        .source_max_len
+        .and_then(|max_len| log_source(event, max_len));
+
+    let line = format_log_line(
+        body,
+        level,
+        color_mode,
+        timestamp.as_deref(),
+        source.as_deref(),
+        0,
+    );
+    if line.is_empty() {
+        return;
+    }
+    print_log_line(line, rate_limiter, printer);
+}
+
+/// Show the summary of a run of repeats that [`LogDeduper`] ended, on a
+/// quiet spell or when the stream stops
+pub fn display_repeat_run(
+    run: &RepeatRun,
+    color_mode: ColorMode,
+    settings: &LogSettings,
+    rate_limiter: Option<&mut LogRateLimiter>,
+    printer: &Printer,
+) {
+    let timestamp = settings
+        .timestamp
+        .as_ref()
+        .map(LogTimestamp::now);
+    let summary = format_repeat_run(run, color_mode, timestamp.as_deref());
+    print_log_line(summary, rate_limiter, printer);
+}
+
+fn format_repeat_run(run: &RepeatRun, color_mode: ColorMode, timestamp: Option<&str>) -> String {
+    format_log_line("", run.level, color_mode, timestamp, None, run.count)
+}
+
+fn print_log_line(line: String, rate_limiter: Option<&mut LogRateLimiter>, printer: &Printer) {
+    match rate_limiter {
+        Some(limiter) => {
+            for line in limiter.push(line, Instant::now()) {
+                printer.print(line);
+            }
+        }
+        None => printer.print(line),
+    }
+}
+
+/// Format a log body for display. Returns an empty string for blank bodies.
+///
+/// The level only picks the color; FreeSWITCH's own `[LEVEL]` tag is
+/// recolored, never added. Unknown levels are colored as DEBUG. `timestamp` is prefixed uncolored,
+/// then `source` (from [`log_source`]) dimmed, as `(file.c:func)`.
+/// A non-zero `repeated` count (a [`RepeatRun`]) replaces the body with
+/// `[repeated N times]`.
+pub fn format_log_line(
+    body: &str,
+    level: u32,
+    color_mode: ColorMode,
+    timestamp: Option<&str>,
+    source: Option<&str>,
+    repeated: u32,
+) -> String {
+    let message = if repeated > 0 {
+        format!(
+            "[repeated {} {}]",
+            repeated,
+            if repeated == 1 { "time" } else { "times" }
+        )
+    } else {
+        body.trim()
+            .to_string()
+    };
+    if message.is_empty() {
+        return String::new();
+    }
+    let log_level = LogLevel::from_number(level).unwrap_or(LogLevel::Debug);
+
+    let formatted = match color_mode {
+        ColorMode::Never => message,
+        ColorMode::Tag => format_colored_log_tag_only(&message, log_level, color_mode),
+        // Auto is resolved at startup; treat a stray one like the default
+        ColorMode::Line | ColorMode::Bright | ColorMode::Auto => {
+            format_colored_log_full_line(&message, log_level, color_mode)
+        }
+    };
+    let formatted = match source {
+        Some(source) if color_mode == ColorMode::Never => format!("({}) {}", source, formatted),
+        Some(source) => format!("{} {}", format!("({})", source).dimmed(), formatted),
+        None => formatted,
+    };
+    match timestamp {
+        Some(ts) => format!("{} {}", ts, formatted),
+        None => formatted,
+    }
+}
+
+/// `log_colors` overrides, parsed once at startup
+static LOG_COLORS: OnceLock<HashMap<String, Color>> = OnceLock::new();
+
+/// Apply the `log_colors` setting. Unknown color names are skipped, keeping
+/// the built-in color for that level.
+pub fn set_log_colors(colors: &HashMap<String, String>) {
+    let parsed = colors
+        .iter()
+        .filter_map(|(level, name)| match parse_color(name) {
+            Some(color) => Some((level.to_lowercase(), color)),
+            None => {
+                debug!(
+                    "log_colors: unknown color {:?} for {}, using the default",
+                    name, level
+                );
+                None
+            }
+        })
+        .collect();
+    let _ = LOG_COLORS.set(parsed);
+}
 
-    let formatted_message = match color_mode {
-        ColorMode::Never => message
-            .trim()
-            .to_string(),
-        ColorMode::Tag => format_colored_log_tag_only(message.trim(), log_level),
-        ColorMode::Line => format_colored_log_full_line(message.trim(), log_level),
+/// Color name as used in `log_colors`, e.g. `red` or `bright_blue`
+pub fn parse_color(s: &str) -> Option<Color> {
+    let color = match s
+        .trim()
+        .to_lowercase()
+        .replace([' ', '-'], "_")
+        .as_str()
+    {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" | "purple" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        "bright_black" | "gray" | "grey" => Color::BrightBlack,
+        "bright_red" => Color::BrightRed,
+        "bright_green" => Color::BrightGreen,
+        "bright_yellow" => Color::BrightYellow,
+        "bright_blue" => Color::BrightBlue,
+        "bright_magenta" | "bright_purple" => Color::BrightMagenta,
+        "bright_cyan" => Color::BrightCyan,
+        "bright_white" => Color::BrightWhite,
+        _ => return None,
     };
+    Some(color)
+}
 
-    printer.print(formatted_message);
+/// Override for `log_level`, looked up by level name (`warn`, `debug3`)
+/// and then by tag (`warning`, `debug`), so `debug` covers every debug level
+fn level_color(log_level: LogLevel, colors: &HashMap<String, Color>) -> Option<Color> {
+    colors
+        .get(log_level.as_str())
+        .or_else(|| {
+            colors.get(
+                &log_level
+                    .tag()
+                    .to_lowercase(),
+            )
+        })
+        .copied()
 }
 
-fn colorize_by_level(text: &str, log_level: u32) -> ColoredString {
+fn colorize_by_level(text: &str, log_level: LogLevel, color_mode: ColorMode) -> ColoredString {
+    if let Some(color) = LOG_COLORS
+        .get()
+        .and_then(|colors| level_color(log_level, colors))
+    {
+        return text.color(color);
+    }
+    if color_mode == ColorMode::Bright {
+        return match log_level {
+            LogLevel::Console => text
+                .bright_white()
+                .bold(),
+            LogLevel::Alert | LogLevel::Crit => text
+                .bright_red()
+                .bold(),
+            LogLevel::Err => text.bright_red(),
+            LogLevel::Warning => text.bright_yellow(),
+            LogLevel::Notice => text.bright_cyan(),
+            LogLevel::Info => text.bright_green(),
+            _ => text.bright_blue(), // DEBUG and higher; dimmed is unreadable here
+        };
+    }
     match log_level {
-        0 => text
+        LogLevel::Console => text
             .white()
-            .bold(), // CONSOLE
-        1 | 2 => text
+            .bold(),
+        LogLevel::Alert | LogLevel::Crit => text
             .red()
-            .bold(), // ALERT / CRIT
-        3 => text.red(),    // ERR
-        4 => text.yellow(), // WARNING
-        5 => text.cyan(),   // NOTICE
-        6 => text.green(),  // INFO - green like real fs_cli
+            .bold(),
+        LogLevel::Err => text.red(),
+        LogLevel::Warning => text.yellow(),
+        LogLevel::Notice => text.cyan(),
+        LogLevel::Info => text.green(), // green like real fs_cli
         _ => text
             .yellow()
             .dimmed(), // DEBUG and higher
     }
 }
 
-fn format_colored_log_tag_only(message: &str, log_level: u32) -> String {
+fn format_colored_log_tag_only(
+    message: &str,
+    log_level: LogLevel,
+    color_mode: ColorMode,
+) -> String {
     if let Some(level_start) = message.find('[') {
         if let Some(level_end) = message[level_start..].find(']') {
             let level_end = level_start + level_end + 1;
@@ -73,7 +643,7 @@ fn format_colored_log_tag_only(message: &str, log_level: u32) -> String {
             let level_tag = &message[level_start..level_end];
             let after = &message[level_end..];
 
-            let colored_level = colorize_by_level(level_tag, log_level);
+            let colored_level = colorize_by_level(level_tag, log_level, color_mode);
 
             return format!("{}{}{}", before, colored_level, after);
         }
@@ -82,8 +652,12 @@ fn format_colored_log_tag_only(message: &str, log_level: u32) -> String {
     message.to_string()
 }
 
-fn format_colored_log_full_line(message: &str, log_level: u32) -> String {
-    colorize_by_level(message, log_level).to_string()
+fn format_colored_log_full_line(
+    message: &str,
+    log_level: LogLevel,
+    color_mode: ColorMode,
+) -> String {
+    colorize_by_level(message, log_level, color_mode).to_string()
 }
 
 #[cfg(test)]
@@ -99,6 +673,39 @@ mod tests {
         assert!(is_log_event(&event));
     }
 
+    #[test]
+    fn is_log_event_with_event_json_log_level() {
+        let mut event = EslEvent::new();
+        event.set_header("Content-Type", "text/event-json");
+        event.set_header("Log-Level", "4");
+        event.set_header("Content", "switch_core.c:42 warning");
+        assert!(is_log_event(&event));
+        assert_eq!(log_event_body(&event), "switch_core.c:42 warning");
+    }
+
+    #[test]
+    fn is_log_event_with_event_plain_log_level() {
+        let mut event = EslEvent::new();
+        event.set_header("Content-Type", "TEXT/EVENT-PLAIN");
+        event.set_header("Log-Level", "7");
+        assert!(is_log_event(&event));
+    }
+
+    #[test]
+    fn is_log_event_rejects_event_json_without_log_level() {
+        let mut event = EslEvent::new();
+        event.set_header("Content-Type", "text/event-json");
+        event.set_header("Event-Name", "HEARTBEAT");
+        assert!(!is_log_event(&event));
+    }
+
+    #[test]
+    fn log_event_body_empty_without_content() {
+        let mut event = EslEvent::new();
+        event.set_header("Content-Type", "log/data");
+        assert_eq!(log_event_body(&event), "");
+    }
+
     #[test]
     fn is_log_event_rejects_normal_event() {
         let mut event = EslEvent::new();
@@ -111,4 +718,326 @@ mod tests {
         let event = EslEvent::new();
         assert!(!is_log_event(&event));
     }
+
+    #[test]
+    fn format_log_line_adds_no_level_tag() {
+        for level in 0..=17 {
+            assert_eq!(
+                format_log_line("switch.c:1 hello", level, ColorMode::Never, None, None, 0),
+                "switch.c:1 hello",
+                "level {}",
+                level
+            );
+        }
+    }
+
+    #[test]
+    fn format_log_line_unknown_level_is_debug() {
+        colored::control::set_override(true);
+        assert_eq!(
+            format_log_line("x", 64, ColorMode::Line, None, None, 0),
+            format_log_line("x", 7, ColorMode::Line, None, None, 0)
+        );
+    }
+
+    #[test]
+    fn format_log_line_keeps_existing_tag() {
+        let body = "2024-01-01 10:00:00.000000 [WARNING] sofia.c:1 Lost connection\n";
+        assert_eq!(
+            format_log_line(body, 4, ColorMode::Never, None, None, 0),
+            "2024-01-01 10:00:00.000000 [WARNING] sofia.c:1 Lost connection"
+        );
+    }
+
+    #[test]
+    fn format_log_line_empty_body() {
+        for mode in [ColorMode::Never, ColorMode::Tag, ColorMode::Line] {
+            assert_eq!(format_log_line("", 6, mode, None, None, 0), "");
+            assert_eq!(format_log_line("  \n", 6, mode, None, None, 0), "");
+        }
+    }
+
+    #[test]
+    fn format_log_line_multi_line_body() {
+        let line = format_log_line("[INFO] first\nsecond\n", 6, ColorMode::Never, None, None, 0);
+        assert_eq!(line, "[INFO] first\nsecond");
+    }
+
+    #[test]
+    fn format_log_line_prefixes_timestamp() {
+        assert_eq!(
+            format_log_line(
+                "[INFO] up",
+                6,
+                ColorMode::Never,
+                Some("10:00:00.000"),
+                None,
+                0
+            ),
+            "10:00:00.000 [INFO] up"
+        );
+        assert_eq!(
+            format_log_line("", 6, ColorMode::Never, Some("10:00:00.000"), None, 0),
+            ""
+        );
+    }
+
+    #[test]
+    fn log_source_strips_directory_and_truncates() {
+        let mut event = EslEvent::new();
+        event.set_header("Content-Type", "log/data");
+        assert_eq!(log_source(&event, 30), None);
+
+        event.set_header("Log-File", "src/mod/endpoints/mod_sofia/sofia.c");
+        assert_eq!(log_source(&event, 30).as_deref(), Some("sofia.c"));
+        event.set_header("Log-Func", "sofia_handle_sip_i_invite");
+        assert_eq!(
+            log_source(&event, 40).as_deref(),
+            Some("sofia.c:sofia_handle_sip_i_invite")
+        );
+        assert_eq!(log_source(&event, 12).as_deref(), Some("sofia.c:sof…"));
+    }
+
+    #[test]
+    fn format_log_line_prefixes_source() {
+        assert_eq!(
+            format_log_line(
+                "[INFO] up",
+                6,
+                ColorMode::Never,
+                Some("10:00:00.000"),
+                Some("switch.c:main"),
+                0
+            ),
+            "10:00:00.000 (switch.c:main) [INFO] up"
+        );
+
+        colored::control::set_override(true);
+        let line = format_log_line(
+            "[INFO] up",
+            6,
+            ColorMode::Line,
+            None,
+            Some("switch.c:main"),
+            0,
+        );
+        assert!(
+            line.starts_with("\x1b[2m(switch.c:main)\x1b[0m "),
+            "{:?}",
+            line
+        );
+    }
+
+    #[test]
+    fn format_log_line_repeat_notice() {
+        assert_eq!(
+            format_log_line("[ERR] x.c:1 boom", 3, ColorMode::Never, None, None, 4),
+            "[repeated 4 times]"
+        );
+        assert_eq!(
+            format_log_line("", 3, ColorMode::Never, None, None, 1),
+            "[repeated 1 time]"
+        );
+    }
+
+    fn repeats(dedupe: Dedupe) -> Option<u32> {
+        match dedupe {
+            Dedupe::New(run) => run.map(|run| run.count),
+            Dedupe::Repeat => panic!("expected a new line"),
+        }
+    }
+
+    #[test]
+    fn deduper_summarizes_run_when_a_new_line_arrives() {
+        let now = Instant::now();
+        let mut dedupe = LogDeduper::new(2);
+        assert_eq!(repeats(dedupe.check("a\n", 3, now)), None);
+        assert_eq!(dedupe.check("a", 3, now), Dedupe::Repeat);
+        assert_eq!(dedupe.check("a", 3, now), Dedupe::Repeat);
+        assert_eq!(repeats(dedupe.check("b", 6, now)), Some(2));
+        // "a" is still within the window of two lines
+        assert_eq!(dedupe.check("a", 3, now), Dedupe::Repeat);
+        assert_eq!(repeats(dedupe.check("c", 6, now)), Some(1));
+        assert_eq!(repeats(dedupe.check("d", 6, now)), None);
+        assert_eq!(repeats(dedupe.check("a", 3, now)), None);
+    }
+
+    #[test]
+    fn deduper_summarizes_run_after_quiet_spell_or_finish() {
+        let start = Instant::now();
+        let mut dedupe = LogDeduper::new(10);
+        dedupe.check("[ERR] boom", 3, start);
+        dedupe.check("[ERR] boom", 3, start);
+        assert_eq!(dedupe.expired(start + Duration::from_secs(1)), None);
+        let run = dedupe
+            .expired(start + REPEAT_SUMMARY_DELAY)
+            .unwrap();
+        assert_eq!((run.count, run.level), (1, 3));
+        assert_eq!(
+            format_repeat_run(&run, ColorMode::Never, None),
+            "[repeated 1 time]"
+        );
+        assert_eq!(dedupe.finish(), None);
+
+        dedupe.check("[ERR] boom", 3, start);
+        assert_eq!(
+            dedupe
+                .finish()
+                .map(|run| run.count),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn deduper_ignores_freeswitch_timestamp() {
+        let now = Instant::now();
+        let mut dedupe = LogDeduper::new(10);
+        assert_eq!(
+            repeats(dedupe.check("2024-01-01 10:00:00.000001 [ERR] x.c:1 boom\n", 3, now)),
+            None
+        );
+        assert_eq!(
+            dedupe.check("2024-01-01 10:00:00.000002 [ERR] x.c:1 boom\n", 3, now),
+            Dedupe::Repeat
+        );
+    }
+
+    #[test]
+    fn rate_limiter_buffers_burst_and_flushes() {
+        let start = Instant::now();
+        let mut limiter = LogRateLimiter::new(10, 100, start);
+        let shown: Vec<String> = (0..1000)
+            .flat_map(|i| limiter.push(format!("line {}", i), start))
+            .collect();
+        assert_eq!(shown.len(), 110);
+        assert_eq!(shown[109], "line 109");
+
+        assert_eq!(
+            limiter.tick(start + Duration::from_secs(1)),
+            vec!["[Log rate limited: 890 messages suppressed]"]
+        );
+        // Nothing new arrives, so the rate is back under the limit
+        let flushed = limiter.tick(start + Duration::from_secs(2));
+        assert_eq!(flushed.len(), 890);
+        assert_eq!(flushed[0], "line 110");
+        assert!(limiter
+            .tick(start + Duration::from_secs(3))
+            .is_empty());
+
+        let after = start + Duration::from_millis(3500);
+        assert_eq!(limiter.push("quiet".to_string(), after), vec!["quiet"]);
+    }
+
+    #[test]
+    fn rate_limiter_keeps_order_while_throttled() {
+        let start = Instant::now();
+        let mut limiter = LogRateLimiter::new(2, 0, start);
+        assert_eq!(limiter.push("a".to_string(), start), vec!["a"]);
+        assert_eq!(limiter.push("b".to_string(), start), vec!["b"]);
+        assert!(limiter
+            .push("c".to_string(), start)
+            .is_empty());
+        // Still throttled in the next window: held behind the earlier line
+        let next = start + Duration::from_secs(1);
+        assert_eq!(
+            limiter.push("d".to_string(), next),
+            vec!["[Log rate limited: 1 messages suppressed]"]
+        );
+        assert_eq!(limiter.tick(next + Duration::from_secs(1)), vec!["c", "d"]);
+    }
+
+    #[test]
+    fn parse_color_names() {
+        assert_eq!(parse_color("red"), Some(Color::Red));
+        assert_eq!(parse_color("Bright_Blue"), Some(Color::BrightBlue));
+        assert_eq!(parse_color("bright yellow"), Some(Color::BrightYellow));
+        assert_eq!(parse_color("purple"), Some(Color::Magenta));
+        assert_eq!(parse_color("chartreuse"), None);
+        assert_eq!(parse_color(""), None);
+    }
+
+    #[test]
+    fn level_color_overrides_by_name_then_tag() {
+        let colors: HashMap<String, Color> = [
+            ("warning".to_string(), Color::BrightMagenta),
+            ("error".to_string(), Color::Blue),
+            ("debug".to_string(), Color::White),
+            ("debug3".to_string(), Color::Cyan),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            level_color(LogLevel::Warning, &colors),
+            Some(Color::BrightMagenta)
+        );
+        assert_eq!(level_color(LogLevel::Err, &colors), Some(Color::Blue));
+        assert_eq!(level_color(LogLevel::Debug1, &colors), Some(Color::White));
+        assert_eq!(level_color(LogLevel::Debug3, &colors), Some(Color::Cyan));
+        assert_eq!(level_color(LogLevel::Info, &colors), None);
+        assert_eq!(level_color(LogLevel::Info, &HashMap::new()), None);
+    }
+
+    #[test]
+    fn log_timestamp_renders_in_timezone() {
+        let ts = LogTimestamp::new(Some("%H:%M:%S%.3f %Z"), Some("America/Toronto")).unwrap();
+        let at = DateTime::parse_from_rfc3339("2024-01-15T17:30:00.250Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(ts.render(at), "12:30:00.250 EST");
+    }
+
+    #[test]
+    fn log_timestamp_rejects_bad_settings() {
+        assert!(LogTimestamp::new(None, None).is_ok());
+        assert!(LogTimestamp::new(Some("%H:%Q"), None).is_err());
+        assert!(LogTimestamp::new(None, Some("Mars/Olympus_Mons")).is_err());
+    }
+
+    #[test]
+    fn format_log_line_color_modes() {
+        colored::control::set_override(true);
+        let body = "[ERR] mod_sofia.c:1 failed";
+
+        let tag = format_log_line(body, 3, ColorMode::Tag, None, None, 0);
+        assert!(tag.starts_with("\x1b["));
+        assert!(tag.ends_with(" mod_sofia.c:1 failed"));
+
+        let line = format_log_line(body, 3, ColorMode::Line, None, None, 0);
+        assert!(line.starts_with("\x1b["));
+        assert!(line.ends_with("\x1b[0m"));
+        assert!(line.contains(body));
+
+        assert_eq!(
+            format_log_line(body, 3, ColorMode::Auto, None, None, 0),
+            line
+        );
+        assert_eq!(
+            format_log_line(body, 3, ColorMode::Never, None, None, 0),
+            body
+        );
+    }
+
+    #[test]
+    fn format_log_line_bright_colors_each_level() {
+        colored::control::set_override(true);
+        for (level, code) in [
+            (0, "\x1b[1;97m"),
+            (1, "\x1b[1;91m"),
+            (2, "\x1b[1;91m"),
+            (3, "\x1b[91m"),
+            (4, "\x1b[93m"),
+            (5, "\x1b[96m"),
+            (6, "\x1b[92m"),
+            (7, "\x1b[94m"),
+            (10, "\x1b[94m"),
+        ] {
+            let line = format_log_line("up", level, ColorMode::Bright, None, None, 0);
+            assert!(line.starts_with(code), "level {}: {:?}", level, line);
+            assert!(line.ends_with("\x1b[0m"), "level {}: {:?}", level, line);
+        }
+        assert_ne!(
+            format_log_line("up", 3, ColorMode::Bright, None, None, 0),
+            format_log_line("up", 3, ColorMode::Line, None, None, 0)
+        );
+    }
 }
@@ -1,21 +1,33 @@
 //! fs_cli-rs: Interactive FreeSWITCH CLI client using ESL
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use freeswitch_esl_tokio::{EslClient, EslError, EslEventStream, EslEventType, EventFormat};
+use rand::Rng;
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
 use tokio::time::{timeout, Duration};
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 mod args;
+mod audit;
 mod channel_info;
 mod commands;
 mod completion;
 mod config;
+mod connections;
 mod console_complete;
 mod esl_debug;
+mod event_manager;
+mod export;
 mod log_display;
+mod mock_server;
 mod printer;
 mod readline;
 mod session;
+mod table;
+mod tls;
 
 use args::Args;
 use commands::CommandProcessor;
@@ -24,9 +36,34 @@ use esl_debug::EslDebugLevel;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
-    let config = Args::parse_and_merge()?;
+    let mut config = Args::parse_and_merge()?;
 
     setup_logging(config.debug);
+    esl_debug::set_structured(config.debug_structured);
+    log_display::set_log_colors(&config.log_colors);
+    if let Some(path) = &config.debug_output_file {
+        esl_debug::set_output_file(path)?;
+    }
+
+    if let Some(port) = config.mock_port {
+        let fixtures = match &config.mock_fixtures {
+            Some(path) => mock_server::MockFixtures::load(path)?,
+            None => mock_server::MockFixtures::default(),
+        };
+        let addr = mock_server::start(
+            port,
+            config
+                .password
+                .clone(),
+            fixtures,
+        )
+        .await?;
+        info!("Mock ESL server listening on {}", addr);
+        config.host = addr
+            .ip()
+            .to_string();
+        config.port = addr.port();
+    }
 
     config
         .debug
@@ -52,16 +89,28 @@ async fn main() -> Result<()> {
         .execute
         .is_empty()
     {
-        execute_commands(&client, &config.execute, &config).await?;
+        let stopped_at = execute_commands(&client, &config.execute, &config).await?;
         info!("Disconnecting from FreeSWITCH...");
         client
             .disconnect()
             .await?;
-    } else if let Err(e) = session::run_interactive_mode(client, events, &config).await {
+        if stopped_at.is_some() {
+            std::process::exit(1);
+        }
+    } else {
         // Event subscriptions, idle-liveness gating, and logging are set up
         // per-connection inside run_interactive_mode (initial and reconnect).
-        eprintln!("{}", e);
-        std::process::exit(1);
+        match session::run_interactive_mode(client, events, &config).await {
+            Ok(session::SessionEnd::Quit) => std::process::exit(0),
+            Ok(end) => {
+                eprintln!("{}", end);
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
     }
 
     Ok(())
@@ -70,6 +119,7 @@ async fn main() -> Result<()> {
 fn setup_logging(debug_level: EslDebugLevel) {
     tracing_subscriber::fmt()
         .with_writer(std::io::stderr)
+        .with_ansi(std::io::IsTerminal::is_terminal(&std::io::stderr()))
         .with_env_filter(debug_level.tracing_filter())
         .with_target(false)
         .with_thread_ids(false)
@@ -80,23 +130,74 @@ fn setup_logging(debug_level: EslDebugLevel) {
 
 /// Connect to FreeSWITCH with timeout
 pub async fn connect_to_freeswitch(config: &AppConfig) -> Result<(EslClient, EslEventStream)> {
+    if let Some(path) = &config.unix_socket {
+        debug!(
+            "unix_socket {} takes precedence over {}",
+            path.display(),
+            format_host_port(&config.host, config.port)
+        );
+        info!("Connecting to FreeSWITCH at {}", path.display());
+        return connect_unix_socket(config, path).await;
+    }
+    if config.tls {
+        info!(
+            "Connecting to FreeSWITCH at {} over TLS",
+            format_host_port(&config.host, config.port)
+        );
+        let stream = timeout(Duration::from_millis(config.timeout), tls::connect(config))
+            .await
+            .context("Connection timed out")??;
+        return connect_over_stream(config, stream).await;
+    }
+
     info!(
         "Connecting to FreeSWITCH at {}",
         format_host_port(&config.host, config.port)
     );
+    let (host, port) = (&config.host, config.port);
+    let result = if let Some(ref user) = config.user {
+        info!("Using user authentication: {}", user);
+        timeout(
+            Duration::from_millis(config.timeout),
+            EslClient::connect_with_user(host, port, user, &config.password),
+        )
+        .await
+    } else {
+        info!("Using password authentication");
+        timeout(
+            Duration::from_millis(config.timeout),
+            EslClient::connect(host, port, &config.password),
+        )
+        .await
+    };
+
+    let (client, events) = result
+        .context("Connection timed out")?
+        .context("Failed to connect to FreeSWITCH")?;
 
+    Ok((client, events))
+}
+
+/// Authenticate over an already-open stream (Unix socket, TLS)
+async fn connect_over_stream<S>(
+    config: &AppConfig,
+    stream: S,
+) -> Result<(EslClient, EslEventStream)>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
     let result = if let Some(ref user) = config.user {
         info!("Using user authentication: {}", user);
         timeout(
             Duration::from_millis(config.timeout),
-            EslClient::connect_with_user(&config.host, config.port, user, &config.password),
+            EslClient::connect_stream_with_user(stream, user, &config.password),
         )
         .await
     } else {
         info!("Using password authentication");
         timeout(
             Duration::from_millis(config.timeout),
-            EslClient::connect(&config.host, config.port, &config.password),
+            EslClient::connect_stream(stream, &config.password),
         )
         .await
     };
@@ -108,15 +209,113 @@ pub async fn connect_to_freeswitch(config: &AppConfig) -> Result<(EslClient, Esl
     Ok((client, events))
 }
 
-/// Retry connecting forever. Never returns — loops until a connection succeeds.
-pub async fn connect_retry_forever(config: &AppConfig) -> (EslClient, EslEventStream) {
+/// ESL straight over the Unix socket, so only users the socket's
+/// permissions allow can reach it
+#[cfg(unix)]
+async fn connect_unix_socket(
+    config: &AppConfig,
+    path: &std::path::Path,
+) -> Result<(EslClient, EslEventStream)> {
+    let unix = timeout(
+        Duration::from_millis(config.timeout),
+        tokio::net::UnixStream::connect(path),
+    )
+    .await
+    .context("Connection timed out")?
+    .with_context(|| format!("Failed to connect to {}", path.display()))?;
+    connect_over_stream(config, unix).await
+}
+
+#[cfg(not(unix))]
+async fn connect_unix_socket(
+    _config: &AppConfig,
+    _path: &std::path::Path,
+) -> Result<(EslClient, EslEventStream)> {
+    anyhow::bail!("Unix sockets are not supported on this platform")
+}
+
+/// Delay before retry number `attempt` (0-based), without jitter: with
+/// `reconnect_backoff`, doubles from `reconnect_base_delay_ms` up to
+/// `reconnect_max_delay_ms`; without, a constant `reconnect_delay_ms`.
+fn reconnect_delay_ms(attempt: u32, config: &AppConfig) -> u64 {
+    if !config.reconnect_backoff {
+        return config.reconnect_delay_ms;
+    }
+    let factor = 1u64
+        .checked_shl(attempt)
+        .unwrap_or(u64::MAX);
+    config
+        .reconnect_base_delay_ms
+        .saturating_mul(factor)
+        .min(config.reconnect_max_delay_ms)
+}
+
+/// Retry connecting until it succeeds, or until `reconnect_max_attempts`
+/// attempts have failed when set.
+pub async fn connect_retrying(config: &AppConfig) -> Result<(EslClient, EslEventStream)> {
+    // Without debug output, a dot per failed attempt shows progress
+    let dots = config.debug == EslDebugLevel::None && std::io::stderr().is_terminal();
+    let end_dots = |attempts: u32| {
+        if dots && attempts > 0 {
+            eprintln!();
+        }
+    };
+    let mut attempt: u32 = 0;
     loop {
+        info!(
+            "Connection attempt {} to {}",
+            attempt + 1,
+            connect_target(config)
+        );
         match connect_to_freeswitch(config).await {
-            Ok(pair) => return pair,
+            Ok(pair) => {
+                end_dots(attempt);
+                return Ok(pair);
+            }
             Err(e) => {
                 warn!("Connection attempt failed: {}", e);
-                info!("Retrying in {} ms...", config.timeout);
-                tokio::time::sleep(Duration::from_millis(config.timeout)).await;
+                if dots {
+                    eprint!(".");
+                    std::io::stderr()
+                        .flush()
+                        .ok();
+                }
+                if let Some(limit) = config
+                    .reconnect_max_attempts
+                    .filter(|&limit| attempt.saturating_add(1) >= limit)
+                {
+                    end_dots(attempt + 1);
+                    return Err(anyhow!("Failed to connect after {} attempts", limit));
+                }
+                let mut delay = reconnect_delay_ms(attempt, config);
+                // Spread out clients that lost the same server at the same time
+                if config.reconnect_backoff && delay >= 4 {
+                    delay += rand::thread_rng().gen_range(0..delay / 4);
+                }
+                attempt = attempt.saturating_add(1);
+                warn!("Reconnect attempt {} (next in {}ms)", attempt, delay);
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+            }
+        }
+    }
+}
+
+/// `--wait-for-connection`: retry every `reconnect_base_delay_ms` until
+/// FreeSWITCH accepts the connection, however long that takes
+async fn wait_for_connection(config: &AppConfig) -> (EslClient, EslEventStream) {
+    let mut attempt: u32 = 0;
+    loop {
+        match connect_to_freeswitch(config).await {
+            Ok(pair) => return pair,
+            Err(e) => {
+                attempt = attempt.saturating_add(1);
+                debug!("Connection attempt failed: {}", e);
+                warn!(
+                    "Waiting for FreeSWITCH to come up at {}... (attempt {})",
+                    connect_target(config),
+                    attempt
+                );
+                tokio::time::sleep(Duration::from_millis(config.reconnect_base_delay_ms)).await;
             }
         }
     }
@@ -125,21 +324,43 @@ pub async fn connect_retry_forever(config: &AppConfig) -> (EslClient, EslEventSt
 async fn connect_to_freeswitch_with_retry(
     config: &AppConfig,
 ) -> Result<(EslClient, EslEventStream)> {
+    if config.wait_for_connection {
+        if config
+            .execute
+            .is_empty()
+        {
+            return Ok(wait_for_connection(config).await);
+        }
+        warn!("--wait-for-connection is ignored with -x");
+    }
     if !config.retry {
         return connect_to_freeswitch(config).await;
     }
     info!(
-        "Retry mode enabled - will retry every {} ms",
-        config.timeout
+        "Retry mode enabled - will retry starting at {} ms",
+        config.reconnect_base_delay_ms
     );
-    Ok(connect_retry_forever(config).await)
+    connect_retrying(config).await
 }
 
-/// Check if error indicates connection loss
+/// Check if error indicates connection loss: an ESL connection error or a
+/// TLS failure, anywhere in the context chain
 pub fn is_connection_error(error: &anyhow::Error) -> bool {
     error
-        .downcast_ref::<EslError>()
-        .is_some_and(|e| e.is_connection_error())
+        .chain()
+        .any(|cause| {
+            if let Some(e) = cause.downcast_ref::<EslError>() {
+                return e.is_connection_error();
+            }
+            if cause.is::<tokio_rustls::rustls::Error>() {
+                return true;
+            }
+            // rustls reports failures on an established stream as io::Error
+            cause
+                .downcast_ref::<std::io::Error>()
+                .and_then(|e| e.get_ref())
+                .is_some_and(|inner| inner.is::<tokio_rustls::rustls::Error>())
+        })
 }
 
 /// Check if error is an ESL permission denial (e.g. an event the user is not
@@ -155,15 +376,7 @@ pub fn is_permission_denied(error: &anyhow::Error) -> bool {
 pub async fn subscribe_to_events(client: &EslClient) -> Result<()> {
     info!("Subscribing to events...");
     client
-        .subscribe_events(
-            EventFormat::Plain,
-            &[
-                EslEventType::ChannelCreate,
-                EslEventType::ChannelAnswer,
-                EslEventType::ChannelHangup,
-                EslEventType::Heartbeat,
-            ],
-        )
+        .subscribe_events(EventFormat::Plain, &event_manager::MONITOR_EVENTS)
         .await?;
     info!("Event monitoring enabled");
     Ok(())
@@ -189,18 +402,102 @@ pub async fn enable_logging(
     Ok(())
 }
 
+/// Run `-x` commands in order. Returns the command that stopped execution
+/// under `--fail-fast` or `--exit-on-error-pattern`, if any.
 async fn execute_commands(
     client: &EslClient,
     commands: &[String],
     config: &AppConfig,
-) -> Result<()> {
-    let processor = CommandProcessor::new(config.color, config.debug);
+) -> Result<Option<String>> {
+    let processor = CommandProcessor::new(config);
+    if config.execute_parallel && !config.dry_run {
+        if config.execute_fail_fast {
+            warn!("--parallel is ignored with --fail-fast; running commands sequentially");
+        } else {
+            return execute_commands_parallel(client, commands, config, &processor).await;
+        }
+    }
     for command in commands {
-        processor
-            .execute_command(client, command)
+        let output = processor
+            .execute_command_output(client, command)
             .await?;
+        if output.failed && config.execute_fail_fast {
+            eprintln!("Error: '{}' failed, skipping remaining commands", command);
+            return Ok(Some(command.clone()));
+        }
+        if let Some(pattern) = &config.exit_on_error_pattern {
+            if pattern.is_match(&output.body) {
+                eprintln!(
+                    "Error: '{}' output matches '{}', skipping remaining commands",
+                    command, pattern
+                );
+                return Ok(Some(command.clone()));
+            }
+        }
     }
-    Ok(())
+    Ok(None)
+}
+
+/// `--parallel`: start every API command up front, at most
+/// `execute_max_concurrent` at a time, then print the results in the original
+/// order. Commands the processor renders itself (`/` commands, `show`,
+/// `log`, ...) still run in sequence, when their turn comes.
+async fn execute_commands_parallel(
+    client: &EslClient,
+    commands: &[String],
+    config: &AppConfig,
+    processor: &CommandProcessor,
+) -> Result<Option<String>> {
+    let limit = Arc::new(Semaphore::new(
+        config
+            .execute_max_concurrent
+            .max(1),
+    ));
+    let handles: Vec<Option<JoinHandle<Result<String>>>> = commands
+        .iter()
+        .map(|command| {
+            // Dry runs and commands rendered locally go through the processor
+            if config.dry_run || commands::is_special_command(command) {
+                return None;
+            }
+            if let Some(audit_log) = &config.audit_log {
+                audit_log.record(command);
+            }
+            let client = client.clone();
+            let command = command.clone();
+            let limit = limit.clone();
+            Some(tokio::spawn(async move {
+                let _permit = limit
+                    .acquire_owned()
+                    .await?;
+                commands::api_body(&client, &command).await
+            }))
+        })
+        .collect();
+
+    for (command, handle) in commands
+        .iter()
+        .zip(handles)
+    {
+        let output = match handle {
+            Some(handle) => processor.show_api_result(handle.await?)?,
+            None => {
+                processor
+                    .execute_command_output(client, command)
+                    .await?
+            }
+        };
+        if let Some(pattern) = &config.exit_on_error_pattern {
+            if pattern.is_match(&output.body) {
+                eprintln!(
+                    "Error: '{}' output matches '{}', skipping remaining results",
+                    command, pattern
+                );
+                return Ok(Some(command.clone()));
+            }
+        }
+    }
+    Ok(None)
 }
 
 fn format_host_port(host: &str, port: u16) -> String {
@@ -211,7 +508,31 @@ fn format_host_port(host: &str, port: u16) -> String {
     }
 }
 
+/// Where `connect_to_freeswitch` connects: the Unix socket path when set,
+/// otherwise host and port
+fn connect_target(config: &AppConfig) -> String {
+    match &config.unix_socket {
+        Some(path) => path
+            .display()
+            .to_string(),
+        None => format_host_port(&config.host, config.port),
+    }
+}
+
 fn print_io_hint(io_err: &std::io::Error, config: &AppConfig) {
+    if let Some(path) = &config.unix_socket {
+        match io_err.kind() {
+            std::io::ErrorKind::NotFound => {
+                eprintln!("Socket {} does not exist", path.display());
+                return;
+            }
+            std::io::ErrorKind::PermissionDenied => {
+                eprintln!("Permission denied on socket {}", path.display());
+                return;
+            }
+            _ => {}
+        }
+    }
     match io_err.kind() {
         std::io::ErrorKind::ConnectionRefused => {
             eprintln!(
@@ -236,7 +557,7 @@ fn print_connect_error(e: &anyhow::Error, config: &AppConfig) {
 
     eprintln!(
         "Failed to connect to FreeSWITCH at {}",
-        format_host_port(&config.host, config.port)
+        connect_target(config)
     );
 
     if let Some(esl_err) = e.downcast_ref::<EslError>() {
@@ -287,6 +608,167 @@ mod tests {
         assert!(!is_connection_error(&err));
     }
 
+    fn backoff_config(base_ms: u64, max_ms: u64, backoff: bool) -> AppConfig {
+        let mut config = config::ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        config.reconnect_base_delay_ms = base_ms;
+        config.reconnect_max_delay_ms = max_ms;
+        config.reconnect_backoff = backoff;
+        config
+    }
+
+    #[test]
+    fn test_connect_target() {
+        let mut config = backoff_config(1000, 10_000, true);
+        config.host = "::1".to_string();
+        config.port = 8021;
+        assert_eq!(connect_target(&config), "[::1]:8021");
+        config.unix_socket = Some(std::path::PathBuf::from("/run/freeswitch/esl.sock"));
+        assert_eq!(connect_target(&config), "/run/freeswitch/esl.sock");
+    }
+
+    #[test]
+    fn test_reconnect_delay_backoff_sequence() {
+        let config = backoff_config(1000, 10_000, true);
+        let delays: Vec<u64> = (0..5)
+            .map(|attempt| reconnect_delay_ms(attempt, &config))
+            .collect();
+        assert_eq!(delays, vec![1000, 2000, 4000, 8000, 10_000]);
+    }
+
+    #[test]
+    fn test_reconnect_delay_without_backoff_is_constant() {
+        let mut config = backoff_config(1000, 10_000, false);
+        config.reconnect_delay_ms = 250;
+        let delays: Vec<u64> = (0..5)
+            .map(|attempt| reconnect_delay_ms(attempt, &config))
+            .collect();
+        assert_eq!(delays, vec![250; 5]);
+    }
+
+    #[test]
+    fn test_reconnect_delay_saturates() {
+        let config = backoff_config(1000, 30_000, true);
+        assert_eq!(reconnect_delay_ms(200, &config), 30_000);
+    }
+
+    async fn mock_client() -> (EslClient, AppConfig) {
+        let addr = mock_server::start(
+            0,
+            "ClueCon".to_string(),
+            mock_server::MockFixtures::default(),
+        )
+        .await
+        .unwrap();
+        let (client, _events) = EslClient::connect("127.0.0.1", addr.port(), "ClueCon")
+            .await
+            .unwrap();
+        let config = config::ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        (client, config)
+    }
+
+    #[tokio::test]
+    async fn test_connect_retrying_gives_up_after_max_attempts() {
+        // Nothing listens on a port freed right after binding
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut config = config::ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        config.host = "127.0.0.1".to_string();
+        config.port = port;
+        config.reconnect_delay_ms = 1;
+        config.reconnect_backoff = false;
+        config.reconnect_max_attempts = Some(3);
+
+        let err = connect_retrying(&config)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Failed to connect after 3 attempts");
+    }
+
+    fn commands(list: &[&str]) -> Vec<String> {
+        list.iter()
+            .map(|c| c.to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_execute_commands_fail_fast_stops_at_first_failure() {
+        let (client, mut config) = mock_client().await;
+        config.execute_fail_fast = true;
+        let stopped =
+            execute_commands(&client, &commands(&["status", "bogus", "version"]), &config)
+                .await
+                .unwrap();
+        assert_eq!(stopped.as_deref(), Some("bogus"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_commands_runs_all_without_fail_fast() {
+        let (client, config) = mock_client().await;
+        let stopped = execute_commands(&client, &commands(&["bogus", "status"]), &config)
+            .await
+            .unwrap();
+        assert!(stopped.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execute_commands_exit_on_error_pattern() {
+        let (client, mut config) = mock_client().await;
+        config.exit_on_error_pattern = Some(regex::Regex::new("is ready").unwrap());
+        let stopped =
+            execute_commands(&client, &commands(&["version", "status", "bogus"]), &config)
+                .await
+                .unwrap();
+        assert_eq!(stopped.as_deref(), Some("status"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_commands_parallel_reports_in_order() {
+        let (client, mut config) = mock_client().await;
+        config.execute_parallel = true;
+        config.execute_max_concurrent = 2;
+        config.exit_on_error_pattern = Some(regex::Regex::new("^-ERR").unwrap());
+        let stopped = execute_commands(
+            &client,
+            &commands(&["status", "version", "bogus", "status"]),
+            &config,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stopped.as_deref(), Some("bogus"));
+    }
+
+    #[test]
+    fn test_is_connection_error_through_context() {
+        let err = anyhow::Error::from(EslError::ConnectionClosed).context("api status failed");
+        assert!(is_connection_error(&err));
+
+        let err = anyhow::Error::from(EslError::auth_failed("bad password")).context("login");
+        assert!(!is_connection_error(&err));
+    }
+
+    #[test]
+    fn test_is_connection_error_with_tls_errors() {
+        let err: anyhow::Error = tokio_rustls::rustls::Error::DecryptError.into();
+        assert!(is_connection_error(&err));
+
+        let io_err = std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            tokio_rustls::rustls::Error::DecryptError,
+        );
+        let err = anyhow::Error::from(io_err).context("TLS read");
+        assert!(is_connection_error(&err));
+    }
+
     #[test]
     fn test_is_connection_error_with_other_errors() {
         let err = anyhow::anyhow!("Some random error");
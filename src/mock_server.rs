@@ -0,0 +1,354 @@
+//! Minimal in-process ESL server for offline testing (`--mock`)
+//!
+//! Speaks just enough of the event socket protocol for fs_cli to connect,
+//! authenticate, run `api` commands against canned responses, and receive
+//! synthetic log and heartbeat events. Not a FreeSWITCH emulator: unknown
+//! commands get the same `-ERR` a real server would send.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{interval, Duration};
+use tracing::{debug, warn};
+
+/// Well under the client's 30 s liveness timeout
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Fixtures used when `--mock-fixtures` is not given
+const DEFAULT_FIXTURES: &str = r#"
+api:
+  - pattern: '^status$'
+    body: |
+      UP 0 years, 0 days, 1 hour, 2 minutes, 3 seconds, 0 milliseconds, 0 microseconds
+      FreeSWITCH (Version 1.10.12-release mock) is ready
+      0 session(s) since startup
+      0 session(s) - peak 0, last 5min 0
+      1000 session(s) max
+  - pattern: '^version'
+    body: "FreeSWITCH Version 1.10.12-release mock\n"
+  - pattern: '^show \w+( count)? as json$'
+    body: '{"row_count":0}'
+  - pattern: '^console_complete '
+    body: ''
+logs:
+  - "mock_server.c:1 Mock FreeSWITCH is logging"
+"#;
+
+#[derive(Debug, Deserialize)]
+struct FixtureFile {
+    #[serde(default)]
+    api: Vec<FixtureEntry>,
+    #[serde(default)]
+    logs: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FixtureEntry {
+    /// Regex matched against the API command (without the `api ` prefix)
+    pattern: String,
+    body: String,
+}
+
+/// Canned responses for the mock server.
+///
+/// The YAML maps command patterns to response bodies; entries are tried in
+/// file order and the first match wins:
+///
+/// ```yaml
+/// api:
+///   - pattern: '^sofia status$'
+///     body: "..."
+/// logs:
+///   - "line sent as a log event once logging is enabled"
+/// ```
+pub struct MockFixtures {
+    api: Vec<(Regex, String)>,
+    logs: Vec<String>,
+}
+
+impl MockFixtures {
+    /// Load fixtures from a YAML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read mock fixtures {}", path.display()))?;
+        Self::from_yaml(&content)
+            .with_context(|| format!("Invalid mock fixtures in {}", path.display()))
+    }
+
+    fn from_yaml(yaml: &str) -> Result<Self> {
+        let file: FixtureFile = serde_yaml::from_str(yaml)?;
+        let api = file
+            .api
+            .into_iter()
+            .map(|entry| {
+                let re = Regex::new(&entry.pattern)
+                    .with_context(|| format!("Invalid pattern '{}'", entry.pattern))?;
+                Ok((re, entry.body))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            api,
+            logs: file.logs,
+        })
+    }
+
+    fn api_response(&self, command: &str) -> String {
+        self.api
+            .iter()
+            .find(|(re, _)| re.is_match(command))
+            .map(|(_, body)| body.clone())
+            .unwrap_or_else(|| format!("-ERR {} Command not found!\n", command))
+    }
+}
+
+impl Default for MockFixtures {
+    fn default() -> Self {
+        Self::from_yaml(DEFAULT_FIXTURES).expect("built-in mock fixtures are valid")
+    }
+}
+
+struct MockState {
+    password: String,
+    fixtures: MockFixtures,
+}
+
+/// Bind `127.0.0.1:port` (0 picks a free port) and serve ESL connections in
+/// the background. Returns the bound address.
+pub async fn start(port: u16, password: String, fixtures: MockFixtures) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("Mock ESL server could not bind port {}", port))?;
+    let addr = listener.local_addr()?;
+    let state = Arc::new(MockState { password, fixtures });
+
+    tokio::spawn(async move {
+        loop {
+            match listener
+                .accept()
+                .await
+            {
+                Ok((stream, peer)) => {
+                    debug!("mock: connection from {}", peer);
+                    let state = state.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve(stream, &state).await {
+                            debug!("mock: connection ended: {}", e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    warn!("mock: accept failed: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(addr)
+}
+
+/// Serve one client until it sends `exit` or disconnects
+async fn serve(stream: TcpStream, state: &MockState) -> Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    write
+        .write_all(b"Content-Type: auth/request\n\n")
+        .await?;
+
+    let mut authenticated = false;
+    let mut events_enabled = false;
+    let mut heartbeat = interval(HEARTBEAT_INTERVAL);
+    // First line of the command being read; following lines until the blank
+    // terminator are headers, which the mock ignores.
+    let mut command = String::new();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    return Ok(());
+                };
+                let line = line.trim_end_matches('\r');
+                if !line.is_empty() {
+                    if command.is_empty() {
+                        command = line.to_string();
+                    }
+                    continue;
+                }
+                if command.is_empty() {
+                    continue;
+                }
+                let cmd = std::mem::take(&mut command);
+                debug!("mock: received {:?}", cmd);
+
+                if !authenticated {
+                    if check_auth(&cmd, &state.password) {
+                        reply(&mut write, "+OK accepted").await?;
+                        authenticated = true;
+                        continue;
+                    }
+                    reply(&mut write, "-ERR invalid").await?;
+                    return Ok(());
+                }
+
+                let (verb, arg) = cmd
+                    .split_once(' ')
+                    .unwrap_or((cmd.as_str(), ""));
+                match verb {
+                    "api" => {
+                        let body = state
+                            .fixtures
+                            .api_response(arg);
+                        send(&mut write, "api/response", &[], &body).await?;
+                    }
+                    "event" => {
+                        events_enabled = true;
+                        reply(&mut write, "+OK event listener enabled plain").await?;
+                    }
+                    "log" => {
+                        reply(&mut write, "+OK log level 7 [7]").await?;
+                        for log in &state.fixtures.logs {
+                            let body = format!("{}\n", log);
+                            send(&mut write, "log/data", &[("Log-Level", "6")], &body).await?;
+                        }
+                    }
+                    "nolog" => reply(&mut write, "+OK no longer logging").await?,
+                    "noevents" | "nixevent" | "filter" | "myevents" => {
+                        reply(&mut write, "+OK").await?;
+                    }
+                    "exit" => {
+                        reply(&mut write, "+OK bye").await?;
+                        send(&mut write, "text/disconnect-notice", &[], "Disconnected, goodbye.\n")
+                            .await?;
+                        return Ok(());
+                    }
+                    _ => reply(&mut write, "-ERR command not found").await?,
+                }
+            }
+            _ = heartbeat.tick(), if events_enabled => {
+                let event = "Event-Name: HEARTBEAT\nCore-UUID: mock\nEvent-Info: System%20Ready\n\n";
+                send(&mut write, "text/event-plain", &[], event).await?;
+            }
+        }
+    }
+}
+
+/// Accept `auth <password>` or `userauth <user>@<domain>:<password>`
+fn check_auth(cmd: &str, password: &str) -> bool {
+    if let Some(given) = cmd.strip_prefix("auth ") {
+        return given == password;
+    }
+    cmd.strip_prefix("userauth ")
+        .and_then(|creds| creds.rsplit_once(':'))
+        .is_some_and(|(_, given)| given == password)
+}
+
+async fn reply(write: &mut OwnedWriteHalf, text: &str) -> Result<()> {
+    let frame = format!("Content-Type: command/reply\nReply-Text: {}\n\n", text);
+    write
+        .write_all(frame.as_bytes())
+        .await?;
+    Ok(())
+}
+
+async fn send(
+    write: &mut OwnedWriteHalf,
+    content_type: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> Result<()> {
+    let mut frame = format!(
+        "Content-Type: {}\nContent-Length: {}\n",
+        content_type,
+        body.len()
+    );
+    for (name, value) in headers {
+        frame.push_str(&format!("{}: {}\n", name, value));
+    }
+    frame.push('\n');
+    frame.push_str(body);
+    write
+        .write_all(frame.as_bytes())
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freeswitch_esl_tokio::EslClient;
+
+    #[test]
+    fn fixtures_first_match_wins() {
+        let fixtures = MockFixtures::from_yaml(
+            r#"
+api:
+  - pattern: '^sofia status profile'
+    body: profile
+  - pattern: '^sofia status'
+    body: summary
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            fixtures.api_response("sofia status profile internal"),
+            "profile"
+        );
+        assert_eq!(fixtures.api_response("sofia status"), "summary");
+        assert!(fixtures
+            .api_response("uptime")
+            .starts_with("-ERR"));
+    }
+
+    #[test]
+    fn fixtures_reject_invalid_regex() {
+        let result = MockFixtures::from_yaml("api:\n  - pattern: '(unclosed'\n    body: x\n");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn auth_commands() {
+        assert!(check_auth("auth ClueCon", "ClueCon"));
+        assert!(!check_auth("auth wrong", "ClueCon"));
+        assert!(check_auth("userauth admin@default:ClueCon", "ClueCon"));
+        assert!(!check_auth("api status", "ClueCon"));
+    }
+
+    #[tokio::test]
+    async fn client_runs_api_against_mock() {
+        let addr = start(0, "ClueCon".to_string(), MockFixtures::default())
+            .await
+            .unwrap();
+        let (client, _events) = EslClient::connect("127.0.0.1", addr.port(), "ClueCon")
+            .await
+            .unwrap();
+        let response = client
+            .api("status")
+            .await
+            .unwrap();
+        assert!(response
+            .body()
+            .unwrap_or_default()
+            .contains("is ready"));
+        client
+            .disconnect()
+            .await
+            .ok();
+    }
+
+    #[tokio::test]
+    async fn client_rejected_with_wrong_password() {
+        let addr = start(0, "ClueCon".to_string(), MockFixtures::default())
+            .await
+            .unwrap();
+        assert!(EslClient::connect("127.0.0.1", addr.port(), "wrong")
+            .await
+            .is_err());
+    }
+}
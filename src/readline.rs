@@ -1,15 +1,21 @@
 //! Readline thread and function key management
 
+use crate::audit::has_credentials;
+use crate::commands::{hupall_command, is_dangerous_command};
 use crate::completion::FsCliCompleter;
-use crate::config::AppConfig;
+use crate::config::{AppConfig, DedupeMode};
 use crate::console_complete::Completion;
 use crate::printer::Printer;
 use anyhow::Result;
+use colored::Colorize;
 use gethostname::gethostname;
 use rustyline::history::{FileHistory, History};
 use rustyline::{Cmd, Editor, EventHandler, KeyCode, KeyEvent, Modifiers, Movement};
 use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
 use tokio::sync::{mpsc, oneshot};
 use tracing::{error, warn};
 
@@ -21,6 +27,72 @@ pub struct CompletionRequest {
     pub response_tx: std::sync::mpsc::SyncSender<Vec<Completion>>,
 }
 
+/// Active channel count request from readline thread to main thread, for
+/// the `/hupall` confirmation; `None` when the server could not be asked
+#[derive(Debug)]
+pub struct ChannelCountRequest {
+    pub response_tx: std::sync::mpsc::SyncSender<Option<u32>>,
+}
+
+/// How long `/hupall` waits for the main thread's channel count
+const CHANNEL_COUNT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Channels between the readline thread and the main thread
+pub struct ReadlineChannels {
+    pub cmd_tx: mpsc::UnboundedSender<String>,
+    pub quit_tx: oneshot::Sender<()>,
+    pub printer_tx: oneshot::Sender<Printer>,
+    pub completion_tx: mpsc::UnboundedSender<CompletionRequest>,
+    pub count_tx: mpsc::UnboundedSender<ChannelCountRequest>,
+    pub macro_update_rx: mpsc::UnboundedReceiver<(String, String)>,
+}
+
+/// Ask the main thread for `show channels count as json`, waiting at most
+/// `CHANNEL_COUNT_TIMEOUT`
+fn request_channel_count(count_tx: &mpsc::UnboundedSender<ChannelCountRequest>) -> Option<u32> {
+    let (response_tx, response_rx) = std::sync::mpsc::sync_channel(1);
+    count_tx
+        .send(ChannelCountRequest { response_tx })
+        .ok()?;
+    response_rx
+        .recv_timeout(CHANNEL_COUNT_TIMEOUT)
+        .ok()
+        .flatten()
+}
+
+/// Entries `/history` shows without an argument
+const DEFAULT_HISTORY_SHOWN: usize = 20;
+
+/// Most entries `/history <n>` shows
+const MAX_HISTORY_SHOWN: usize = 1000;
+
+/// Entries to show for `/history [n]`: 20 by default, at most 1000
+fn history_count(args: &str) -> Result<usize, String> {
+    let args = args.trim();
+    if args.is_empty() {
+        return Ok(DEFAULT_HISTORY_SHOWN);
+    }
+    args.parse::<usize>()
+        .map(|n| n.min(MAX_HISTORY_SHOWN))
+        .map_err(|_| "Usage: /history [count]".to_string())
+}
+
+/// Entry limit from `/history-size` arguments
+fn history_size(args: &str) -> Result<usize, String> {
+    args.trim()
+        .parse::<usize>()
+        .map_err(|_| "Usage: /history-size <entries>".to_string())
+}
+
+/// Function key number and command of a valid `/set-macro` line
+fn set_macro_binding(line: &str) -> Option<(u8, &str)> {
+    let args = line
+        .strip_prefix("/set-macro ")?
+        .trim();
+    let (key, command) = args.split_once(char::is_whitespace)?;
+    Some((function_key_number(key)?, command.trim()))
+}
+
 /// Default F1-F12 macro bindings in key-sorted order.
 pub const DEFAULT_FNKEYS: [(&str, &str); 12] = [
     ("f1", "help"),
@@ -53,15 +125,342 @@ pub fn parse_function_key(input: &str, macros: &HashMap<String, String>) -> Opti
         .cloned()
 }
 
-/// Build merged macros from defaults + config overrides
+/// Number of a function key name: `f1` to `f12`, case-insensitive, with
+/// no sign or leading zeros
+pub fn function_key_number(key: &str) -> Option<u8> {
+    key.strip_prefix(['f', 'F'])
+        .filter(|n| {
+            !n.starts_with('0')
+                && n.bytes()
+                    .all(|b| b.is_ascii_digit())
+        })
+        .and_then(|n| {
+            n.parse::<u8>()
+                .ok()
+        })
+        .filter(|n| (1..=12).contains(n))
+}
+
+/// Build merged macros from defaults + config overrides. Keys are
+/// lowercased; ones that are not function keys are kept but warned about,
+/// since no key triggers them.
 pub fn build_macros(config: &AppConfig) -> HashMap<String, String> {
     let mut macros = get_default_fnkeys();
     for (key, value) in &config.macros {
-        macros.insert(key.clone(), value.clone());
+        let key = key.to_lowercase();
+        if function_key_number(&key).is_none() {
+            warn!("Macro key {:?} is not f1-f12 and will be ignored", key);
+        }
+        macros.insert(key, value.clone());
     }
     macros
 }
 
+/// Host name shown in the prompt: the local hostname stands in for `localhost`
+pub fn prompt_host(host: &str) -> String {
+    if host == "localhost" {
+        gethostname()
+            .to_string_lossy()
+            .to_string()
+    } else {
+        host.to_string()
+    }
+}
+
+/// Ask a yes/no question on the terminal; anything but `y`/`yes` is a no.
+///
+/// Only call from the readline thread between `readline()` calls, when
+/// nothing else is reading stdin.
+pub fn prompt_confirmation(message: &str) -> bool {
+    prompt_line(message).is_some_and(|answer| {
+        matches!(
+            answer
+                .to_lowercase()
+                .as_str(),
+            "y" | "yes"
+        )
+    })
+}
+
+/// Print `message` and read one trimmed line from stdin; `None` on error.
+/// Same threading rules as [`prompt_confirmation`].
+fn prompt_line(message: &str) -> Option<String> {
+    print!("{}", message);
+    std::io::stdout()
+        .flush()
+        .ok()?;
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .ok()?;
+    Some(
+        answer
+            .trim()
+            .to_string(),
+    )
+}
+
+/// Ask for the settings of a new profile and build the
+/// `/create-profile <name> <host> <port> <password>` line for the command
+/// processor. Empty answers take the defaults; `None` if input failed.
+fn prompt_new_profile(name: &str) -> Option<String> {
+    let host = prompt_line("Host [localhost]: ")?;
+    let port = prompt_line("Port [8021]: ")?;
+    let password = rpassword::prompt_password("Password [ClueCon]: ").ok()?;
+    Some(format!(
+        "/create-profile {} {} {} {}",
+        name,
+        if host.is_empty() { "localhost" } else { &host },
+        if port.is_empty() { "8021" } else { &port },
+        if password.is_empty() {
+            "ClueCon"
+        } else {
+            &password
+        },
+    ))
+}
+
+/// Question asked before `/hupall`; `count` is the prompt's channel count
+/// when known
+fn hupall_question(cause: Option<&str>, count: Option<u32>) -> String {
+    let command = hupall_command(cause);
+    let cause = command.trim_start_matches("hupall ");
+    match count {
+        Some(count) => format!(
+            "This will hang up {} active channels with cause {}. Type 'yes' to confirm: ",
+            count, cause
+        ),
+        None => format!(
+            "This will hang up every active channel with cause {}. Type 'yes' to confirm: ",
+            cause
+        ),
+    }
+}
+
+/// Quote a channel variable value for an `originate` `{...}` block
+fn originate_var_value(value: &str) -> String {
+    let escaped = value.replace(',', "\\,");
+    if escaped.contains(' ') {
+        format!("'{}'", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// Assemble `originate {vars}<url> <extension> <dialplan> <context>`.
+/// Caller ID parts left empty are omitted; URL and extension are required.
+fn build_originate_command(
+    cid_number: &str,
+    cid_name: &str,
+    url: &str,
+    extension: &str,
+    context: &str,
+    dialplan: &str,
+) -> Result<String, String> {
+    if url.is_empty() {
+        return Err("A destination URL is required".to_string());
+    }
+    if extension.is_empty() {
+        return Err("A destination extension is required".to_string());
+    }
+    if url.contains(char::is_whitespace) || extension.contains(char::is_whitespace) {
+        return Err("The URL and extension cannot contain spaces".to_string());
+    }
+    let vars: Vec<String> = [
+        ("origination_caller_id_number", cid_number),
+        ("origination_caller_id_name", cid_name),
+    ]
+    .iter()
+    .filter(|(_, value)| !value.is_empty())
+    .map(|(name, value)| format!("{}={}", name, originate_var_value(value)))
+    .collect();
+    let vars = if vars.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", vars.join(","))
+    };
+    Ok(format!(
+        "originate {}{} {} {} {}",
+        vars,
+        url,
+        extension,
+        if dialplan.is_empty() { "XML" } else { dialplan },
+        if context.is_empty() {
+            "default"
+        } else {
+            context
+        },
+    ))
+}
+
+/// `/originate-wizard`: ask for each part of an originate, show the
+/// assembled command and return it once confirmed
+fn prompt_originate() -> Option<String> {
+    let cid_number = prompt_line("Caller ID number []: ")?;
+    let cid_name = prompt_line("Caller ID name []: ")?;
+    let url = prompt_line("Destination URL (e.g. user/1000): ")?;
+    let extension = prompt_line("Destination extension (e.g. 9196 or &park()): ")?;
+    let context = prompt_line("Context [default]: ")?;
+    let dialplan = prompt_line("Dialplan [XML]: ")?;
+    match build_originate_command(
+        &cid_number,
+        &cid_name,
+        &url,
+        &extension,
+        &context,
+        &dialplan,
+    ) {
+        Ok(command) => {
+            println!("{}", command);
+            prompt_confirmation("Run this command? [y/N]: ").then_some(command)
+        }
+        Err(e) => {
+            println!("{}", e);
+            None
+        }
+    }
+}
+
+/// Prompt used when no `prompt_template` is configured
+pub const DEFAULT_PROMPT_TEMPLATE: &str = "freeswitch@{host}> ";
+
+/// Stored in the prompt channel counter until the first count arrives
+pub const CHANNEL_COUNT_UNKNOWN: u32 = u32::MAX;
+
+/// Insert `[N ch]` before the trailing `> ` of `prompt`, or append it.
+/// The count is yellow above 50 channels and red above 100.
+pub fn with_channel_count(prompt: &str, count: u32, color: bool) -> String {
+    let label = format!("[{} ch]", count);
+    let label = match count {
+        _ if !color => label,
+        101.. => label
+            .red()
+            .to_string(),
+        51..=100 => label
+            .yellow()
+            .to_string(),
+        _ => label,
+    };
+    match prompt.strip_suffix("> ") {
+        Some(base) => format!("{} {}> ", base, label),
+        None => format!("{}{} ", prompt, label),
+    }
+}
+
+/// Whether a terminal identified by `TERM`/`COLORTERM` understands the OSC 0
+/// title sequence. `COLORTERM` is only set by modern emulators, all of which
+/// do; without it, any `TERM` other than `dumb` (or the Linux console) is
+/// assumed to.
+fn supports_title(term: Option<&str>, colorterm: Option<&str>) -> bool {
+    if colorterm.is_some_and(|c| !c.is_empty()) {
+        return true;
+    }
+    term.is_some_and(|t| !t.is_empty() && t != "dumb" && t != "linux")
+}
+
+/// Set the terminal title, when stdout is a terminal that supports it
+pub fn set_title(title: &str) {
+    let term = std::env::var("TERM").ok();
+    let colorterm = std::env::var("COLORTERM").ok();
+    // Windows Terminal sets neither, but identifies itself
+    let windows_terminal = std::env::var_os("WT_SESSION").is_some();
+    if !std::io::stdout().is_terminal()
+        || !(windows_terminal || supports_title(term.as_deref(), colorterm.as_deref()))
+    {
+        return;
+    }
+    let mut stdout = std::io::stdout();
+    let _ = write!(stdout, "\x1b]0;{}\x07", title);
+    let _ = stdout.flush();
+}
+
+/// Substitute prompt tokens in `template`. `host` is passed separately
+/// because it follows the active connection after `/switch`. Unknown tokens
+/// are left as-is.
+pub fn render_prompt(template: &str, config: &AppConfig, host: &str) -> String {
+    template
+        .replace("{host}", host)
+        .replace(
+            "{port}",
+            &config
+                .port
+                .to_string(),
+        )
+        .replace(
+            "{user}",
+            config
+                .user
+                .as_deref()
+                .unwrap_or(""),
+        )
+        .replace("{profile}", &config.profile_name)
+        .replace(
+            "{log_level}",
+            config
+                .log_level
+                .as_str(),
+        )
+        .replace(
+            "{color_mode}",
+            &config
+                .color
+                .to_string(),
+        )
+}
+
+/// History file for this session: an explicit `history_file` wins, otherwise
+/// `~/.fs_cli_history`, suffixed with the profile name when
+/// `per_profile_history` is on so different servers don't share history.
+fn history_path(config: &AppConfig, home: Option<PathBuf>) -> PathBuf {
+    if let Some(path) = &config.history_file {
+        return path.clone();
+    }
+    let name = if config.per_profile_history {
+        format!(
+            ".fs_cli_history.{}",
+            config
+                .profile_name
+                .replace(std::path::is_separator, "_")
+        )
+    } else {
+        ".fs_cli_history".to_string()
+    };
+    match home {
+        Some(home) => home.join(name),
+        None => {
+            warn!("HOME is unset, saving history in current directory");
+            PathBuf::from(name)
+        }
+    }
+}
+
+/// Whether `line` duplicates an entry in `history` (oldest first) under `mode`
+fn is_history_duplicate<'a>(
+    mut history: impl DoubleEndedIterator<Item = &'a String>,
+    line: &str,
+    mode: DedupeMode,
+) -> bool {
+    match mode {
+        DedupeMode::Adjacent => history
+            .next_back()
+            .is_some_and(|entry| entry == line),
+        DedupeMode::All => history.any(|entry| entry == line),
+    }
+}
+
+/// Make F`number` run `command`, keeping any partially typed line
+fn bind_function_key(rl: &mut Editor<FsCliCompleter, FileHistory>, number: u8, command: String) {
+    rl.bind_sequence(
+        KeyEvent(KeyCode::F(number), Modifiers::NONE),
+        EventHandler::Macro(vec![
+            Cmd::Stash,
+            Cmd::Kill(Movement::WholeLine),
+            Cmd::Insert(1, command),
+            Cmd::AcceptLine,
+        ]),
+    );
+}
+
 fn setup_function_key_bindings(
     rl: &mut Editor<FsCliCompleter, FileHistory>,
     macros: &HashMap<String, String>,
@@ -69,16 +468,7 @@ fn setup_function_key_bindings(
     for i in 1..=12 {
         let key = format!("f{}", i);
         if let Some(command) = macros.get(&key) {
-            let f_key = KeyEvent(KeyCode::F(i as u8), Modifiers::NONE);
-            rl.bind_sequence(
-                f_key,
-                EventHandler::Macro(vec![
-                    Cmd::Stash,
-                    Cmd::Kill(Movement::WholeLine),
-                    Cmd::Insert(1, command.clone()),
-                    Cmd::AcceptLine,
-                ]),
-            );
+            bind_function_key(rl, i, command.clone());
         }
     }
     Ok(())
@@ -86,22 +476,33 @@ fn setup_function_key_bindings(
 
 /// Run the readline loop in a blocking thread
 pub fn run_readline_loop(
-    cmd_tx: mpsc::UnboundedSender<String>,
-    quit_tx: oneshot::Sender<()>,
-    printer_tx: oneshot::Sender<Printer>,
-    completion_tx: mpsc::UnboundedSender<CompletionRequest>,
+    channels: ReadlineChannels,
+    prompt_host: Arc<RwLock<String>>,
+    channel_count: Option<Arc<AtomicU32>>,
+    macros: HashMap<String, String>,
     config: &AppConfig,
 ) -> Result<()> {
+    let ReadlineChannels {
+        cmd_tx,
+        quit_tx,
+        printer_tx,
+        completion_tx,
+        count_tx,
+        mut macro_update_rx,
+    } = channels;
     let rl_config = rustyline::Config::builder()
         .completion_type(rustyline::CompletionType::List)
         .completion_show_all_if_ambiguous(true)
+        .history_ignore_dups(false)?
         .build();
     let mut rl = Editor::<FsCliCompleter, FileHistory>::with_config(rl_config)?;
+    if let Some(max) = config.history_max_size {
+        rl.set_max_history_size(max)?;
+    }
 
     let completer = FsCliCompleter::new(completion_tx, config.debug);
     rl.set_helper(Some(completer));
 
-    let macros = build_macros(config);
     setup_function_key_bindings(&mut rl, &macros)?;
 
     let printer = rl.create_external_printer()?;
@@ -112,38 +513,44 @@ pub fn run_readline_loop(
         warn!("Session ended before printer was delivered");
     }
 
-    let history_file = config
-        .history_file
-        .clone()
-        .unwrap_or_else(|| match dirs::home_dir() {
-            Some(mut path) => {
-                path.push(".fs_cli_history");
-                path
-            }
-            None => {
-                warn!("HOME is unset, saving history in current directory");
-                PathBuf::from(".fs_cli_history")
-            }
-        });
+    let history_file = (!config.no_history).then(|| history_path(config, dirs::home_dir()));
 
-    if history_file.exists() {
-        if let Err(e) = rl.load_history(&history_file) {
+    if let Some(history_file) = history_file
+        .as_ref()
+        .filter(|path| path.exists())
+    {
+        if let Err(e) = rl.load_history(history_file) {
             warn!("Could not load history: {}", e);
         }
     }
 
-    let prompt_host = if config.host == "localhost" {
-        gethostname()
-            .to_string_lossy()
-            .to_string()
-    } else {
-        config
-            .host
-            .clone()
-    };
-    let prompt = format!("freeswitch@{}> ", prompt_host);
+    let template = config
+        .prompt_template
+        .as_deref()
+        .unwrap_or(DEFAULT_PROMPT_TEMPLATE);
 
     loop {
+        // Bindings from sourced `/set-macro` lines; typed ones are bound below
+        while let Ok((key, command)) = macro_update_rx.try_recv() {
+            if let Some(number) = function_key_number(&key) {
+                bind_function_key(&mut rl, number, command);
+            }
+        }
+
+        // Re-read each time: /switch changes the active connection's host
+        let mut prompt = match prompt_host.read() {
+            Ok(host) => render_prompt(template, config, &host),
+            Err(_) => "freeswitch> ".to_string(),
+        };
+        // Only refreshed here: the external printer cannot redraw the prompt
+        if let Some(count) = &channel_count {
+            let count = count.load(Ordering::Relaxed);
+            if count != CHANNEL_COUNT_UNKNOWN {
+                let color = config.color != crate::commands::ColorMode::Never;
+                prompt = with_channel_count(&prompt, count, color);
+            }
+        }
+
         let result = if let Some(stashed) = rl.take_stashed_line() {
             rl.readline_with_initial(&prompt, (&stashed, ""))
         } else {
@@ -157,8 +564,18 @@ pub fn run_readline_loop(
                     continue;
                 }
 
-                if let Err(e) = rl.add_history_entry(line) {
-                    warn!("Could not add history entry: {}", e);
+                let duplicate = config.history_dedupe
+                    && is_history_duplicate(
+                        rl.history()
+                            .iter(),
+                        line,
+                        config.history_dedupe_mode,
+                    );
+                // Passwords typed on the line must not reach the history file
+                if !duplicate && !has_credentials(line) {
+                    if let Err(e) = rl.add_history_entry(line) {
+                        warn!("Could not add history entry: {}", e);
+                    }
                 }
 
                 if matches!(line, "/quit" | "/exit" | "/bye") {
@@ -167,20 +584,133 @@ pub fn run_readline_loop(
                     break;
                 }
 
-                if line == "/history" {
+                if let Some(args) = line
+                    .strip_prefix("/history")
+                    .filter(|args| args.is_empty() || args.starts_with(' '))
+                {
+                    let count = match history_count(args) {
+                        Ok(count) => count,
+                        Err(usage) => {
+                            println!("{}", usage);
+                            continue;
+                        }
+                    };
                     println!("Command History:");
                     let history = rl.history();
                     let len = history.len();
                     for (i, entry) in history
                         .iter()
                         .enumerate()
-                        .skip(len.saturating_sub(20))
+                        .skip(len.saturating_sub(count))
                     {
                         println!("  {}: {}", i + 1, entry);
                     }
                     continue;
                 }
 
+                if line == "/clear-history" {
+                    match rl.clear_history() {
+                        Ok(()) => println!("History cleared"),
+                        Err(e) => warn!("Could not clear history: {}", e),
+                    }
+                    continue;
+                }
+
+                if let Some(args) = line
+                    .strip_prefix("/history-size")
+                    .filter(|args| args.is_empty() || args.starts_with(' '))
+                {
+                    match history_size(args) {
+                        Ok(max) => match rl.set_max_history_size(max) {
+                            Ok(()) => println!("History limited to {} entries", max),
+                            Err(e) => warn!("Could not resize history: {}", e),
+                        },
+                        Err(usage) => println!("{}", usage),
+                    }
+                    continue;
+                }
+
+                // Bind now so the key works at the very next prompt; the
+                // processor still records it for /macros
+                if let Some((number, command)) = set_macro_binding(line) {
+                    bind_function_key(&mut rl, number, command.to_string());
+                }
+
+                // Profile commands prompt here, where stdin is free; the
+                // processor only applies the result
+                let mut line = line.to_string();
+                if let Some(args) = line.strip_prefix("/create-profile") {
+                    let args: Vec<&str> = args
+                        .split_whitespace()
+                        .collect();
+                    let [name] = args.as_slice() else {
+                        println!("Usage: /create-profile <name>");
+                        continue;
+                    };
+                    if config
+                        .loaded_config
+                        .is_none()
+                    {
+                        println!("No configuration file to save to");
+                        continue;
+                    }
+                    match prompt_new_profile(name) {
+                        Some(command) => line = command,
+                        None => {
+                            println!("Cancelled.");
+                            continue;
+                        }
+                    }
+                } else if line == "/originate-wizard" {
+                    match prompt_originate() {
+                        Some(command) => line = command,
+                        None => {
+                            println!("Cancelled.");
+                            continue;
+                        }
+                    }
+                } else if let Some(args) = line
+                    .strip_prefix("/hupall")
+                    .filter(|args| args.is_empty() || args.starts_with(' '))
+                {
+                    let count = request_channel_count(&count_tx);
+                    if count == Some(0) {
+                        println!("No active channels");
+                        continue;
+                    }
+                    let cause = args
+                        .split_whitespace()
+                        .next();
+                    let confirmed = prompt_line(&hupall_question(cause, count))
+                        .is_some_and(|answer| answer.eq_ignore_ascii_case("yes"));
+                    if !confirmed {
+                        println!("Cancelled.");
+                        continue;
+                    }
+                    line = format!("/hupall-force{}", args);
+                } else if let Some(name) = line.strip_prefix("/delete-profile ") {
+                    let question = format!("Delete profile '{}'? [y/N]: ", name.trim());
+                    if !prompt_confirmation(&question) {
+                        println!("Cancelled.");
+                        continue;
+                    }
+                }
+                let line = line.as_str();
+
+                if config.confirm_dangerous
+                    && !config.dry_run
+                    && is_dangerous_command(line, &config.dangerous_commands)
+                {
+                    let question = format!(
+                        "This will execute '{}' which may be destructive. Continue? [y/N]: ",
+                        line
+                    );
+                    if !prompt_confirmation(&question) {
+                        println!("Cancelled.");
+                        continue;
+                    }
+                }
+
                 if cmd_tx
                     .send(line.to_string())
                     .is_err()
@@ -204,9 +734,266 @@ pub fn run_readline_loop(
         }
     }
 
-    if let Err(e) = rl.save_history(&history_file) {
-        warn!("Could not save history: {}", e);
+    if let Some(history_file) = &history_file {
+        if let Err(e) = rl.save_history(history_file) {
+            warn!("Could not save history: {}", e);
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::ColorMode;
+    use crate::config::ProfileConfig;
+
+    #[test]
+    fn build_originate_command_with_caller_id() {
+        assert_eq!(
+            build_originate_command("1000", "Front Desk", "user/1001", "9196", "", ""),
+            Ok("originate {origination_caller_id_number=1000,origination_caller_id_name='Front Desk'}user/1001 9196 XML default".to_string())
+        );
+    }
+
+    #[test]
+    fn build_originate_command_defaults_and_validation() {
+        assert_eq!(
+            build_originate_command(
+                "",
+                "",
+                "sofia/gateway/gw/5551234",
+                "&park()",
+                "public",
+                "inline"
+            ),
+            Ok("originate sofia/gateway/gw/5551234 &park() inline public".to_string())
+        );
+        assert!(build_originate_command("", "", "", "9196", "", "").is_err());
+        assert!(build_originate_command("", "", "user/1001", "", "", "").is_err());
+        assert!(build_originate_command("", "", "user/1001 x", "9196", "", "").is_err());
+    }
+
+    #[test]
+    fn originate_var_value_escapes_commas() {
+        assert_eq!(originate_var_value("Smith, John"), "'Smith\\, John'");
+        assert_eq!(originate_var_value("1000"), "1000");
+    }
+
+    #[test]
+    fn history_count_default_and_cap() {
+        assert_eq!(history_count(""), Ok(20));
+        assert_eq!(history_count(" 5"), Ok(5));
+        assert_eq!(history_count("5000"), Ok(1000));
+        assert!(history_count("all").is_err());
+    }
+
+    #[test]
+    fn history_size_requires_a_count() {
+        assert_eq!(history_size(" 50"), Ok(50));
+        assert!(history_size("").is_err());
+        assert!(history_size(" lots").is_err());
+    }
+
+    #[test]
+    fn set_macro_binding_parses_valid_lines() {
+        assert_eq!(
+            set_macro_binding("/set-macro F3 sofia status"),
+            Some((3, "sofia status"))
+        );
+        assert_eq!(set_macro_binding("/set-macro f13 status"), None);
+        assert_eq!(set_macro_binding("/set-macro f3"), None);
+        assert_eq!(set_macro_binding("/set-macros f3 status"), None);
+    }
+
+    #[test]
+    fn function_key_numbers() {
+        assert_eq!(function_key_number("f1"), Some(1));
+        assert_eq!(function_key_number("F12"), Some(12));
+        assert_eq!(function_key_number("f0"), None);
+        assert_eq!(function_key_number("f13"), None);
+        assert_eq!(function_key_number("x3"), None);
+        assert_eq!(function_key_number("f"), None);
+        assert_eq!(function_key_number("f01"), None);
+        assert_eq!(function_key_number("f+1"), None);
+        assert_eq!(function_key_number(""), None);
+    }
+
+    #[test]
+    fn title_support_from_env() {
+        assert!(supports_title(Some("xterm-256color"), None));
+        assert!(supports_title(Some("dumb"), Some("truecolor")));
+        assert!(!supports_title(Some("dumb"), None));
+        assert!(!supports_title(Some("linux"), Some("")));
+        assert!(!supports_title(None, None));
+    }
+
+    fn config() -> AppConfig {
+        let mut config = ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        config.port = 8022;
+        config.user = Some("admin@default".to_string());
+        config.profile_name = "prod".to_string();
+        config.color = ColorMode::Line;
+        config
+    }
+
+    #[test]
+    fn hupall_question_with_and_without_count() {
+        assert_eq!(
+            hupall_question(None, Some(3)),
+            "This will hang up 3 active channels with cause NORMAL_CLEARING. Type 'yes' to confirm: "
+        );
+        assert_eq!(
+            hupall_question(Some("user_busy"), None),
+            "This will hang up every active channel with cause USER_BUSY. Type 'yes' to confirm: "
+        );
+    }
+
+    #[test]
+    fn build_macros_defaults_cover_all_function_keys() {
+        let mut config = config();
+        config.macros = HashMap::new();
+        let macros = build_macros(&config);
+        assert_eq!(macros.len(), 12);
+        for n in 1..=12 {
+            assert!(macros.contains_key(&format!("f{}", n)), "f{}", n);
+        }
+        assert_eq!(macros, get_default_fnkeys());
+    }
+
+    #[test]
+    fn build_macros_overrides_and_normalizes_keys() {
+        let mut config = config();
+        config.macros = HashMap::from([
+            ("f1".to_string(), "status".to_string()),
+            ("F5".to_string(), "show registrations".to_string()),
+            ("ctrl-x".to_string(), "hupall".to_string()),
+        ]);
+        let macros = build_macros(&config);
+        assert_eq!(macros["f1"], "status");
+        assert_eq!(macros["f5"], "show registrations");
+        assert!(!macros.contains_key("F5"));
+        assert_eq!(macros["ctrl-x"], "hupall");
+        assert_eq!(macros["f2"], get_default_fnkeys()["f2"]);
+        assert_eq!(macros.len(), 13);
+    }
+
+    #[test]
+    fn history_path_per_profile() {
+        let home = Some(PathBuf::from("/home/op"));
+        let mut config = config();
+        assert_eq!(
+            history_path(&config, home.clone()),
+            PathBuf::from("/home/op/.fs_cli_history.prod")
+        );
+        config.profile_name = "lab".to_string();
+        assert_eq!(
+            history_path(&config, home.clone()),
+            PathBuf::from("/home/op/.fs_cli_history.lab")
+        );
+        config.profile_name = "../etc".to_string();
+        assert_eq!(
+            history_path(&config, home),
+            PathBuf::from("/home/op/.fs_cli_history..._etc")
+        );
+    }
+
+    #[test]
+    fn history_path_shared_or_explicit() {
+        let home = Some(PathBuf::from("/home/op"));
+        let mut config = config();
+        config.per_profile_history = false;
+        assert_eq!(
+            history_path(&config, home.clone()),
+            PathBuf::from("/home/op/.fs_cli_history")
+        );
+        config.per_profile_history = true;
+        config.history_file = Some(PathBuf::from("/tmp/hist"));
+        assert_eq!(history_path(&config, home), PathBuf::from("/tmp/hist"));
+    }
+
+    #[test]
+    fn history_duplicate_modes() {
+        let history: Vec<String> = ["status", "show channels", "version"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(is_history_duplicate(
+            history.iter(),
+            "version",
+            DedupeMode::Adjacent
+        ));
+        assert!(!is_history_duplicate(
+            history.iter(),
+            "status",
+            DedupeMode::Adjacent
+        ));
+        assert!(is_history_duplicate(
+            history.iter(),
+            "status",
+            DedupeMode::All
+        ));
+        assert!(!is_history_duplicate(
+            history.iter(),
+            "uptime",
+            DedupeMode::All
+        ));
+        assert!(!is_history_duplicate(
+            [].iter(),
+            "status",
+            DedupeMode::Adjacent
+        ));
+    }
+
+    #[test]
+    fn render_prompt_default_template() {
+        assert_eq!(
+            render_prompt(DEFAULT_PROMPT_TEMPLATE, &config(), "pbx1"),
+            "freeswitch@pbx1> "
+        );
+    }
+
+    #[test]
+    fn render_prompt_all_tokens() {
+        let template = "{user} {host}:{port} [{profile}] {log_level}/{color_mode}> ";
+        assert_eq!(
+            render_prompt(template, &config(), "pbx1"),
+            "admin@default pbx1:8022 [prod] debug/line> "
+        );
+    }
+
+    #[test]
+    fn render_prompt_missing_and_unknown_tokens() {
+        let mut config = config();
+        config.user = None;
+        assert_eq!(
+            render_prompt("{user}@{host} {nope}> ", &config, "pbx1"),
+            "@pbx1 {nope}> "
+        );
+    }
+
+    #[test]
+    fn channel_count_in_prompt() {
+        assert_eq!(
+            with_channel_count("freeswitch@pbx1> ", 3, false),
+            "freeswitch@pbx1 [3 ch]> "
+        );
+        assert_eq!(with_channel_count("fs>", 0, false), "fs>[0 ch] ");
+    }
+
+    #[test]
+    fn channel_count_colors() {
+        colored::control::set_override(true);
+        assert_eq!(with_channel_count("fs> ", 50, true), "fs [50 ch]> ");
+        assert!(with_channel_count("fs> ", 51, true).contains("\x1b[33m"));
+        assert!(with_channel_count("fs> ", 101, true).contains("\x1b[31m"));
+    }
+
+    #[test]
+    fn render_prompt_without_tokens() {
+        assert_eq!(render_prompt("fs> ", &config(), "pbx1"), "fs> ");
+    }
+}
@@ -2,19 +2,26 @@
 //!
 //! Owns the main select! loop, event consumer task, and reconnection logic.
 
-use crate::channel_info::ChannelProvider;
+use crate::channel_info::{ChannelProvider, ChannelsResponse};
 use crate::commands::CommandProcessor;
 use crate::config::AppConfig;
+use crate::connections::{parse_host_port, ConnectionManager, PRIMARY_CONNECTION};
 use crate::console_complete::get_console_complete;
 use crate::esl_debug::EslDebugLevel;
-use crate::log_display::{display_log_event, is_log_event};
+use crate::event_manager::{format_job_done, take_finished_job, PendingJobs};
+use crate::log_display::{
+    display_log_event, display_repeat_run, is_log_event, LogDeduper, LogRateLimiter, LogSettings,
+};
 use crate::printer::Printer;
-use crate::readline::{build_macros, parse_function_key, run_readline_loop, CompletionRequest};
+use crate::readline::{
+    parse_function_key, prompt_host, run_readline_loop, set_title, ChannelCountRequest,
+    CompletionRequest, ReadlineChannels, CHANNEL_COUNT_UNKNOWN,
+};
 use crate::{
-    connect_retry_forever, enable_logging, is_connection_error, is_permission_denied,
-    subscribe_heartbeat, subscribe_to_events,
+    connect_retrying, connect_to_freeswitch, enable_logging, format_host_port, is_connection_error,
+    is_permission_denied, subscribe_heartbeat, subscribe_to_events,
 };
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use colored::Colorize;
 use crossterm::{
     cursor::MoveTo,
@@ -22,17 +29,34 @@ use crossterm::{
     ExecutableCommand,
 };
 use freeswitch_esl_tokio::{
-    ConnectionStatus, EslClient, EslEventStream, EslEventType, HeaderLookup,
+    ConnectionStatus, DisconnectReason, EslClient, EslEvent, EslEventStream, EslEventType,
+    HeaderLookup,
 };
-use std::collections::HashMap;
 use std::io::{self, Write};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinHandle;
-use tokio::time::Duration;
+use tokio::time::{timeout, Duration, Timeout};
 use tracing::{debug, error, info, warn};
 
 const LIVENESS_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// `/wait-event` timeout when none is given
+const DEFAULT_WAIT_EVENT_TIMEOUT_MS: u64 = 30_000;
+
+/// Slot for an outstanding `/wait-event`, shared with every event consumer.
+/// The first consumer to see a matching event takes the sender and fires it.
+type EventWaiter = Arc<Mutex<Option<(EslEventType, oneshot::Sender<EslEvent>)>>>;
+
+/// An outstanding `/wait-event`, awaited from the command loop
+struct PendingWait {
+    event_name: String,
+    result: Pin<Box<Timeout<oneshot::Receiver<EslEvent>>>>,
+}
+
 #[cfg(unix)]
 fn save_terminal_state() -> Option<libc::termios> {
     use std::mem::MaybeUninit;
@@ -54,40 +78,100 @@ fn restore_terminal_state(termios: &libc::termios) {
 }
 
 /// Why the command loop exited
-enum SessionEnd {
+#[derive(Debug)]
+pub enum SessionEnd {
     Quit,
-    /// Connection lost. A liveness timeout (heartbeats stopped on a connection
-    /// that had them) arrives here too, via `DisconnectReason::HeartbeatExpired`
-    /// stringified into the reason — treated like any disconnect, so it honors
-    /// `--reconnect`. Liveness is only ever enabled when a HEARTBEAT
-    /// subscription succeeded, so a timeout means a genuinely stalled socket.
+    /// No server traffic within the liveness timeout. Treated like any
+    /// disconnect, so it honors `--reconnect`. Liveness is only ever enabled
+    /// when a HEARTBEAT subscription succeeded, so a timeout means a genuinely
+    /// stalled socket.
+    HeartbeatTimeout(Duration),
+    /// Connection lost
     Disconnected(Option<String>),
 }
 
-/// Run interactive CLI mode with reconnection support
+impl std::fmt::Display for SessionEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionEnd::Quit => write!(f, "User requested exit"),
+            SessionEnd::HeartbeatTimeout(timeout) => write!(
+                f,
+                "Heartbeat timeout (no server traffic for {}s)",
+                timeout.as_secs()
+            ),
+            SessionEnd::Disconnected(Some(reason)) => {
+                write!(f, "Connection dropped: {}", reason)
+            }
+            SessionEnd::Disconnected(None) => write!(f, "Connection dropped"),
+        }
+    }
+}
+
+/// Run interactive CLI mode with reconnection support. Returns how the
+/// session ended; never a disconnect when `--reconnect` is set, but an error
+/// once `--max-reconnect-attempts` runs out.
 pub async fn run_interactive_mode(
     mut client: EslClient,
     mut events: EslEventStream,
     config: &AppConfig,
-) -> Result<()> {
-    let mut processor = CommandProcessor::new(config.color, config.debug);
+) -> Result<SessionEnd> {
+    let mut processor = CommandProcessor::new(config);
+    let log_settings = LogSettings::from_config(config)?;
 
     let (cmd_tx, mut cmd_rx) = mpsc::unbounded_channel::<String>();
+    processor.set_source_tx(cmd_tx.clone());
+    let (macro_update_tx, macro_update_rx) = mpsc::unbounded_channel();
+    processor.set_macro_update_tx(macro_update_tx);
     let (quit_tx, mut quit_rx) = oneshot::channel::<()>();
     let (printer_tx, printer_rx) = oneshot::channel::<Printer>();
     let (completion_tx, mut completion_rx) = mpsc::unbounded_channel::<CompletionRequest>();
+    let (count_tx, mut count_rx) = mpsc::unbounded_channel::<ChannelCountRequest>();
 
     setup_subscriptions(&client, config).await;
     println!("FreeSWITCH CLI ready. Type 'help' for commands, '/quit' to exit.\n");
-
-    let macros = build_macros(config);
+    if config.dry_run {
+        let banner = "=== DRY Run mode active — commands will NOT be sent to FreeSWITCH ===";
+        if config.color == crate::commands::ColorMode::Never {
+            println!("{}\n", banner);
+        } else {
+            println!(
+                "{}\n",
+                banner
+                    .yellow()
+                    .bold()
+            );
+        }
+    }
 
     #[cfg(unix)]
     let original_termios = save_terminal_state();
 
+    let shared_prompt_host = Arc::new(RwLock::new(prompt_host(&config.host)));
+    let mut connections =
+        ConnectionManager::new(&config.host, config.port, shared_prompt_host.clone());
+
+    let channel_count = config
+        .show_channel_count
+        .then(|| Arc::new(AtomicU32::new(CHANNEL_COUNT_UNKNOWN)));
+
     let config_clone = config.clone();
+    let readline_count = channel_count.clone();
+    let macros = processor.macros();
     let readline_handle = tokio::task::spawn_blocking(move || {
-        run_readline_loop(cmd_tx, quit_tx, printer_tx, completion_tx, &config_clone)
+        run_readline_loop(
+            ReadlineChannels {
+                cmd_tx,
+                quit_tx,
+                printer_tx,
+                completion_tx,
+                count_tx,
+                macro_update_rx,
+            },
+            shared_prompt_host,
+            readline_count,
+            macros,
+            &config_clone,
+        )
     });
 
     let printer = match printer_rx.await {
@@ -99,26 +183,62 @@ pub async fn run_interactive_mode(
     };
     processor.set_printer(printer.clone());
 
-    let channel_provider = ChannelProvider::new(config.max_auto_complete_uuid);
+    let channel_provider = ChannelProvider::new(config.max_auto_complete_uuid, config.cache_ttl_ms);
+
+    let event_waiter: EventWaiter = Arc::new(Mutex::new(None));
 
     let mut ctx = CommandLoopCtx {
         processor: &processor,
-        macros: &macros,
         channel_provider: &channel_provider,
+        connections: &mut connections,
+        printer: &printer,
         config,
         cmd_rx: &mut cmd_rx,
         quit_rx: &mut quit_rx,
         completion_rx: &mut completion_rx,
+        count_rx: &mut count_rx,
+        event_waiter: event_waiter.clone(),
+        pending_wait: None,
+        log_settings: log_settings.clone(),
+        idle_timed_out: false,
     };
 
+    // Set when --max-reconnect-attempts runs out; ends the session with Err
+    let mut reconnect_error = None;
+
+    let title = format!("freeswitch@{}", format_host_port(&config.host, config.port));
+
     // Reconnection loop — each iteration is one connection session
     let session_result = loop {
-        let mut event_task =
-            spawn_event_consumer(events, printer.clone(), config.color, config.debug);
+        if config.set_terminal_title {
+            set_title(&title);
+        }
+        let mut event_task = spawn_event_consumer(
+            events,
+            printer.clone(),
+            config.color,
+            log_settings.clone(),
+            config.debug,
+            event_waiter.clone(),
+            processor.pending_jobs(),
+        );
+
+        let count_task = channel_count
+            .as_ref()
+            .map(|count| {
+                spawn_channel_counter(
+                    client.clone(),
+                    count.clone(),
+                    Duration::from_millis(config.channel_count_interval_ms),
+                )
+            });
 
         let result = run_command_loop(&client, &mut ctx, &mut event_task).await;
 
         event_task.abort();
+        if let Some(task) = count_task {
+            task.abort();
+        }
 
         let dropped = client.dropped_event_count();
         if dropped > 0 {
@@ -127,37 +247,50 @@ pub async fn run_interactive_mode(
 
         match result {
             SessionEnd::Quit => {
+                ctx.processor
+                    .unschedule_all();
+                ctx.connections
+                    .disconnect_all()
+                    .await;
                 client
                     .disconnect()
                     .await
                     .ok();
-                break Ok(());
+                break SessionEnd::Quit;
             }
-            SessionEnd::Disconnected(reason) => {
+            end => {
                 if !config.reconnect {
-                    let msg = match &reason {
-                        Some(r) => format!("Connection to FreeSWITCH lost: {}", r),
-                        None => "Connection to FreeSWITCH lost".to_string(),
-                    };
-                    break Err(anyhow::anyhow!(msg));
+                    break end;
                 }
-                match &reason {
-                    Some(r) => warn!("Connection lost ({}), reconnecting...", r),
-                    None => warn!("Connection lost, reconnecting..."),
+                warn!("{}, reconnecting...", end);
+                if config.set_terminal_title {
+                    set_title(&format!("[disconnected] {}", title));
                 }
-                let (new_client, new_events) = connect_retry_forever(config).await;
+                let (new_client, new_events) = match connect_retrying(config).await {
+                    Ok(pair) => pair,
+                    Err(e) => {
+                        reconnect_error = Some(e);
+                        break end;
+                    }
+                };
                 info!("Reconnected successfully");
                 client = new_client;
                 events = new_events;
                 setup_subscriptions(&client, config).await;
+                ctx.processor
+                    .restore_subscriptions(&client)
+                    .await;
                 continue;
             }
         }
     };
 
     readline_handle.abort();
+    if config.set_terminal_title {
+        set_title("");
+    }
 
-    if session_result.is_err() {
+    if !matches!(session_result, SessionEnd::Quit) || ctx.idle_timed_out {
         // The readline thread is blocked inside rl.readline() and cannot be
         // interrupted. Restore the terminal ourselves (rustyline won't get the
         // chance) and return immediately; main.rs will call process::exit which
@@ -166,7 +299,10 @@ pub async fn run_interactive_mode(
         if let Some(ref termios) = original_termios {
             restore_terminal_state(termios);
         }
-        return session_result;
+        if let Some(e) = reconnect_error {
+            return Err(e);
+        }
+        return Ok(session_result);
     }
 
     // Clean exit: readline already broke its loop (user typed /quit or EOF),
@@ -177,7 +313,7 @@ pub async fn run_interactive_mode(
         }
     }
 
-    session_result
+    Ok(session_result)
 }
 
 /// Subscribe to the events this session needs and enable the idle-liveness
@@ -262,18 +398,116 @@ fn format_channel_event(
     })
 }
 
+/// One-line summary for events added with `/subscribe`. HEARTBEAT is only
+/// there for liveness and stays silent.
+fn format_subscribed_event(
+    event: &EslEvent,
+    color_mode: crate::commands::ColorMode,
+) -> Option<String> {
+    let event_type = event.event_type()?;
+    if event_type == EslEventType::Heartbeat {
+        return None;
+    }
+    let mut line = format!("[EVENT] {}", event_type);
+    if let Some(subclass) = event.header_str("Event-Subclass") {
+        line.push_str(&format!(" {}", subclass));
+    }
+    if let Some(uuid) = event.unique_id() {
+        line.push_str(&format!(" {}", uuid));
+    }
+    Some(match color_mode {
+        crate::commands::ColorMode::Never => line,
+        _ => line
+            .cyan()
+            .to_string(),
+    })
+}
+
+/// Active channels, from `show channels count as json`
+async fn fetch_channel_count(client: &EslClient) -> Result<u32> {
+    let body = crate::commands::api_body(client, "show channels count as json").await?;
+    Ok(serde_json::from_str::<ChannelsResponse>(&body)?.row_count)
+}
+
+/// Spawn a task refreshing the prompt channel count every `period` from
+/// `show channels count as json`
+fn spawn_channel_counter(
+    client: EslClient,
+    count: Arc<AtomicU32>,
+    period: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period.max(Duration::from_millis(100)));
+        loop {
+            ticker
+                .tick()
+                .await;
+            match fetch_channel_count(&client).await {
+                Ok(active) => count.store(active, Ordering::Relaxed),
+                Err(e) => debug!("Channel count refresh failed: {}", e),
+            }
+        }
+    })
+}
+
 /// Spawn a task that consumes events and displays log/channel messages
 fn spawn_event_consumer(
     mut events: EslEventStream,
     printer: Printer,
     color_mode: crate::commands::ColorMode,
+    log_settings: LogSettings,
     debug_level: EslDebugLevel,
+    waiter: EventWaiter,
+    jobs: PendingJobs,
 ) -> JoinHandle<()> {
     tokio::spawn(async move {
-        while let Some(result) = events
-            .recv()
-            .await
-        {
+        let mut deduper = log_settings
+            .dedupe_window
+            .map(LogDeduper::new);
+        let mut rate_limiter = log_settings
+            .rate_limit
+            .map(|(limit, burst)| LogRateLimiter::new(limit, burst, Instant::now()));
+        // Shows the rate notice and flushes held lines when no new ones
+        // arrive; summarizes repeats once they stop
+        let mut log_tick = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            let result = tokio::select! {
+                result = events.recv() => match result {
+                    Some(result) => result,
+                    None => {
+                        if let Some(run) = deduper.as_mut().and_then(LogDeduper::finish) {
+                            display_repeat_run(
+                                &run,
+                                color_mode,
+                                &log_settings,
+                                rate_limiter.as_mut(),
+                                &printer,
+                            );
+                        }
+                        break;
+                    }
+                },
+                _ = log_tick.tick(), if rate_limiter.is_some() || deduper.is_some() => {
+                    let expired = deduper
+                        .as_mut()
+                        .and_then(|deduper| deduper.expired(Instant::now()));
+                    if let Some(run) = expired {
+                        display_repeat_run(
+                            &run,
+                            color_mode,
+                            &log_settings,
+                            rate_limiter.as_mut(),
+                            &printer,
+                        );
+                    }
+                    if let Some(limiter) = rate_limiter.as_mut() {
+                        for line in limiter.tick(Instant::now()) {
+                            printer.print(line);
+                        }
+                    }
+                    continue;
+                }
+            };
             match result {
                 Ok(event) => {
                     if let Some(raw) = event.raw_body() {
@@ -287,9 +521,24 @@ fn spawn_event_consumer(
                     }
                     if let Some(msg) = format_channel_event(&event, color_mode) {
                         printer.print(msg);
+                    } else if let Some((command, elapsed)) = take_finished_job(&event, &jobs) {
+                        let body = event
+                            .body()
+                            .unwrap_or("");
+                        printer.print(format_job_done(body, &command, elapsed, color_mode));
                     } else if is_log_event(&event) {
-                        display_log_event(&event, color_mode, &printer);
+                        display_log_event(
+                            &event,
+                            color_mode,
+                            &log_settings,
+                            deduper.as_mut(),
+                            rate_limiter.as_mut(),
+                            &printer,
+                        );
+                    } else if let Some(msg) = format_subscribed_event(&event, color_mode) {
+                        printer.print(msg);
                     }
+                    deliver_waited_event(&waiter, event);
                 }
                 Err(e) => {
                     warn!("Event stream error: {}", e);
@@ -299,6 +548,21 @@ fn spawn_event_consumer(
     })
 }
 
+/// Hand `event` to an outstanding `/wait-event` if it is the awaited type
+fn deliver_waited_event(waiter: &EventWaiter, event: EslEvent) {
+    let Ok(mut slot) = waiter.lock() else {
+        return;
+    };
+    let wanted = slot
+        .as_ref()
+        .map(|(event_type, _)| *event_type);
+    if wanted.is_some() && event.event_type() == wanted {
+        if let Some((_, tx)) = slot.take() {
+            let _ = tx.send(event);
+        }
+    }
+}
+
 /// Session-lifetime state shared across reconnect iterations.
 ///
 /// Per-connection resources (`client`, `event_task`) are passed separately to
@@ -306,12 +570,20 @@ fn spawn_event_consumer(
 /// this struct.
 struct CommandLoopCtx<'a> {
     processor: &'a CommandProcessor,
-    macros: &'a HashMap<String, String>,
     channel_provider: &'a ChannelProvider,
+    connections: &'a mut ConnectionManager,
+    printer: &'a Printer,
     config: &'a AppConfig,
     cmd_rx: &'a mut mpsc::UnboundedReceiver<String>,
     quit_rx: &'a mut oneshot::Receiver<()>,
     completion_rx: &'a mut mpsc::UnboundedReceiver<CompletionRequest>,
+    count_rx: &'a mut mpsc::UnboundedReceiver<ChannelCountRequest>,
+    event_waiter: EventWaiter,
+    pending_wait: Option<PendingWait>,
+    log_settings: LogSettings,
+    /// Set when `--idle-timeout` ended the session; readline is then still
+    /// blocked waiting for input
+    idle_timed_out: bool,
 }
 
 /// Main command processing select! loop for one connection session.
@@ -320,6 +592,19 @@ async fn run_command_loop(
     ctx: &mut CommandLoopCtx<'_>,
     event_task: &mut JoinHandle<()>,
 ) -> SessionEnd {
+    // Only user input counts as activity, not server events such as HEARTBEAT
+    let idle_timeout = ctx
+        .config
+        .idle_timeout_ms
+        .map(Duration::from_millis);
+    let idle = tokio::time::sleep(idle_timeout.unwrap_or_default());
+    tokio::pin!(idle);
+    let reset_idle = |idle: Pin<&mut tokio::time::Sleep>| {
+        if let Some(timeout) = idle_timeout {
+            idle.reset(tokio::time::Instant::now() + timeout);
+        }
+    };
+
     loop {
         tokio::select! {
             result = &mut *event_task => {
@@ -329,6 +614,9 @@ async fn run_command_loop(
                     Ok(()) => {}
                 }
                 return match client.status() {
+                    ConnectionStatus::Disconnected(DisconnectReason::HeartbeatExpired { .. }) => {
+                        SessionEnd::HeartbeatTimeout(LIVENESS_TIMEOUT)
+                    }
                     ConnectionStatus::Disconnected(r) => {
                         SessionEnd::Disconnected(Some(r.to_string()))
                     }
@@ -336,36 +624,169 @@ async fn run_command_loop(
                 };
             }
             Some(command) = ctx.cmd_rx.recv() => {
-                if let Some(end) = handle_command_line(ctx.processor, ctx.macros, client, command).await {
+                reset_idle(idle.as_mut());
+                cancel_wait_event(ctx);
+                if let Some(end) = handle_command_line(ctx, client, command).await {
                     return end;
                 }
             }
             Some(request) = ctx.completion_rx.recv() => {
+                reset_idle(idle.as_mut());
                 let completions = get_console_complete(
-                    client, &request.line, request.pos,
+                    ctx.connections.active_client(client), &request.line, request.pos,
                     ctx.config.debug, ctx.channel_provider,
                 ).await;
                 let _ = request.response_tx.send(completions);
             }
+            Some(request) = ctx.count_rx.recv() => {
+                let count = fetch_channel_count(ctx.connections.active_client(client))
+                    .await
+                    .map_err(|e| debug!("Channel count failed: {}", e))
+                    .ok();
+                let _ = request.response_tx.send(count);
+            }
+            outcome = next_wait_outcome(&mut ctx.pending_wait), if ctx.pending_wait.is_some() => {
+                finish_wait_event(ctx, outcome);
+            }
             _ = &mut *ctx.quit_rx => {
                 return SessionEnd::Quit;
             }
+            _ = &mut idle, if idle_timeout.is_some() => {
+                ctx.printer
+                    .print("Session idle timeout — disconnecting".to_string());
+                ctx.idle_timed_out = true;
+                return SessionEnd::Quit;
+            }
         }
     }
 }
 
+/// Resolve when the pending `/wait-event` gets its event (`Some`) or times
+/// out (`None`). Never resolves without a pending wait.
+async fn next_wait_outcome(pending: &mut Option<PendingWait>) -> Option<EslEvent> {
+    match pending {
+        Some(wait) => wait
+            .result
+            .as_mut()
+            .await
+            .ok()
+            .and_then(Result::ok),
+        None => std::future::pending().await,
+    }
+}
+
+/// Parse `/wait-event <event_type> [timeout_ms]` and arm the waiter
+fn start_wait_event(ctx: &mut CommandLoopCtx<'_>, args: &[&str]) -> Result<String> {
+    let (name, timeout_ms) = match args {
+        [name] => (*name, DEFAULT_WAIT_EVENT_TIMEOUT_MS),
+        [name, ms] => (
+            *name,
+            ms.parse::<u64>()
+                .map_err(|_| anyhow!("Invalid timeout: {}", ms))?,
+        ),
+        _ => return Ok("Usage: /wait-event <event_type> [timeout_ms]".to_string()),
+    };
+    let event_name = name.to_uppercase();
+    let event_type: EslEventType = event_name
+        .parse()
+        .map_err(|_| anyhow!("Unknown event type: {}", name))?;
+
+    let (tx, rx) = oneshot::channel();
+    if let Ok(mut slot) = ctx
+        .event_waiter
+        .lock()
+    {
+        *slot = Some((event_type, tx));
+    }
+    ctx.pending_wait = Some(PendingWait {
+        event_name: event_name.clone(),
+        result: Box::pin(timeout(Duration::from_millis(timeout_ms), rx)),
+    });
+    Ok(format!(
+        "Waiting up to {}ms for {}...",
+        timeout_ms, event_name
+    ))
+}
+
+/// Report the outcome of a `/wait-event` and disarm the waiter
+fn finish_wait_event(ctx: &mut CommandLoopCtx<'_>, outcome: Option<EslEvent>) {
+    let Some(wait) = ctx
+        .pending_wait
+        .take()
+    else {
+        return;
+    };
+    if let Ok(mut slot) = ctx
+        .event_waiter
+        .lock()
+    {
+        *slot = None;
+    }
+    let message = match outcome {
+        Some(event) => match event.unique_id() {
+            Some(uuid) => format!("Received {} for {}", wait.event_name, uuid),
+            None => format!("Received {}", wait.event_name),
+        },
+        None => format!("Timed out waiting for {}", wait.event_name),
+    };
+    ctx.processor
+        .print_message(&message);
+}
+
+/// A new command abandons any outstanding `/wait-event`
+fn cancel_wait_event(ctx: &mut CommandLoopCtx<'_>) {
+    let Some(wait) = ctx
+        .pending_wait
+        .take()
+    else {
+        return;
+    };
+    if let Ok(mut slot) = ctx
+        .event_waiter
+        .lock()
+    {
+        *slot = None;
+    }
+    ctx.processor
+        .print_message(&format!("Stopped waiting for {}", wait.event_name));
+}
+
 /// Dispatch one line from the readline thread. Returns `Some(end)` if the
 /// session should terminate, `None` to continue.
 async fn handle_command_line(
-    processor: &CommandProcessor,
-    macros: &HashMap<String, String>,
+    ctx: &mut CommandLoopCtx<'_>,
     client: &EslClient,
     command: String,
 ) -> Option<SessionEnd> {
     if command.starts_with('/') {
+        if handle_connection_command(ctx, &command).await {
+            return None;
+        }
+        let parts: Vec<&str> = command
+            .split_whitespace()
+            .collect();
+        if parts[0] == "/wait-event" {
+            match start_wait_event(ctx, &parts[1..]) {
+                Ok(message) => ctx
+                    .processor
+                    .print_message(&message),
+                Err(e) => ctx
+                    .processor
+                    .handle_error(e),
+            }
+            return None;
+        }
         return match command.as_str() {
             "/help" => {
-                processor.show_help(macros);
+                ctx.processor
+                    .show_help();
+                None
+            }
+            "/refresh" => {
+                ctx.channel_provider
+                    .invalidate_cache();
+                ctx.processor
+                    .print_message("Channel cache cleared");
                 None
             }
             "/clear" => {
@@ -380,15 +801,186 @@ async fn handle_command_line(
                 }
                 None
             }
-            _ => execute_with_disconnect_check(client, processor, &command).await,
+            _ => execute_on_active(ctx, client, &command).await,
         };
     }
     if command == "help" {
-        processor.show_help(macros);
+        ctx.processor
+            .show_help();
         return None;
     }
-    let effective = parse_function_key(&command, macros).unwrap_or(command);
-    execute_with_disconnect_check(client, processor, &effective).await
+    let effective = parse_function_key(
+        &command,
+        &ctx.processor
+            .macros(),
+    )
+    .unwrap_or(command);
+    execute_on_active(ctx, client, &effective).await
+}
+
+/// Handle `/connect`, `/switch`, `/disconnect` and `/connections`.
+/// Returns `false` when `command` is not one of them.
+async fn handle_connection_command(ctx: &mut CommandLoopCtx<'_>, command: &str) -> bool {
+    let parts: Vec<&str> = command
+        .split_whitespace()
+        .collect();
+    let result = match parts.as_slice() {
+        ["/connect", name, address] => connect_named(ctx, name, address, None).await,
+        ["/connect", name, address, password] => {
+            connect_named(ctx, name, address, Some(*password)).await
+        }
+        ["/connect", ..] => Ok("Usage: /connect <name> <host:port> [password]".to_string()),
+        ["/switch", name] => ctx
+            .connections
+            .switch(name)
+            .map(|()| format!("Switched to '{}'", name))
+            .map_err(|e| anyhow!(e)),
+        ["/switch", ..] => Ok("Usage: /switch <name>".to_string()),
+        ["/disconnect", name] if *name == PRIMARY_CONNECTION => Ok(format!(
+            "'{}' is the startup connection, use /quit to close it",
+            PRIMARY_CONNECTION
+        )),
+        ["/disconnect", name] => match ctx
+            .connections
+            .remove(name)
+        {
+            Some(named) => {
+                named
+                    .disconnect()
+                    .await
+                    .ok();
+                Ok(format!("Disconnected '{}'", name))
+            }
+            None => Err(anyhow!("No connection named '{}'", name)),
+        },
+        ["/disconnect", ..] => Ok("Usage: /disconnect <name>".to_string()),
+        ["/connections"] => Ok(ctx
+            .connections
+            .list()),
+        _ => return false,
+    };
+    let (host, port) = ctx
+        .connections
+        .active_address();
+    ctx.processor
+        .set_ping_target(host, port);
+    match result {
+        Ok(message) => ctx
+            .processor
+            .print_message(&message),
+        Err(e) => ctx
+            .processor
+            .handle_error(e),
+    }
+    true
+}
+
+/// Open a secondary connection labelled `name` and make it active
+async fn connect_named(
+    ctx: &mut CommandLoopCtx<'_>,
+    name: &str,
+    address: &str,
+    password: Option<&str>,
+) -> Result<String> {
+    if name == PRIMARY_CONNECTION {
+        bail!("'{}' is reserved for the startup connection", name);
+    }
+    let (host, port) =
+        parse_host_port(address).ok_or_else(|| anyhow!("Invalid address: {}", address))?;
+
+    let mut target = ctx
+        .config
+        .clone();
+    target.host = host.clone();
+    target.port = port;
+    target.user = None;
+    if let Some(password) = password {
+        target.password = password.to_string();
+    }
+
+    let (named, events) = connect_to_freeswitch(&target).await?;
+    let event_task = spawn_event_consumer(
+        events,
+        ctx.printer
+            .clone(),
+        ctx.config
+            .color,
+        ctx.log_settings
+            .clone(),
+        ctx.config
+            .debug,
+        ctx.event_waiter
+            .clone(),
+        ctx.processor
+            .pending_jobs(),
+    );
+    if let Some(replaced) = ctx
+        .connections
+        .add(name, &host, port, named, event_task)
+    {
+        replaced
+            .disconnect()
+            .await
+            .ok();
+    }
+    Ok(format!(
+        "Connected '{}' to {} (now active)",
+        name,
+        format_host_port(&host, port)
+    ))
+}
+
+/// Execute on the active connection. A lost secondary connection is dropped
+/// and commands fall back to the primary; only the primary ends the session.
+async fn execute_on_active(
+    ctx: &mut CommandLoopCtx<'_>,
+    client: &EslClient,
+    command: &str,
+) -> Option<SessionEnd> {
+    let end = execute_with_disconnect_check(
+        ctx.connections
+            .active_client(client),
+        ctx.processor,
+        ctx.channel_provider,
+        command,
+    )
+    .await;
+    if end.is_some()
+        && !ctx
+            .connections
+            .is_primary_active()
+    {
+        let name = ctx
+            .connections
+            .active_name()
+            .to_string();
+        ctx.connections
+            .remove(&name);
+        ctx.processor
+            .handle_error(anyhow!(
+                "Connection '{}' lost, switched back to '{}'",
+                name,
+                PRIMARY_CONNECTION
+            ));
+        return None;
+    }
+    end
+}
+
+/// Commands after which cached channels for UUID completion are stale
+const CHANNEL_CHANGING_COMMANDS: &[&str] = &[
+    "uuid_hangup",
+    "uuid_kill",
+    "originate",
+    "hupall",
+    "/hupall-force",
+];
+
+fn changes_channels(command: &str) -> bool {
+    command
+        .split_whitespace()
+        .next()
+        .is_some_and(|first| CHANNEL_CHANGING_COMMANDS.contains(&first))
 }
 
 /// Execute a command and check for connection errors.
@@ -396,16 +988,92 @@ async fn handle_command_line(
 async fn execute_with_disconnect_check(
     client: &EslClient,
     processor: &CommandProcessor,
+    channel_provider: &ChannelProvider,
     command: &str,
 ) -> Option<SessionEnd> {
-    if let Err(e) = processor
+    match processor
         .execute_command(client, command)
         .await
     {
-        if is_connection_error(&e) {
+        Ok(output) if !output.failed && changes_channels(command) => {
+            channel_provider.invalidate_cache();
+        }
+        Ok(_) => {}
+        Err(e) if is_connection_error(&e) => {
             return Some(SessionEnd::Disconnected(Some(e.to_string())));
         }
-        processor.handle_error(e);
+        Err(e) => processor.handle_error(e),
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProfileConfig;
+    use crate::mock_server::{self, MockFixtures};
+
+    #[test]
+    fn changes_channels_matches_first_word() {
+        assert!(changes_channels("originate user/1000 &park"));
+        assert!(changes_channels("uuid_kill 0f2a"));
+        assert!(changes_channels("hupall NORMAL_CLEARING"));
+        assert!(!changes_channels("show channels"));
+        assert!(!changes_channels("uuid_killer"));
+    }
+
+    #[tokio::test]
+    async fn idle_timeout_ends_session_without_input() {
+        let addr = mock_server::start(0, "ClueCon".to_string(), MockFixtures::default())
+            .await
+            .unwrap();
+        let (client, _events) = EslClient::connect("127.0.0.1", addr.port(), "ClueCon")
+            .await
+            .unwrap();
+        let mut config = ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        config.idle_timeout_ms = Some(50);
+
+        let processor = CommandProcessor::new(&config);
+        let channel_provider =
+            ChannelProvider::new(config.max_auto_complete_uuid, config.cache_ttl_ms);
+        let mut connections = ConnectionManager::new(
+            &config.host,
+            config.port,
+            Arc::new(RwLock::new(String::new())),
+        );
+        let printer = Printer::none();
+        let (_cmd_tx, mut cmd_rx) = mpsc::unbounded_channel();
+        let (_quit_tx, mut quit_rx) = oneshot::channel();
+        let (_completion_tx, mut completion_rx) = mpsc::unbounded_channel();
+        let (_count_tx, mut count_rx) = mpsc::unbounded_channel();
+        let mut ctx = CommandLoopCtx {
+            processor: &processor,
+            channel_provider: &channel_provider,
+            connections: &mut connections,
+            printer: &printer,
+            config: &config,
+            cmd_rx: &mut cmd_rx,
+            quit_rx: &mut quit_rx,
+            completion_rx: &mut completion_rx,
+            count_rx: &mut count_rx,
+            event_waiter: Arc::new(Mutex::new(None)),
+            pending_wait: None,
+            log_settings: LogSettings::from_config(&config).unwrap(),
+            idle_timed_out: false,
+        };
+        let mut event_task = tokio::spawn(std::future::pending::<()>());
+
+        let end = timeout(
+            Duration::from_secs(5),
+            run_command_loop(&client, &mut ctx, &mut event_task),
+        )
+        .await
+        .unwrap();
+        event_task.abort();
+
+        assert!(matches!(end, SessionEnd::Quit));
+        assert!(ctx.idle_timed_out);
+    }
+}
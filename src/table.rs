@@ -0,0 +1,420 @@
+//! Terminal-width-aware tabular output for fs_cli-rs
+
+use crate::commands::ColorMode;
+use colored::Colorize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Width used when the terminal size cannot be determined (piped output).
+const FALLBACK_WIDTH: usize = 120;
+
+/// Width used for word wrapping when the terminal size cannot be determined
+const WRAP_FALLBACK_WIDTH: usize = 80;
+
+/// Indent of wrapped continuation lines
+const WRAP_INDENT: &str = "    ";
+
+/// Columns are never shrunk below this many characters to fit the terminal.
+const MIN_COLUMN_WIDTH: usize = 4;
+
+const COLUMN_GAP: &str = "  ";
+
+/// Resolve the width available for table output.
+///
+/// An explicit `--output-width` wins; otherwise the terminal is queried, which
+/// fails or misreports when stdout is piped.
+pub fn output_width(override_width: Option<u16>) -> usize {
+    if let Some(width) = override_width {
+        return width as usize;
+    }
+    match crossterm::terminal::size() {
+        Ok((cols, _)) if cols > 0 => cols as usize,
+        _ => FALLBACK_WIDTH,
+    }
+}
+
+/// Terminal width for word wrapping: `--output-width`, then the terminal
+/// size, then 80 columns
+pub fn wrap_width(override_width: Option<u16>) -> usize {
+    if let Some(width) = override_width {
+        return width as usize;
+    }
+    match crossterm::terminal::size() {
+        Ok((cols, _)) if cols > 0 => cols as usize,
+        _ => WRAP_FALLBACK_WIDTH,
+    }
+}
+
+/// Wrap lines wider than `width` visible columns at whitespace, indenting
+/// continuations by four spaces. Words wider than a line are left whole so
+/// UUIDs and URLs stay copyable.
+pub fn word_wrap(text: &str, width: usize) -> String {
+    if width <= WRAP_INDENT.len() * 2 {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    for (i, line) in text
+        .split('\n')
+        .enumerate()
+    {
+        if i > 0 {
+            out.push('\n');
+        }
+        if visible_len(line) <= width {
+            out.push_str(line);
+            continue;
+        }
+        wrap_line(line, width, &mut out);
+    }
+    out
+}
+
+fn wrap_line(line: &str, width: usize, out: &mut String) {
+    let mut len = 0;
+    for token in line.split_inclusive(char::is_whitespace) {
+        let word_len = visible_len(token.trim_end());
+        if len > 0
+            && len + word_len > width
+            && !token
+                .trim()
+                .is_empty()
+        {
+            // Drop the whitespace the line would have ended with
+            while out.ends_with(|c: char| c.is_whitespace() && c != '\n') {
+                out.pop();
+            }
+            out.push('\n');
+            out.push_str(WRAP_INDENT);
+            len = WRAP_INDENT.len();
+        }
+        out.push_str(token);
+        len += visible_len(token);
+    }
+}
+
+/// Number of columns `s` occupies on screen, skipping ANSI escape sequences.
+pub fn visible_len(s: &str) -> usize {
+    let mut len = 0;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            skip_escape(&mut chars, |_| {});
+            continue;
+        }
+        len += 1;
+    }
+    len
+}
+
+/// Consume the remainder of a CSI escape sequence (the ESC is already read).
+fn skip_escape(chars: &mut impl Iterator<Item = char>, mut keep: impl FnMut(char)) {
+    if let Some(c) = chars.next() {
+        keep(c);
+        if c != '[' {
+            return;
+        }
+        for c in chars.by_ref() {
+            keep(c);
+            if ('@'..='~').contains(&c) {
+                break;
+            }
+        }
+    }
+}
+
+/// Cut `s` to `width` visible columns, marking the cut with `…`.
+fn truncate_visible(s: &str, width: usize) -> String {
+    if visible_len(s) <= width {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    let mut len = 0;
+    let mut has_escape = false;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            has_escape = true;
+            out.push(c);
+            skip_escape(&mut chars, |c| out.push(c));
+            continue;
+        }
+        if len + 1 >= width {
+            break;
+        }
+        out.push(c);
+        len += 1;
+    }
+    out.push('…');
+    if has_escape {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+fn pad_visible(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(visible_len(s));
+    format!("{}{}", s, " ".repeat(padding))
+}
+
+fn render_row<'a>(cells: impl Iterator<Item = &'a str>, widths: &[usize]) -> String {
+    cells
+        .zip(widths)
+        .map(|(cell, &w)| pad_visible(&truncate_visible(cell, w), w))
+        .collect::<Vec<_>>()
+        .join(COLUMN_GAP)
+        .trim_end()
+        .to_string()
+}
+
+/// Render `rows` under `headers` as aligned columns no wider than `max_width`.
+///
+/// Cells may already carry color; alignment only counts visible characters.
+/// When the table is too wide, the widest columns are shrunk and their cells
+/// truncated with `…`.
+pub fn format_table(
+    headers: &[&str],
+    rows: &[Vec<String>],
+    color_mode: ColorMode,
+    max_width: usize,
+) -> String {
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|h| visible_len(h))
+        .collect();
+    for row in rows {
+        for (i, cell) in row
+            .iter()
+            .enumerate()
+            .take(widths.len())
+        {
+            widths[i] = widths[i].max(visible_len(cell));
+        }
+    }
+
+    let gaps = COLUMN_GAP.len()
+        * widths
+            .len()
+            .saturating_sub(1);
+    while widths
+        .iter()
+        .sum::<usize>()
+        + gaps
+        > max_width
+    {
+        let Some((widest, width)) = widths
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by_key(|&(_, w)| w)
+        else {
+            break;
+        };
+        if width <= MIN_COLUMN_WIDTH {
+            break;
+        }
+        widths[widest] -= 1;
+    }
+
+    let mut lines = Vec::with_capacity(rows.len() + 2);
+    let header = render_row(
+        headers
+            .iter()
+            .copied(),
+        &widths,
+    );
+    lines.push(if color_mode == ColorMode::Never {
+        header
+    } else {
+        header
+            .bold()
+            .to_string()
+    });
+    lines.push(
+        widths
+            .iter()
+            .map(|&w| "─".repeat(w))
+            .collect::<Vec<_>>()
+            .join(COLUMN_GAP),
+    );
+    for row in rows {
+        lines.push(render_row(
+            row.iter()
+                .map(String::as_str),
+            &widths,
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Owned table for callers that build headers at runtime, e.g. from a
+/// response's own column names
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Render with [`format_table`], sized to the terminal unless
+    /// `max_width` is given
+    pub fn render(&self, color_mode: ColorMode, max_width: Option<usize>) -> String {
+        let headers: Vec<&str> = self
+            .headers
+            .iter()
+            .map(String::as_str)
+            .collect();
+        format_table(
+            &headers,
+            &self.rows,
+            color_mode,
+            max_width.unwrap_or_else(|| output_width(None)),
+        )
+    }
+}
+
+/// Seconds since the Unix epoch
+pub fn now_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format a number of seconds as `HH:MM:SS` (hours are not capped at 24)
+pub fn format_duration(secs: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        (secs / 60) % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_width_override_wins() {
+        assert_eq!(output_width(Some(42)), 42);
+    }
+
+    #[test]
+    fn visible_len_skips_ansi() {
+        assert_eq!(visible_len("abc"), 3);
+        assert_eq!(visible_len("\x1b[1;33mabc\x1b[0m"), 3);
+        assert_eq!(visible_len("─…"), 2);
+    }
+
+    #[test]
+    fn word_wrap_breaks_at_whitespace() {
+        let text = "Channel-Name: sofia/internal/1000@example.com Channel-State: CS_EXECUTE";
+        let wrapped = word_wrap(text, 40);
+        assert_eq!(
+            wrapped,
+            "Channel-Name:\n    sofia/internal/1000@example.com\n    Channel-State: CS_EXECUTE"
+        );
+        for line in wrapped.lines() {
+            assert!(visible_len(line) <= 40);
+        }
+    }
+
+    #[test]
+    fn word_wrap_keeps_short_lines_and_long_words() {
+        assert_eq!(word_wrap("short\nlines", 20), "short\nlines");
+        let uuid = "0f2a3c4e-1111-2222-3333-444455556666";
+        assert_eq!(word_wrap(uuid, 20), uuid);
+    }
+
+    #[test]
+    fn word_wrap_ignores_ansi_width() {
+        let colored = "\x1b[1;33mabc\x1b[0m def ghi";
+        assert_eq!(word_wrap(colored, 11), colored);
+        assert_eq!(word_wrap(colored, 10), "\x1b[1;33mabc\x1b[0m def\n    ghi");
+    }
+
+    #[test]
+    fn format_table_aligns_columns() {
+        let rows = vec![
+            vec!["1".to_string(), "alice".to_string()],
+            vec!["22".to_string(), "bob".to_string()],
+        ];
+        let out = format_table(&["ID", "Name"], &rows, ColorMode::Never, 80);
+        let lines: Vec<&str> = out
+            .lines()
+            .collect();
+        assert_eq!(lines[0], "ID  Name");
+        assert_eq!(lines[1], "──  ─────");
+        assert_eq!(lines[2], "1   alice");
+        assert_eq!(lines[3], "22  bob");
+    }
+
+    #[test]
+    fn format_table_truncates_to_width() {
+        let rows = vec![vec!["x".to_string(), "a".repeat(50)]];
+        let out = format_table(&["A", "B"], &rows, ColorMode::Never, 20);
+        for line in out.lines() {
+            assert!(visible_len(line) <= 20, "too wide: {:?}", line);
+        }
+        assert!(out.contains('…'));
+    }
+
+    fn sample_table() -> Table {
+        Table {
+            headers: vec!["name".to_string(), "state".to_string()],
+            rows: vec![
+                vec!["mod_sofia".to_string(), "running".to_string()],
+                vec!["mod_conference".to_string(), "down".to_string()],
+            ],
+        }
+    }
+
+    #[test]
+    fn table_render_pads_to_widest_cell() {
+        let out = sample_table().render(ColorMode::Never, Some(120));
+        let lines: Vec<&str> = out
+            .lines()
+            .collect();
+        assert_eq!(lines[0], "name            state");
+        assert_eq!(lines[1], "──────────────  ───────");
+        assert_eq!(lines[2], "mod_sofia       running");
+        assert_eq!(lines[3], "mod_conference  down");
+    }
+
+    #[test]
+    fn table_render_truncates_with_ellipsis() {
+        let out = sample_table().render(ColorMode::Never, Some(16));
+        for line in out.lines() {
+            assert!(visible_len(line) <= 16, "{:?}", line);
+        }
+        assert!(out.contains('…'));
+    }
+
+    #[test]
+    fn table_render_bold_headers_only_with_color() {
+        colored::control::set_override(true);
+        let colored_out = sample_table().render(ColorMode::Line, Some(120));
+        assert!(colored_out
+            .lines()
+            .next()
+            .unwrap()
+            .contains("\x1b[1m"));
+        let plain = sample_table().render(ColorMode::Never, Some(120));
+        assert!(!plain.contains('\x1b'));
+    }
+
+    #[test]
+    fn table_render_empty_rows_keeps_header() {
+        let table = Table {
+            headers: vec!["uuid".to_string()],
+            rows: Vec::new(),
+        };
+        assert_eq!(table.render(ColorMode::Never, Some(80)), "uuid\n────");
+    }
+
+    #[test]
+    fn format_duration_hh_mm_ss() {
+        assert_eq!(format_duration(0), "00:00:00");
+        assert_eq!(format_duration(3661), "01:01:01");
+        assert_eq!(format_duration(90000), "25:00:00");
+    }
+}
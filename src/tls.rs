@@ -0,0 +1,321 @@
+//! ESL over TLS (`--tls`)
+
+use crate::config::AppConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use std::io::BufReader;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{
+    ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider,
+};
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio_rustls::TlsConnector;
+use tracing::{debug, warn};
+
+/// Open a TLS connection to `config.host:config.port`
+pub async fn connect(config: &AppConfig) -> Result<TlsStream<TcpStream>> {
+    let connector = TlsConnector::from(Arc::new(client_config(config)?));
+    let server_name = ServerName::try_from(
+        config
+            .host
+            .clone(),
+    )
+    .map_err(|_| anyhow!("Invalid TLS server name: {}", config.host))?;
+
+    let tcp = TcpStream::connect((
+        config
+            .host
+            .as_str(),
+        config.port,
+    ))
+    .await?;
+    connector
+        .connect(server_name, tcp)
+        .await
+        .context("TLS handshake failed")
+}
+
+fn client_config(config: &AppConfig) -> Result<ClientConfig> {
+    let provider = Arc::new(ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+
+    let builder = if config.tls_insecure {
+        warn!("TLS certificate verification is disabled");
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoVerification(provider)))
+    } else {
+        builder.with_root_certificates(root_store(config)?)
+    };
+
+    match (&config.tls_client_cert, &config.tls_client_key) {
+        (Some(cert), Some(key)) => {
+            Ok(builder.with_client_auth_cert(load_certs(cert)?, load_key(key)?)?)
+        }
+        (None, None) => Ok(builder.with_no_client_auth()),
+        _ => bail!("A TLS client certificate needs both a certificate and a key"),
+    }
+}
+
+/// Trust `tls_ca_cert` when given, the system store otherwise
+fn root_store(config: &AppConfig) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    if let Some(ca) = &config.tls_ca_cert {
+        for cert in load_certs(ca)? {
+            roots.add(cert)?;
+        }
+        return Ok(roots);
+    }
+
+    let native = rustls_native_certs::load_native_certs();
+    for e in &native.errors {
+        debug!("Skipping system certificate: {}", e);
+    }
+    let (added, ignored) = roots.add_parsable_certificates(native.certs);
+    debug!(
+        "Loaded {} system CA certificates ({} ignored)",
+        added, ignored
+    );
+    if roots.is_empty() {
+        bail!("No system CA certificates found; use --tls-ca");
+    }
+    Ok(roots)
+}
+
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Cannot open certificate {}", path))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Invalid certificate file {}", path))?;
+    if certs.is_empty() {
+        bail!("No certificates found in {}", path);
+    }
+    Ok(certs)
+}
+
+fn load_key(path: &str) -> Result<PrivateKeyDer<'static>> {
+    let file = std::fs::File::open(path).with_context(|| format!("Cannot open key {}", path))?;
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .with_context(|| format!("Invalid key file {}", path))?
+        .ok_or_else(|| anyhow!("No private key found in {}", path))
+}
+
+/// `--tls-insecure`: accept any server certificate, still checking that the
+/// handshake is signed by it
+#[derive(Debug)]
+struct NoVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self
+                .0
+                .signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self
+                .0
+                .signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProfileConfig;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio_rustls::rustls::pki_types::PrivatePkcs8KeyDer;
+    use tokio_rustls::rustls::ServerConfig;
+    use tokio_rustls::TlsAcceptor;
+
+    /// Serve one TLS connection with a fresh self-signed certificate for
+    /// `localhost`, echoing a line back. Returns the port and the CA PEM.
+    async fn echo_server() -> (u16, String) {
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(
+            certified
+                .key_pair
+                .serialize_der(),
+        ));
+        let server_config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![certified
+                    .cert
+                    .der()
+                    .clone()],
+                key,
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let port = listener
+            .local_addr()
+            .unwrap()
+            .port();
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener
+                .accept()
+                .await
+                .unwrap();
+            if let Ok(mut tls) = acceptor
+                .accept(tcp)
+                .await
+            {
+                let mut buf = [0u8; 5];
+                if tls
+                    .read_exact(&mut buf)
+                    .await
+                    .is_ok()
+                {
+                    tls.write_all(&buf)
+                        .await
+                        .ok();
+                }
+            }
+        });
+        (
+            port,
+            certified
+                .cert
+                .pem(),
+        )
+    }
+
+    fn tls_config(port: u16) -> AppConfig {
+        let mut config = ProfileConfig::default()
+            .to_app_config()
+            .unwrap();
+        config.host = "localhost".to_string();
+        config.port = port;
+        config.tls = true;
+        config
+    }
+
+    async fn echo(mut stream: TlsStream<TcpStream>) -> Vec<u8> {
+        stream
+            .write_all(b"hello")
+            .await
+            .unwrap();
+        let mut buf = [0u8; 5];
+        stream
+            .read_exact(&mut buf)
+            .await
+            .unwrap();
+        buf.to_vec()
+    }
+
+    #[tokio::test]
+    async fn connect_with_ca_cert() {
+        let (port, ca_pem) = echo_server().await;
+        let ca_path = std::env::temp_dir().join(format!("fs_cli_tls_ca_{}.pem", port));
+        std::fs::write(&ca_path, ca_pem).unwrap();
+
+        let mut config = tls_config(port);
+        config.tls_ca_cert = Some(
+            ca_path
+                .display()
+                .to_string(),
+        );
+        let result = connect(&config).await;
+        std::fs::remove_file(&ca_path).ok();
+        assert_eq!(echo(result.unwrap()).await, b"hello");
+    }
+
+    #[tokio::test]
+    async fn connect_insecure_skips_verification() {
+        let (port, _) = echo_server().await;
+        let mut config = tls_config(port);
+        config.tls_insecure = true;
+        let stream = connect(&config)
+            .await
+            .unwrap();
+        assert_eq!(echo(stream).await, b"hello");
+    }
+
+    #[tokio::test]
+    async fn connect_rejects_untrusted_cert() {
+        let (port, _) = echo_server().await;
+        // A CA that did not sign the server certificate
+        let other = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let ca_path = std::env::temp_dir().join(format!("fs_cli_tls_other_{}.pem", port));
+        std::fs::write(
+            &ca_path,
+            other
+                .cert
+                .pem(),
+        )
+        .unwrap();
+
+        let mut config = tls_config(port);
+        config.tls_ca_cert = Some(
+            ca_path
+                .display()
+                .to_string(),
+        );
+        let result = connect(&config).await;
+        std::fs::remove_file(&ca_path).ok();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn client_cert_requires_key() {
+        let mut config = tls_config(8021);
+        config.tls_insecure = true;
+        config.tls_client_cert = Some("/nonexistent/cert.pem".to_string());
+        let err = client_config(&config).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("both a certificate and a key"));
+    }
+}